@@ -2,6 +2,7 @@ mod console_tests;
 mod iterator;
 
 use std::borrow::Cow;
+use std::io::{self, Write};
 
 use ansi_term::Style;
 use itertools::Itertools;
@@ -21,6 +22,27 @@ pub fn strip_ansi_codes(s: &str) -> String {
     strip_ansi_codes_from_strings_iterator(ansi_strings_iterator(s))
 }
 
+/// Return `s` made safe to embed in output formats other than ANSI-escaped text, such as HTML or
+/// JSON: escape sequences are removed, and any remaining control character other than tab and
+/// newline (e.g. the ESC of a truncated sequence) is replaced by its Unicode control picture, or by
+/// U+FFFD if it has none, so that it is visible but inert.
+pub fn neutralize_escape_sequences(s: &str) -> String {
+    strip_ansi_codes(s)
+        .chars()
+        .map(|c| match c {
+            '\t' | '\n' => c,
+            '\x00'..='\x1f' => {
+                char::from_u32(0x2400 + c as u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+            }
+            '\x7f' => '\u{2421}',
+            '\u{80}'..='\u{9f}' => char::REPLACEMENT_CHARACTER,
+            _ => c,
+        })
+        .collect()
+}
+
+/// Return the number of terminal columns occupied by `s`, ignoring ANSI escape sequences: wide
+/// characters, such as CJK ideographs and most emoji, occupy two columns, and combining marks none.
 pub fn measure_text_width(s: &str) -> usize {
     ansi_strings_iterator(s).fold(0, |acc, (element, is_ansi)| {
         acc + if is_ansi { 0 } else { element.width() }
@@ -100,7 +122,7 @@ pub fn truncate_str<'a>(s: &'a str, display_width: usize, tail: &str) -> Cow<'a,
 /// calculating the width. If a double-width ("fullwidth") grapheme has to be cut, it is omitted and
 /// the resulting string is *shorter* than `display_width`. But this way the result is always a
 /// prefix of the input `s`.
-pub fn truncate_str_short(s: &str, display_width: usize) -> Cow<str> {
+pub fn truncate_str_short(s: &str, display_width: usize) -> Cow<'_, str> {
     truncate_str_impl(s, display_width, "", None)
 }
 
@@ -117,6 +139,89 @@ pub fn parse_style_sections(s: &str) -> Vec<(ansi_term::Style, &str)> {
     sections
 }
 
+/// Like [`parse_style_sections`], but with each escape sequence adding to the current style, as in
+/// a terminal, rather than replacing it, unless it resets it, and with each section paired with the
+/// target of the OSC 8 hyperlink whose text it is part of, if any. This is how text colored by git,
+/// or by delta itself, is to be read.
+pub fn parse_hyperlinked_style_sections(s: &str) -> Vec<(ansi_term::Style, Option<&str>, &str)> {
+    let mut sections = Vec::new();
+    let mut curr_style = Style::default();
+    let mut curr_url = None;
+    for element in AnsiElementIterator::new(s) {
+        match element {
+            Element::Text(start, end) => sections.push((curr_style, curr_url, &s[start..end])),
+            Element::Sgr(style, _, _) => curr_style = apply_sgr(curr_style, style),
+            Element::Osc(start, end) => {
+                // An OSC 8 sequence opens a hyperlink, or closes it if its target is empty.
+                if let Some((_, url)) = s[start..end]
+                    .strip_prefix("\x1b]8;")
+                    .and_then(|osc| osc.split_once(';'))
+                {
+                    let url = url.trim_end_matches(['\x1b', '\x07']);
+                    curr_url = (!url.is_empty()).then_some(url);
+                }
+            }
+            _ => {}
+        }
+    }
+    sections
+}
+
+// Return the style in effect after an SGR sequence parsed as `sgr` is applied to `curr_style`.
+fn apply_sgr(curr_style: Style, sgr: Style) -> Style {
+    if sgr == Style::default() {
+        return sgr;
+    }
+    Style {
+        foreground: sgr.foreground.or(curr_style.foreground),
+        background: sgr.background.or(curr_style.background),
+        is_bold: sgr.is_bold || curr_style.is_bold,
+        is_dimmed: sgr.is_dimmed || curr_style.is_dimmed,
+        is_italic: sgr.is_italic || curr_style.is_italic,
+        is_underline: sgr.is_underline || curr_style.is_underline,
+        is_blink: sgr.is_blink || curr_style.is_blink,
+        is_reverse: sgr.is_reverse || curr_style.is_reverse,
+        is_hidden: sgr.is_hidden || curr_style.is_hidden,
+        is_strikethrough: sgr.is_strikethrough || curr_style.is_strikethrough,
+    }
+}
+
+/// Split `s` between graphemes into rows at most `width` columns wide. The first `indent` columns
+/// of `s` stay on the first row, and every other row starts with `prefix`, which should be
+/// `indent` columns wide, followed by the style in effect where `s` was split; a row ending with
+/// a style in effect ends with a style reset.
+pub fn wrap(s: &str, width: usize, indent: usize, prefix: &str) -> Vec<String> {
+    let mut rows = vec![String::new()];
+    let mut row_width = 0;
+    let mut curr_style = Style::default();
+    for element in AnsiElementIterator::new(s) {
+        match element {
+            Element::Text(start, end) => {
+                for grapheme in s[start..end].graphemes(true) {
+                    let grapheme_width = grapheme.width();
+                    if row_width + grapheme_width > width && row_width > indent {
+                        if curr_style != Style::default() {
+                            rows.last_mut().unwrap().push_str(ANSI_SGR_RESET);
+                        }
+                        rows.push(format!("{prefix}{}", curr_style.prefix()));
+                        row_width = indent;
+                    }
+                    rows.last_mut().unwrap().push_str(grapheme);
+                    row_width += grapheme_width;
+                }
+            }
+            Element::Sgr(style, start, end) => {
+                curr_style = apply_sgr(curr_style, style);
+                rows.last_mut().unwrap().push_str(&s[start..end]);
+            }
+            Element::Csi(start, end) | Element::Esc(start, end) | Element::Osc(start, end) => {
+                rows.last_mut().unwrap().push_str(&s[start..end])
+            }
+        }
+    }
+    rows
+}
+
 // Return the first CSI element, if any, as an `ansi_term::Style`.
 pub fn parse_first_style(s: &str) -> Option<ansi_term::Style> {
     AnsiElementIterator::new(s).find_map(|el| match el {
@@ -215,14 +320,119 @@ pub fn explain_ansi(line: &str, colorful: bool) -> String {
         .collect()
 }
 
+/// A writer that removes ANSI escape sequences from each line written to it before passing the
+/// line on to the underlying writer.
+pub struct StripAnsiWriter<'a> {
+    writer: &'a mut dyn Write,
+    buffer: Vec<u8>,
+}
+
+impl<'a> StripAnsiWriter<'a> {
+    pub fn new(writer: &'a mut dyn Write) -> Self {
+        Self {
+            writer,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn write_stripped(&mut self, end: usize) -> io::Result<()> {
+        let stripped = strip_ansi_codes(&String::from_utf8_lossy(&self.buffer[..end]));
+        self.buffer.drain(..end);
+        self.writer.write_all(stripped.as_bytes())
+    }
+}
+
+impl Write for StripAnsiWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        // Escape sequences do not span lines, so complete lines can be stripped and passed on.
+        if let Some(newline) = self.buffer.iter().rposition(|b| *b == b'\n') {
+            self.write_stripped(newline + 1)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_stripped(self.buffer.len())?;
+        self.writer.flush()
+    }
+}
+
+/// A writer that fits each line written to it into exactly `width` columns (see --grid-width)
+/// before passing the line on to the underlying writer.
+pub struct GridWriter<'a> {
+    writer: &'a mut dyn Write,
+    width: usize,
+    buffer: Vec<u8>,
+}
+
+impl<'a> GridWriter<'a> {
+    pub fn new(writer: &'a mut dyn Write, width: usize) -> Self {
+        Self {
+            writer,
+            width,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Fit and pass on the final line, if it does not end with a newline, and flush.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&self.buffer).into_owned();
+            self.buffer.clear();
+            self.writer
+                .write_all(fit_to_width(&line, self.width).as_bytes())?;
+        }
+        self.writer.flush()
+    }
+}
+
+impl Write for GridWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        while let Some(newline) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line = String::from_utf8_lossy(&self.buffer[..newline]).into_owned();
+            self.buffer.drain(..=newline);
+            let mut fitted = fit_to_width(&line, self.width);
+            fitted.push('\n');
+            self.writer.write_all(fitted.as_bytes())?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // A partial line cannot be fitted before it is complete.
+        self.writer.flush()
+    }
+}
+
+/// Truncate or pad `line` with spaces to exactly `width` columns, replacing tabs, removing carriage
+/// returns and clear-to-end-of-line sequences, and resetting its style at the end if it contains
+/// any escape sequence.
+fn fit_to_width(line: &str, width: usize) -> String {
+    let line = line
+        .replace('\t', " ")
+        .replace('\r', "")
+        .replace(ANSI_CSI_CLEAR_TO_EOL, "");
+    let mut fitted = truncate_str(&line, width, "").into_owned();
+    if fitted.contains('\x1b') {
+        fitted.push_str(ANSI_SGR_RESET);
+    }
+    let padding = width.saturating_sub(measure_text_width(&fitted));
+    fitted.push_str(&" ".repeat(padding));
+    fitted
+}
+
 #[cfg(test)]
 mod tests {
     use unicode_width::UnicodeWidthStr;
 
     // Note that src/ansi/console_tests.rs contains additional test coverage for this module.
     use super::{
-        ansi_preserving_index, ansi_preserving_slice, measure_text_width, parse_first_style,
+        ansi_preserving_index, ansi_preserving_slice, fit_to_width, measure_text_width,
+        neutralize_escape_sequences, parse_first_style, parse_hyperlinked_style_sections,
         string_starts_with_ansi_style_sequence, strip_ansi_codes, truncate_str, truncate_str_short,
+        wrap,
     };
 
     #[test]
@@ -233,6 +443,28 @@ mod tests {
         assert_eq!(strip_ansi_codes("\x1b[31mバー\x1b[0m"), "バー");
     }
 
+    #[test]
+    fn test_neutralize_escape_sequences() {
+        assert_eq!(neutralize_escape_sequences("\x1b[31mred\x1b[0m"), "red");
+        assert_eq!(
+            neutralize_escape_sequences("\x1b]8;;file:///a.rs\x1b\\a.rs\x1b]8;;\x1b\\"),
+            "a.rs"
+        );
+        assert_eq!(
+            neutralize_escape_sequences("a\tb\x07c\x7f\u{9b}"),
+            "a\tb␇c␡\u{fffd}"
+        );
+        for s in ["lone \x1b", "\x1b[31", "\x1b[31mred\x1b"] {
+            let neutralized = neutralize_escape_sequences(s);
+            assert!(
+                !neutralized.chars().any(|c| c.is_control()),
+                "{:?} -> {:?}",
+                s,
+                neutralized
+            );
+        }
+    }
+
     #[test]
     fn test_measure_text_width() {
         assert_eq!(measure_text_width("src/ansi/mod.rs"), 15);
@@ -240,6 +472,11 @@ mod tests {
         assert_eq!(measure_text_width("src/ansi/modバー.rs"), 19);
         assert_eq!(measure_text_width("\x1b[31mバー\x1b[0m"), 4);
         assert_eq!(measure_text_width("a\nb\n"), 2);
+        assert_eq!(measure_text_width("日本語"), 6);
+        // U+2764 HEAVY BLACK HEART, followed by the emoji presentation selector.
+        assert_eq!(measure_text_width("\u{2764}\u{fe0f}"), 2);
+        // "é" as "e" followed by U+0301 COMBINING ACUTE ACCENT.
+        assert_eq!(measure_text_width("e\u{301}"), 1);
     }
 
     #[test]
@@ -271,6 +508,23 @@ mod tests {
         assert_eq!(Some(expected_style), style);
     }
 
+    #[test]
+    fn test_parse_hyperlinked_style_sections() {
+        let red = ansi_term::Style::new().fg(ansi_term::Color::Red);
+        let sections = parse_hyperlinked_style_sections(
+            "\x1b[31ma\x1b[1m\x1b]8;;file:///b\x1b\\b\x1b]8;;\x1b\\c\x1b[0md",
+        );
+        assert_eq!(
+            sections,
+            vec![
+                (red, None, "a"),
+                (red.bold(), Some("file:///b"), "b"),
+                (red.bold(), None, "c"),
+                (ansi_term::Style::new(), None, "d"),
+            ]
+        );
+    }
+
     #[test]
     fn test_string_starts_with_ansi_escape_sequence() {
         assert!(!string_starts_with_ansi_style_sequence(""));
@@ -364,4 +618,28 @@ mod tests {
         assert_eq!(truncate_str_short(double, 1), "");
         assert_eq!(truncate_str_short(double, 2), double);
     }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("> abcd", 6, 2, ". "), ["> abcd"]);
+        assert_eq!(wrap("> abcdefgh", 6, 2, ". "), ["> abcd", ". efgh"]);
+        assert_eq!(wrap("> ab＃c", 5, 2, ". "), ["> ab", ". ＃c"]);
+        assert_eq!(
+            wrap("> \x1b[31mab\x1b[1mcd\x1b[0me", 4, 2, ". "),
+            ["> \x1b[31mab\x1b[1m\x1b[0m", ". \x1b[1;31mcd\x1b[0m", ". e"]
+        );
+    }
+
+    #[test]
+    fn test_fit_to_width() {
+        assert_eq!(fit_to_width("ab", 4), "ab  ");
+        assert_eq!(fit_to_width("a\tb\r", 4), "a b ");
+        assert_eq!(fit_to_width("abcdef", 4), "abcd");
+        assert_eq!(fit_to_width("ab\u{1f608}", 3), "ab ");
+        assert_eq!(
+            fit_to_width("\x1b[31mabcdef\x1b[0m", 4),
+            "\x1b[31mabcd\x1b[0m\x1b[0m"
+        );
+        assert_eq!(fit_to_width("\x1b[41mab\x1b[0K", 4), "\x1b[41mab\x1b[0m  ");
+    }
 }
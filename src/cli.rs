@@ -21,7 +21,7 @@ use crate::subcommands;
 use crate::utils;
 use crate::utils::bat::output::PagingMode;
 
-const TERM_FALLBACK_WIDTH: usize = 79;
+pub const TERM_FALLBACK_WIDTH: usize = 79;
 
 #[derive(Parser)]
 #[command(
@@ -34,6 +34,25 @@ const TERM_FALLBACK_WIDTH: usize = 79;
     max_term_width = usize::MAX,
 )]
 pub struct Opt {
+    #[arg(
+        long = "added-line-label",
+        default_value = "[added]",
+        value_name = "STRING"
+    )]
+    /// Label of added lines with --line-kind-labels.
+    ///
+    /// An empty label omits the label of those lines.
+    pub added_line_label: String,
+
+    #[arg(long = "auto-emph-colors")]
+    /// Derive the emphasis background colors from those of minus-style and plus-style.
+    ///
+    /// The "auto" background of minus-emph-style and plus-emph-style, which they have by default,
+    /// becomes a more saturated shade of the background of minus-style and plus-style respectively:
+    /// brighter for a dark background color, and darker for a light one. So only the two base
+    /// colors have to be chosen, e.g. --minus-style='normal #400020' --plus-style='normal #002040'.
+    pub auto_emph_colors: bool,
+
     #[arg(long = "blame-code-style", value_name = "STYLE")]
     /// Style string for the code section of a git blame line.
     ///
@@ -96,6 +115,53 @@ pub struct Opt {
     /// See: <https://docs.rs/chrono/latest/chrono/format/strftime/index.html>
     pub blame_timestamp_output_format: Option<String>,
 
+    #[arg(
+        long = "changed-line-label",
+        default_value = "[changed]",
+        value_name = "STRING"
+    )]
+    /// Label of changed lines with --line-kind-labels.
+    ///
+    /// An empty label omits the label of those lines.
+    pub changed_line_label: String,
+
+    #[arg(
+        long = "collapse-leading-context",
+        default_value = "0",
+        value_name = "N"
+    )]
+    /// Collapse long unchanged preambles of files into a one-line summary.
+    ///
+    /// If the first hunk of a file starts with more than N unchanged lines, these are replaced by a
+    /// summary such as "(… 57 lines …)". This is mostly useful with a large number of context lines
+    /// (e.g. git diff -U50). The default, 0, disables this.
+    pub collapse_leading_context: usize,
+
+    #[arg(
+        long = "color",
+        default_value = "always",
+        value_name = "auto|always|never",
+        value_parser = ["auto", "always", "never"]
+    )]
+    /// Whether to emit colors and other ANSI escape sequences.
+    ///
+    /// With 'always' (the default), delta emits escape sequences wherever its output is going.
+    /// With 'never', all escape sequences, including any present in the input, are removed from
+    /// the output. With 'auto', delta behaves as with 'always' if standard output is a terminal,
+    /// and as with 'never' otherwise. If the NO_COLOR environment variable is set to a non-empty
+    /// value, the default is 'never'.
+    pub color: String,
+
+    #[arg(long = "color-moved")]
+    /// Highlight blocks of lines that are moved within a hunk, like git diff --color-moved.
+    ///
+    /// A run of at least 3 consecutive removed lines is moved when the same hunk contains a run of
+    /// added lines with the same content, elsewhere than where they were removed. The lines of
+    /// both runs are painted with moved-style, without emphasis, instead of minus-style and
+    /// plus-style. This requires reading the whole input before displaying it. See also
+    /// --map-styles, to restyle the moved lines of git's own --color-moved output.
+    pub color_moved: bool,
+
     #[arg(long = "color-only")]
     /// Do not alter the input structurally in any way.
     ///
@@ -118,6 +184,20 @@ pub struct Opt {
     /// 'ul' (underline), 'ol' (overline), or the combination 'ul ol'.
     pub commit_decoration_style: String,
 
+    #[arg(
+        long = "comment-emphasis",
+        default_value = "normal",
+        value_name = "MODE",
+        value_parser = ["normal", "suppress", "only"]
+    )]
+    /// How to emphasize changes that fall inside code comments.
+    ///
+    /// With 'suppress', parts of a changed line that lie entirely within a comment (according to
+    /// the syntax definition of the file) are not emphasized. With 'only', parts of a changed line
+    /// are emphasized only if they lie entirely within a comment. Comments are recognized line by
+    /// line, so lines inside a multi-line comment are not treated as comments.
+    pub comment_emphasis: String,
+
     #[arg(
         long = "commit-regex",
         default_value = r"^commit ",
@@ -133,6 +213,29 @@ pub struct Opt {
     /// output.
     pub commit_style: String,
 
+    #[arg(
+        long = "completion-notification",
+        default_value = "none",
+        value_name = "NOTIFICATION"
+    )]
+    /// Notify the terminal when delta has finished writing its output.
+    ///
+    /// With 'bell', a terminal bell character is written after the last line. With 'title:TEXT',
+    /// an OSC escape sequence setting the terminal title to TEXT is written instead. Nothing is
+    /// written if standard output is not a terminal. When a pager is used, it must pass the
+    /// sequence through to the terminal.
+    pub completion_notification: String,
+
+    #[arg(
+        long = "context-line-label",
+        default_value = "[context]",
+        value_name = "STRING"
+    )]
+    /// Label of unchanged lines with --line-kind-labels.
+    ///
+    /// An empty label omits the label of those lines.
+    pub context_line_label: String,
+
     #[arg(long = "dark")]
     /// Use default colors appropriate for a dark terminal background.
     ///
@@ -146,6 +249,29 @@ pub struct Opt {
     /// typically make sense to set this in the per-repository config file '.git/config'.
     pub default_language: String,
 
+    #[arg(long = "density-column")]
+    /// Display a column indicating the density of changes at the right of hunk lines.
+    ///
+    /// Each hunk line is followed, in the last column of the decorations width, by a shade from
+    /// --density-column-shades reflecting how many of the last --density-column-window lines
+    /// (including that one) are removed or added lines. This gives an overview of where the
+    /// changes of a long diff are concentrated. Not available in side-by-side mode.
+    pub density_column: bool,
+
+    #[arg(
+        long = "density-column-shades",
+        default_value = " ░▒▓█",
+        value_name = "STRING"
+    )]
+    /// Shades of --density-column, from lowest to highest density.
+    ///
+    /// Each character of the string is one shade. There must be at least two.
+    pub density_column_shades: String,
+
+    #[arg(long = "density-column-window", default_value = "8", value_name = "N")]
+    /// Number of lines over which --density-column measures the density of changes.
+    pub density_column_window: usize,
+
     /// Detect whether or not the terminal is dark or light by querying for its colors.
     ///
     /// Ignored if either `--dark` or `--light` is specified.
@@ -162,6 +288,10 @@ pub struct Opt {
     ///
     /// `auto` tries to account for these situations by testing if the output is redirected.
     ///
+    /// If the terminal does not answer, or is not queried with `auto`, the background color is
+    /// read from the COLORFGBG environment variable instead, when it is set (e.g. by rxvt). This
+    /// is not done with `never`.
+    ///
     /// The `--color-only` option is treated as an indicator that delta is used
     /// as `interactive.diffFilter`. In this case the color is queried from the terminal even
     /// though the output is redirected.
@@ -202,6 +332,88 @@ pub struct Opt {
     /// If a relativized file path exceeds this width then the diff stat will be misaligned.
     pub diff_stat_align_width: usize,
 
+    #[arg(long = "diff-stat-colors")]
+    /// Color the graph of a diff stat section.
+    ///
+    /// The `+` and `-` bars of lines like "src/delta.rs | 14 ++++++++++----" are painted with
+    /// plus-style and minus-style respectively, so that they match the coloring of the diff.
+    pub diff_stat_colors: bool,
+
+    #[arg(long = "emph-gradient", value_name = "FROM TO")]
+    /// Paint emphasized text with a gradient of background colors.
+    ///
+    /// The value is two space-separated colors, e.g. "#3f0000 #7f3f00". The background of each run
+    /// of emphasized text changes character by character from the first color, for its first
+    /// character, to the second, for its last character, so that long changed spans stand out.
+    /// The other attributes of minus-emph-style and plus-emph-style are kept. Unset by default.
+    pub emph_gradient: Option<String>,
+
+    #[arg(long = "emph-merge-gap", default_value = "0", value_name = "N")]
+    /// Merge emphasized sections separated by at most N unchanged characters.
+    ///
+    /// A change is sometimes inferred as several small edits separated by short unchanged runs, for
+    /// example "a, b" becoming "x, y". Positive values trade accuracy for readability: the
+    /// unchanged runs are emphasized too, so that the edits appear as one contiguous region. The
+    /// default, 0, emphasizes exactly the inferred edits.
+    pub emph_merge_gap: usize,
+
+    #[arg(long = "emph-token-regex", value_name = "REGEX")]
+    /// Extend emphasized sections to whole tokens matching this regular expression.
+    ///
+    /// When the change inferred within a line starts or ends in the middle of a token, as can
+    /// happen with a fine-grained --word-diff-regex such as ".", the emphasis is extended outward
+    /// to the boundaries of that token, so that whole affected tokens are emphasized. A typical
+    /// value would be "\w+". Unset by default, in which case emphasis is not extended.
+    pub emph_token_regex: Option<String>,
+
+    #[arg(
+        long = "emphasis-algorithm",
+        default_value = "edit-distance",
+        value_name = "ALGORITHM",
+        value_parser = ["edit-distance", "lcs"]
+    )]
+    /// How to infer the changed sections of a pair of lines.
+    ///
+    /// With 'edit-distance' (the default), the lines are split into tokens (see
+    /// --word-diff-regex), and delta emphasizes the tokens deleted and inserted by an alignment
+    /// which favors grouping changed tokens together. With 'lcs', the characters of the lines that
+    /// are on a longest common subsequence of them are left unemphasized, and all other characters
+    /// are emphasized; this shows interleaved edits of single characters more precisely. Very long
+    /// lines keep the default emphasis.
+    pub emphasis_algorithm: String,
+
+    #[arg(long = "emphasis-records-file", value_name = "PATH", value_hint = ValueHint::FilePath)]
+    /// Write the emphasized text of each painted changed line to the file at PATH.
+    ///
+    /// When delta finishes, the file is written with one JSON object per removed or added line
+    /// containing emphasized text, e.g. {"file":"src/main.rs","line_number":12,"ranges":[[4,9]],
+    /// "side":"plus"}. The line number is that of the line in the version of the file given by
+    /// side ("minus" or "plus"), and ranges are the [start, end) character offsets of the
+    /// emphasized text, excluding the +/- prefix and counting tabs after their expansion. Lines
+    /// painted with --inline-edits are not recorded. This is intended for tools that check or
+    /// reuse delta's emphasis. Unset by default.
+    pub emphasis_records_file: Option<String>,
+
+    #[arg(long = "emphasize-additions-only")]
+    /// Emphasize the changed sections of added lines only.
+    ///
+    /// Removed lines are painted with minus-style throughout, without minus-emph-style or
+    /// minus-non-emph-style, so that attention goes to the new code. Added lines are emphasized as
+    /// usual.
+    pub emphasize_additions_only: bool,
+
+    #[arg(
+        long = "empty-file-style",
+        default_value = "inline-hint-style",
+        value_name = "STYLE"
+    )]
+    /// Style string for the notice displayed for added and removed empty files.
+    ///
+    /// The diff of an empty file that is created or deleted has no hunks, so delta displays "new
+    /// empty file" or "deleted empty file" below the file section. Use 'omit' to display nothing.
+    /// See STYLES section.
+    pub empty_file_style: String,
+
     #[arg(long = "features", value_name = "FEATURES")]
     /// Names of delta features to activate (space-separated).
     ///
@@ -251,6 +463,23 @@ pub struct Opt {
     /// Used in the default value of navigate-regex.
     pub file_modified_label: String,
 
+    #[arg(
+        long = "file-order",
+        default_value = "input",
+        value_name = "ORDER",
+        value_parser = ["input", "size", "path"]
+    )]
+    /// Order in which to display the files of each commit.
+    ///
+    /// With 'input' (the default), files are displayed in the order of the input. With 'size',
+    /// the files with the most removed and added lines are displayed first. With 'path', files are
+    /// displayed in alphabetical order of their paths. Files comparing equal keep their input
+    /// order. Only the files of a diff whose file sections start with a "diff" line (e.g. that of
+    /// git) are reordered; the files of each commit in the output of `git log -p` are reordered
+    /// separately. Other than with 'input', delta reads all of its input before displaying any of
+    /// it.
+    pub file_order: String,
+
     #[arg(
         long = "file-removed-label",
         default_value = "removed:",
@@ -271,6 +500,24 @@ pub struct Opt {
     /// Used in the default value of navigate-regex.
     pub file_renamed_label: String,
 
+    #[arg(long = "file-separator", default_value = "", value_name = "STRING")]
+    /// Text of a horizontal rule displayed between files.
+    ///
+    /// The text is repeated to fill the decorations width (see --width), so a single box-drawing
+    /// character such as '─' draws a rule across the terminal. The rule is displayed above the
+    /// file section of every file but the first. An empty string (the default) disables it.
+    pub file_separator: String,
+
+    #[arg(
+        long = "file-separator-style",
+        default_value = "blue",
+        value_name = "STYLE"
+    )]
+    /// Style string for the file-separator.
+    ///
+    /// See STYLES section.
+    pub file_separator_style: String,
+
     #[arg(long = "file-style", default_value = "blue", value_name = "STYLE")]
     /// Style string for the file section.
     ///
@@ -281,6 +528,17 @@ pub struct Opt {
     /// Sed-style command transforming file paths for display.
     pub file_regex_replacement: Option<String>,
 
+    #[arg(long = "formatting-emph-style", value_name = "STYLE")]
+    /// Style string for emphasized sections that only change case or whitespace.
+    ///
+    /// When set, the changes within a pair of removed and added lines are also compared after
+    /// converting both lines to lowercase, removing their leading and trailing whitespace, and
+    /// collapsing each run of whitespace into a single space. Emphasized sections that do not
+    /// differ in this comparison, such as re-indentation or a change of capitalization, are
+    /// displayed in this style, so that a muted style leaves only the meaningful changes
+    /// emphasized. Unset by default. See STYLES section.
+    pub formatting_emph_style: Option<String>,
+
     #[arg(long = "generate-completion")]
     /// Print completion file for the given shell.
     pub generate_completion: Option<Shell>,
@@ -355,6 +613,75 @@ pub struct Opt {
     /// lines: set this option to "keep" to keep the original separator symbols.
     pub grep_separator_symbol: String,
 
+    #[arg(long = "grid-width", default_value = "0", value_name = "N")]
+    /// Paint every output line into a cell exactly N columns wide.
+    ///
+    /// This is intended for programs displaying delta's output in a grid of fixed-width cells, e.g.
+    /// a TUI pane. Each line is truncated or padded with spaces to N columns, ends with a style
+    /// reset, and contains no tab, so that it occupies exactly one terminal row without wrapping
+    /// and without its styles leaking into the next line. The terminal is assumed to be N columns
+    /// wide, and backgrounds are filled with spaces (see --line-fill-method). The default, 0,
+    /// disables this. It cannot be used with --output-format=html.
+    pub grid_width: usize,
+
+    #[arg(long = "gutter")]
+    /// Display a colored single-character gutter instead of +/- markers.
+    ///
+    /// The gutter symbol (see --gutter-symbol) is placed to the left of the line content, which
+    /// otherwise starts flush. Its color encodes the kind of line: removed lines use
+    /// --gutter-minus-style, added lines --gutter-plus-style, removed or added lines that are
+    /// paired with a line on the other side --gutter-changed-style, and unchanged lines
+    /// --gutter-zero-style. Has no effect in side-by-side mode.
+    pub gutter: bool,
+
+    #[arg(
+        long = "gutter-changed-style",
+        default_value = "yellow",
+        value_name = "STYLE"
+    )]
+    /// Style string for the gutter symbol of changed lines.
+    ///
+    /// A changed line is a removed or added line that is paired with a line on the other side.
+    /// See --gutter and the STYLES section.
+    pub gutter_changed_style: String,
+
+    #[arg(
+        long = "gutter-minus-style",
+        default_value = "red",
+        value_name = "STYLE"
+    )]
+    /// Style string for the gutter symbol of removed lines.
+    ///
+    /// See --gutter and the STYLES section.
+    pub gutter_minus_style: String,
+
+    #[arg(
+        long = "gutter-plus-style",
+        default_value = "green",
+        value_name = "STYLE"
+    )]
+    /// Style string for the gutter symbol of added lines.
+    ///
+    /// See --gutter and the STYLES section.
+    pub gutter_plus_style: String,
+
+    #[arg(long = "gutter-symbol", default_value = "█", value_name = "STRING")]
+    /// Symbol displayed in the gutter (see --gutter).
+    ///
+    /// The symbol should occupy a single terminal column.
+    pub gutter_symbol: String,
+
+    #[arg(
+        long = "gutter-zero-style",
+        default_value = "omit",
+        value_name = "STYLE"
+    )]
+    /// Style string for the gutter symbol of unchanged lines.
+    ///
+    /// The default, 'omit', leaves the gutter blank for unchanged lines. See --gutter and the
+    /// STYLES section.
+    pub gutter_zero_style: String,
+
     #[arg(
         long = "hunk-header-decoration-style",
         default_value = "blue box",
@@ -388,6 +715,25 @@ pub struct Opt {
     /// 'line-number' special attribute.
     pub hunk_header_line_number_style: String,
 
+    #[arg(long = "hunk-header-line-ranges")]
+    /// Display the ranges of lines covered by each hunk in the hunk header.
+    ///
+    /// Instead of the first line number of the hunk in the new file, display the ranges of lines
+    /// that the hunk spans in the old and the new file, for example "lines 10–24 → 10–27" for a
+    /// hunk starting with "@@ -10,15 +10,18 @@". Only has an effect if hunk-header-style contains
+    /// the 'line-number' special attribute.
+    pub hunk_header_line_ranges: bool,
+
+    #[arg(long = "hunk-header-marker", value_name = "STRING")]
+    /// Text to display in place of the @@ markers of raw hunk headers.
+    ///
+    /// When hunk-header-style is 'raw', the runs of '@' before and after the line numbers of
+    /// each hunk header are replaced by STRING, e.g. --hunk-header-marker='§'. If STRING is empty,
+    /// the markers are removed, together with the space separating them from the line numbers.
+    /// If unset (the default), hunk headers are displayed unchanged. Has no effect with
+    /// --color-only.
+    pub hunk_header_marker: Option<String>,
+
     #[arg(
         long = "hunk-header-style",
         default_value = "line-number syntax",
@@ -406,6 +752,33 @@ pub struct Opt {
     /// Used in the default value of navigate-regex.
     pub hunk_label: String,
 
+    #[arg(long = "hunk-separator", default_value = "", value_name = "STRING")]
+    /// Text of a horizontal rule displayed between the hunks of a file.
+    ///
+    /// The text is repeated to fill the decorations width, as with --file-separator. The rule is
+    /// displayed above the hunk header of every hunk but the first of each file. An empty string
+    /// (the default) disables it.
+    pub hunk_separator: String,
+
+    #[arg(
+        long = "hunk-separator-style",
+        default_value = "blue",
+        value_name = "STYLE"
+    )]
+    /// Style string for the hunk-separator.
+    ///
+    /// See STYLES section.
+    pub hunk_separator_style: String,
+
+    #[arg(long = "hunk-size-format", default_value = "", value_name = "FMT")]
+    /// Format string for an annotation giving the number of changed lines in each hunk.
+    ///
+    /// The annotation is displayed on a line of its own after the last line of each hunk, using
+    /// the colors of hunk-header-style. The placeholders {added} and {removed} are replaced by the
+    /// numbers of added and removed lines in the hunk, e.g. --hunk-size-format='(+{added}/-{removed})'.
+    /// An empty string (the default) disables the annotation.
+    pub hunk_size_format: String,
+
     #[arg(long = "hyperlinks")]
     /// Render commit hashes, file names, and line numbers as hyperlinks.
     ///
@@ -442,6 +815,34 @@ pub struct Opt {
     /// <https://dandavison.github.io/delta/hyperlinks.html> for details.
     pub hyperlinks_file_link_format: String,
 
+    #[arg(long = "indent-guide-style", value_name = "STYLE")]
+    /// Style string for guides drawn at each indentation level of lines.
+    ///
+    /// If set, the leading spaces of each hunk line at the start of each indentation level, i.e.
+    /// every --tabs columns, are displayed as a vertical line ('│'), so that the nesting of deeply
+    /// indented code remains visible. Only the foreground color of the style is used: guides keep
+    /// the background of the line. Unset by default. Has no effect with --tabs=0. See STYLES
+    /// section.
+    pub indent_guide_style: Option<String>,
+
+    #[arg(long = "indent-normalization-style", value_name = "STYLE")]
+    /// Style string for paired lines that only normalize their indentation.
+    ///
+    /// When a removed line and the added line replacing it differ only in how their indentation
+    /// mixes tabs and spaces, the indentation having the same width (see --tabs), both lines are
+    /// painted with this style, typically a muted one, instead of being emphasized as changed
+    /// lines. Unset by default. See STYLES section.
+    pub indent_normalization_style: Option<String>,
+
+    #[arg(long = "inline-edits")]
+    /// Display a line that was edited as a single line, with the changes inline.
+    ///
+    /// When a removed line is paired with a single added line, display one combined line instead
+    /// of two: text removed from the old line is styled with minus-emph-style, text inserted in the
+    /// new line is styled with plus-emph-style, and text common to both lines is styled with
+    /// zero-style. Has no effect in side-by-side mode.
+    pub inline_edits: bool,
+
     #[arg(
         long = "inline-hint-style",
         default_value = "blue",
@@ -453,6 +854,15 @@ pub struct Opt {
     /// to highlight tabs, and the symbols used to indicate wrapped lines. See STYLES section.
     pub inline_hint_style: String,
 
+    #[arg(long = "insertion-point-style", value_name = "STYLE")]
+    /// Style string for a caret marking where text was inserted into a line.
+    ///
+    /// When an added line only inserts text into the removed line it is paired with, the removed
+    /// line has nothing to emphasize. If this option is set, a caret (‸) painted with this style is
+    /// displayed in the removed line at each position where the added line inserts text. Unset by
+    /// default. See STYLES section.
+    pub insertion_point_style: Option<String>,
+
     #[arg(
         long = "inspect-raw-lines",
         default_value = "true",
@@ -470,7 +880,7 @@ pub struct Opt {
     /// Prefix added/removed lines with a +/- character, as git does.
     ///
     /// By default, delta does not emit any prefix, so code can be copied directly from delta's
-    /// output.
+    /// output. The prefixes can be changed with --minus-marker, --plus-marker and --zero-marker.
     pub keep_plus_minus_markers: bool,
 
     #[arg(long = "light")]
@@ -489,6 +899,21 @@ pub struct Opt {
     /// affect delta's performance when entire files are added/removed.
     pub line_buffer_size: usize,
 
+    #[arg(
+        long = "line-ending-changes",
+        default_value = "show",
+        value_name = "show|compact|hide",
+        value_parser = ["show", "compact", "hide"]
+    )]
+    /// How to display changes that only alter line endings.
+    ///
+    /// A run of removed and added lines is a line-ending change if the lines are identical once
+    /// their line endings are disregarded, as when a file is converted from CRLF to LF line
+    /// endings. With 'compact', such a change is displayed as a notice followed by the added lines
+    /// only; with 'hide', only the notice is displayed. The default, 'show', displays the change
+    /// like any other.
+    pub line_ending_changes: String,
+
     #[arg(long = "line-fill-method", value_name = "STRING", value_parser = ["ansi", "spaces"])]
     /// Line-fill method in side-by-side mode.
     ///
@@ -497,6 +922,15 @@ pub struct Opt {
     /// --width=variable is given.
     pub line_fill_method: Option<String>,
 
+    #[arg(long = "line-kind-labels")]
+    /// Start each line of a hunk with a plain-text label of its kind.
+    ///
+    /// This conveys without colors, e.g. to screen readers, whether a line is removed
+    /// (--removed-line-label), added (--added-line-label), a removed or added line that is paired
+    /// with a line on the other side (--changed-line-label), or unchanged (--context-line-label).
+    /// A label is followed by a space. Has no effect in side-by-side mode.
+    pub line_kind_labels: bool,
+
     #[arg(short = 'n', long = "line-numbers")]
     /// Display line numbers next to the diff.
     ///
@@ -591,6 +1025,38 @@ pub struct Opt {
     /// An example is --map-styles='bold purple => red "#eeeeee", bold cyan => syntax "#eeeeee"'
     pub map_styles: Option<String>,
 
+    #[arg(long = "map-syntax", value_name = "SYNTAX_MAP")]
+    /// Map file names or extensions to the syntax used to highlight them.
+    ///
+    /// For files whose syntax is not detected, or not correctly, from their name. An example is
+    /// --map-syntax='tsx => TypeScript, Jenkinsfile => Groovy, .env => Bourne Again Shell (bash)'.
+    /// The whole file name is looked up first, then its extension. Syntaxes are given by name or
+    /// extension, as listed by --list-languages. These mappings take precedence over syntax
+    /// detection.
+    pub map_syntax: Option<String>,
+
+    #[arg(long = "matching-bracket-style", value_name = "STYLE")]
+    /// Style string for the pair of brackets enclosing the changes in a line.
+    ///
+    /// When the emphasized sections of a line lie between a matching pair of brackets, that is
+    /// `()`, `[]` or `{}`, the nearest such pair is displayed in this style to show the context of
+    /// the edit. Colors that the style does not specify are taken from the surrounding text. Unset
+    /// by default. See STYLES section.
+    pub matching_bracket_style: Option<String>,
+
+    #[arg(
+        long = "max-common-prefix-width",
+        default_value = "0",
+        value_name = "N"
+    )]
+    /// Elide the middle of long text shared by the start of a removed line and its added line.
+    ///
+    /// When a removed line and the added line paired with it begin with the same text, and that
+    /// text is longer than N characters, display only its first and last characters, joined by an
+    /// ellipsis, so that the changes are brought into view. Use --max-common-prefix-width=0
+    /// (the default) to disable this. Has no effect in side-by-side mode.
+    pub max_common_prefix_width: usize,
+
     #[arg(long = "max-line-distance", default_value = "0.6", value_name = "DIST")]
     /// Maximum line pair distance parameter in within-line diff algorithm.
     ///
@@ -610,6 +1076,14 @@ pub struct Opt {
     /// long lines (e.g. minified .js).
     pub max_syntax_length: usize,
 
+    #[arg(long = "max-syntax-scope-depth", default_value = "0", value_name = "N")]
+    /// Do not syntax highlight lines whose syntax nests deeper than N scopes.
+    ///
+    /// Highlighting deeply nested constructs (e.g. in generated or adversarial input) can be very
+    /// slow. Lines on which the scope stack of the syntax definition grows deeper than N are
+    /// displayed without syntax highlighting instead. The default, 0, imposes no limit.
+    pub max_syntax_scope_depth: usize,
+
     #[arg(long = "max-line-length", default_value = "3000", value_name = "N")]
     /// Truncate lines longer than this.
     ///
@@ -617,6 +1091,15 @@ pub struct Opt {
     /// overwritten to fit at least all visible characters, see `--wrap-max-lines`.
     pub max_line_length: usize,
 
+    #[arg(long = "max-section-length", default_value = "0", value_name = "N")]
+    /// Split styled sections of a line longer than N characters.
+    ///
+    /// Each run of characters sharing one style that is longer than N characters is emitted as
+    /// several runs of at most N characters with that same style. This does not change the
+    /// appearance of the output, but helps programs processing it that handle very long runs
+    /// poorly. Runs are only split between characters. The default, 0, disables this.
+    pub max_section_length: usize,
+
     #[arg(
         long = "merge-conflict-begin-symbol",
         default_value = "▼",
@@ -682,6 +1165,16 @@ pub struct Opt {
     /// STYLES section.
     pub merge_conflict_theirs_diff_header_style: String,
 
+    #[arg(long = "min-terminal-width", default_value = "0", value_name = "N")]
+    /// Minimum terminal width assumed by delta.
+    ///
+    /// If the width of the terminal cannot be determined, or is determined to be narrower than N
+    /// columns (e.g. 0 for some pseudo-terminals), delta lays out its output (using --width,
+    /// wrapping and side-by-side panels) as though the terminal were N columns wide. The default,
+    /// 0, sets no minimum, in which case a terminal whose width cannot be determined is assumed to
+    /// be 79 columns wide.
+    pub min_terminal_width: usize,
+
     #[arg(
         long = "minus-empty-line-marker-style",
         default_value = "normal auto",
@@ -692,6 +1185,13 @@ pub struct Opt {
     /// Used only if --minus-style has no background color.
     pub minus_empty_line_marker_style: String,
 
+    #[arg(long = "minus-marker", default_value = "-", value_name = "STRING")]
+    /// Marker prefixing removed lines with --keep-plus-minus-markers.
+    ///
+    /// The marker is displayed in minus-style, without syntax highlighting. In side-by-side mode,
+    /// it prefixes the lines of the left panel.
+    pub minus_marker: String,
+
     #[arg(
         long = "minus-emph-style",
         default_value = "normal auto",
@@ -722,6 +1222,17 @@ pub struct Opt {
     /// See STYLES section.
     pub minus_style: String,
 
+    #[arg(
+        long = "moved-style",
+        default_value = "bold purple",
+        value_name = "STYLE"
+    )]
+    /// Style string for moved lines (see --color-moved).
+    ///
+    /// Whether a moved line was removed or added is shown by its +/- marker or line numbers. See
+    /// STYLES section.
+    pub moved_style: String,
+
     #[arg(long = "navigate")]
     /// Activate diff navigation.
     ///
@@ -739,6 +1250,65 @@ pub struct Opt {
     /// See GIT CONFIG section.
     pub no_gitconfig: bool,
 
+    #[arg(
+        long = "no-newline-marker-style",
+        default_value = "dim",
+        value_name = "STYLE"
+    )]
+    /// Style string for the "\ No newline at end of file" marker.
+    ///
+    /// Git writes this marker after the last line of a file lacking a final newline. It is
+    /// displayed after the block of removed and added lines that it follows, so that it does not
+    /// prevent these lines from being paired for emphasis. See STYLES section.
+    pub no_newline_marker_style: String,
+
+    #[arg(long = "operator-emph-style", value_name = "STYLE")]
+    /// Style string for emphasized sections that change an operator.
+    ///
+    /// An emphasized section is treated as an operator change when the token it belongs to is one
+    /// of --operator-tokens, for example when `==` becomes `!=`. Such small changes are easily
+    /// overlooked, so this style can give them more visibility than minus-emph-style and
+    /// plus-emph-style. Unset by default, in which case operator changes are styled like any other
+    /// change. See STYLES section.
+    pub operator_emph_style: Option<String>,
+
+    #[arg(
+        long = "operator-tokens",
+        default_value = "== != === !== <= >= < > && || ! + - * / % & | ^ << >> = += -= *= /= -> =>",
+        value_name = "TOKENS"
+    )]
+    /// Space-separated list of operators recognized by --operator-emph-style.
+    pub operator_tokens: String,
+
+    #[arg(long = "output-buffer-limit", default_value = "0", value_name = "N")]
+    /// Write output as soon as N bytes of it are pending.
+    ///
+    /// Delta holds the painted output of lines in a buffer, up to the whole of a block of changed
+    /// lines, before writing it. For use where memory is constrained, this option gives the buffer
+    /// an initial capacity of N bytes, writes it as soon as it holds N bytes or more, and shrinks
+    /// it back to N bytes after each write. The check is made once a line or block of changed
+    /// lines has been painted, so that a single block larger than N bytes still grows the buffer
+    /// beyond it. The default, 0, imposes no limit.
+    pub output_buffer_limit: usize,
+
+    #[arg(
+        long = "output-format",
+        default_value = "ansi",
+        value_name = "ansi|html",
+        value_parser = ["ansi", "html"],
+    )]
+    /// Format in which to write the output.
+    ///
+    /// With ansi, the output is text styled with ANSI escape sequences, for display in a terminal.
+    /// With html, each line is written as a <div> element containing a <span> element for each
+    /// styled section of it, with the style given as CSS, and hyperlinks (see --hyperlinks) are
+    /// written as <a> elements. With --color=never, no <span> elements are written. The HTML
+    /// is meant to be embedded in a page where it is displayed in a monospace font with
+    /// `white-space: pre`. Backgrounds are not extended to the width of a terminal, and html
+    /// cannot be used with options laying out lines for a terminal: --side-by-side, --wrap-lines,
+    /// --truncate-lines, --grid-width, --shell-integration-marks and --completion-notification.
+    pub output_format: String,
+
     #[arg(long = "pager", value_name = "CMD")]
     /// Which pager to use.
     ///
@@ -758,6 +1328,26 @@ pub struct Opt {
     /// Options are: auto, always, and never.
     pub paging_mode: String,
 
+    #[arg(long = "pair-swap-emph-style", value_name = "STYLE")]
+    /// Style string for emphasized tokens swapped for their pair from --paired-tokens.
+    ///
+    /// Replacing a token such as `true` by `false`, or `ENABLED` by `DISABLED`, is a one-word
+    /// change that can invert the behavior of the code. This style, typically a loud one, makes
+    /// such swaps stand out from the other changes, which keep minus-emph-style and
+    /// plus-emph-style. Unset by default. See STYLES section.
+    pub pair_swap_emph_style: Option<String>,
+
+    #[arg(
+        long = "paired-tokens",
+        default_value = "true=false True=False TRUE=FALSE yes=no on=off enabled=disabled ENABLED=DISABLED",
+        value_name = "A=B ..."
+    )]
+    /// Space-separated list of token pairs recognized by --pair-swap-emph-style.
+    ///
+    /// Each entry, e.g. "true=false", is a pair of tokens (as defined by --word-diff-regex) either
+    /// of which may replace the other.
+    pub paired_tokens: String,
+
     #[arg(long = "parse-ansi")]
     /// Display ANSI color escape sequences in human-readable form.
     ///
@@ -765,6 +1355,15 @@ pub struct Opt {
     /// This can be used to help identify input style strings to use with map-styles.
     pub parse_ansi: bool,
 
+    #[arg(long = "parse-structure")]
+    /// Display the files and hunks of a diff as JSON, without painting it.
+    ///
+    /// Example usage: git show | delta --parse-structure
+    /// For each file, a line is written with the paths of its minus and plus versions, and, for
+    /// each hunk, the (1-based, end-exclusive) ranges of lines it covers in the minus and plus
+    /// files. This can be used to build an index of a diff for navigation.
+    pub parse_structure: bool,
+
     #[arg(
         long = "plus-emph-style",
         default_value = "syntax auto",
@@ -785,6 +1384,20 @@ pub struct Opt {
     /// Used only if --plus-style has no background color.
     pub plus_empty_line_marker_style: String,
 
+    #[arg(long = "plus-marker", default_value = "+", value_name = "STRING")]
+    /// Marker prefixing added lines with --keep-plus-minus-markers.
+    ///
+    /// The marker is displayed in plus-style, without syntax highlighting. In side-by-side mode,
+    /// it prefixes the lines of the right panel.
+    pub plus_marker: String,
+
+    #[arg(long = "plus-lines-first")]
+    /// Display the added lines of each change before the removed lines.
+    ///
+    /// This shows the new version of the code first, followed by what it replaced. Removed and
+    /// added lines are still paired and emphasized as usual. Has no effect in side-by-side mode.
+    pub plus_lines_first: bool,
+
     #[arg(
         long = "plus-non-emph-style",
         default_value = "plus-style",
@@ -805,24 +1418,114 @@ pub struct Opt {
     /// See STYLES section.
     pub plus_style: String,
 
+    #[arg(long = "preserve-missing-final-newline")]
+    /// Do not end the output with a newline if the input does not end with one.
+    ///
+    /// Delta ends each line that it outputs with a newline, including the last one, even if the
+    /// last line of its input is not terminated. With this option, such a final newline is
+    /// omitted, so that the output of delta used as a filter stays faithful to its input in this
+    /// respect.
+    pub preserve_missing_final_newline: bool,
+
+    #[arg(long = "preview-styles")]
+    /// Display a small sample diff painted with the active styles.
+    ///
+    /// This can be used to try out a style configuration, by combining this option with other
+    /// options such as --minus-style, --plus-emph-style, --file-style, etc., before applying it
+    /// to real diffs.
+    pub preview_styles: bool,
+
+    #[arg(long = "punctuation-emph-style", value_name = "STYLE")]
+    /// Style string for emphasized sections that only add or remove punctuation.
+    ///
+    /// Changes such as adding a trailing comma (`a` becoming `a,`) are easily overlooked, so this
+    /// style can give them more visibility than minus-emph-style and plus-emph-style. Operator
+    /// changes are styled with --operator-emph-style instead when that is set. Unset by default,
+    /// in which case punctuation changes are styled like any other change. See STYLES section.
+    pub punctuation_emph_style: Option<String>,
+
     #[arg(long = "raw")]
     /// Do not alter the input in any way.
     ///
     /// This is mainly intended for testing delta.
     pub raw: bool,
 
+    #[arg(long = "reordered-lines-style", value_name = "STYLE")]
+    /// Style string for removed and added lines that only reorder each other.
+    ///
+    /// When a run of removed lines and the run of added lines following it contain the same lines
+    /// in a different order (e.g. sorted imports), the lines are painted with this style and without
+    /// emphasis, instead of being paired and emphasized as changed lines. Unset by default. See
+    /// STYLES section.
+    pub reordered_lines_style: Option<String>,
+
     #[arg(long = "relative-paths")]
     /// Output all file paths relative to the current directory.
     ///
     /// This means that they will resolve correctly when clicked on or used in shell commands.
     pub relative_paths: bool,
 
+    #[arg(
+        long = "removed-line-label",
+        default_value = "[removed]",
+        value_name = "STRING"
+    )]
+    /// Label of removed lines with --line-kind-labels.
+    ///
+    /// An empty label omits the label of those lines.
+    pub removed_line_label: String,
+
+    #[arg(long = "rename-emph-style", value_name = "STYLE")]
+    /// Style string for emphasized sections that apply a rename from --rename-map.
+    ///
+    /// During a refactoring that renames an identifier throughout the code, every line using it is
+    /// changed. This style, typically a calm one, distinguishes those expected substitutions from
+    /// the other changes, which keep minus-emph-style and plus-emph-style. Unset by default, in
+    /// which case renames are styled like any other change. See STYLES section.
+    pub rename_emph_style: Option<String>,
+
+    #[arg(long = "rename-map", default_value = "", value_name = "OLD=NEW ...")]
+    /// Space-separated list of renames recognized by --rename-emph-style.
+    ///
+    /// Each entry, e.g. "OldName=NewName", is a token (as defined by --word-diff-regex) of the
+    /// removed lines and the token replacing it in the added lines.
+    pub rename_map: String,
+
     #[arg(long = "right-arrow", default_value = "⟶  ", value_name = "STRING")]
     /// Text to display with a changed file path.
     ///
     /// For example, a unified diff heading, a rename, or a chmod.
     pub right_arrow: String,
 
+    #[arg(long = "scope-change-emph-style", value_name = "STYLE")]
+    /// Style string for changed tokens whose kind of syntax changed.
+    ///
+    /// When text of a removed line is replaced in its added line by text that the syntax
+    /// definition of the file classifies differently, for example a keyword replaced by an
+    /// identifier, both the removed and the inserted text are emphasized with this style instead
+    /// of minus-emph-style and plus-emph-style. The kind of a token is the first component of its
+    /// innermost syntax scope, e.g. "keyword" or "string". Unset by default. See STYLES section.
+    pub scope_change_emph_style: Option<String>,
+
+    #[arg(long = "scrollback-lines", default_value = "0", value_name = "N")]
+    /// Write only the last N lines of output.
+    ///
+    /// Painted lines are held in a buffer of N lines, each new line evicting the oldest one once
+    /// it is full, and the buffer is written when the whole input has been read. This lets a pager
+    /// display the end of a long diff without delta retaining all of its painted output. The
+    /// default, 0, writes all lines.
+    pub scrollback_lines: usize,
+
+    #[arg(long = "shell-integration-marks")]
+    /// Bracket the output with OSC 133 shell-integration marks.
+    ///
+    /// A 'command output start' mark (OSC 133;C) is written before the output and a 'command
+    /// finished' mark (OSC 133;D) after it, so that terminals supporting semantic prompts can
+    /// navigate between the outputs of successive delta invocations. Nothing is written if standard
+    /// output is not a terminal. When a pager is used, it must pass the sequences through to the
+    /// terminal.
+    pub shell_integration_marks: bool,
+
     #[arg(long = "show-colors")]
     /// Show available named colors.
     ///
@@ -856,10 +1559,38 @@ pub struct Opt {
     /// shown, use --dark or --light, or both, on the command line together with this option.
     pub show_themes: bool,
 
+    #[arg(long = "show-whitespace")]
+    /// Make spaces and tabs in the code of diff lines visible.
+    ///
+    /// Each space is displayed as '·' and each tab as --tab-symbol, or '→' if no tab symbol is
+    /// set, followed by spaces up to the next tab stop. These symbols are dimmed. Tabs are only
+    /// marked when they are expanded, i.e. not with --tabs=0.
+    pub show_whitespace: bool,
+
     #[arg(short = 's', long = "side-by-side")]
     /// Display diffs in side-by-side layout.
     pub side_by_side: bool,
 
+    #[arg(
+        long = "side-by-side-ratio",
+        default_value = "50/50",
+        value_name = "LEFT/RIGHT"
+    )]
+    /// Ratio of the widths of the left and right panels in side-by-side layout.
+    ///
+    /// For example, --side-by-side-ratio=40/60 gives 40% of the width to the left (removed)
+    /// panel and 60% to the right (added) one. When the width does not divide according to the
+    /// ratio, the right panel receives the remainder.
+    pub side_by_side_ratio: String,
+
+    #[arg(long = "syntax-font-styles")]
+    /// Render the bold, italic and underline font styles of the syntax theme.
+    ///
+    /// By default, syntax highlighting only colors the foreground of the code, so that themes
+    /// making keywords bold or comments italic lose those attributes. With this option they are
+    /// added to those of the style of the line.
+    pub syntax_font_styles: bool,
+
     #[arg(long = "syntax-theme", value_name = "SYNTAX_THEME")]
     /// The syntax-highlighting theme to use.
     ///
@@ -868,15 +1599,50 @@ pub struct Opt {
     /// syntax highlighting.
     pub syntax_theme: Option<String>,
 
+    #[arg(long = "syntax-theme-background-fallback", value_name = "COLOR")]
+    /// Background color to use where the syntax theme does not define one.
+    ///
+    /// An 'auto' background color in --zero-style takes the background color of the syntax theme.
+    /// Some minimal themes do not define a background color; in that case this color is used
+    /// instead, so that the background of unchanged lines can still be filled. See COLORS section.
+    pub syntax_theme_background_fallback: Option<String>,
+
+    #[arg(long = "tab-symbol", default_value = "", value_name = "STRING")]
+    /// Symbol used to make tab characters visible.
+    ///
+    /// If set, each tab character is displayed as this symbol followed by spaces up to the next tab
+    /// stop, tab stops being --tabs columns apart. The symbol is displayed in the foreground color
+    /// of inline-hint-style. Has no effect with --tabs=0.
+    pub tab_symbol: String,
+
     #[arg(long = "tabs", default_value = "8", value_name = "N")]
-    /// The number of spaces to replace tab characters with.
+    /// The number of columns between tab stops.
     ///
-    /// Use --tabs=0 to pass tab characters through directly, but note that in that case delta will
+    /// Each tab character is replaced with spaces up to the next tab stop, columns being counted
+    /// from the start of the line's code, i.e. not including its +/- marker or line numbers. Use
+    /// --tabs=0 to pass tab characters through directly, but note that in that case delta will
     /// calculate line widths assuming tabs occupy one character's width on the screen: if your
     /// terminal renders tabs as more than one character wide then delta's output will look
     /// incorrect.
     pub tab_width: usize,
 
+    #[arg(long = "trailing-comment-emph-style", value_name = "STYLE")]
+    /// Style string for trailing comments that are the only change in a line.
+    ///
+    /// When a removed line and its added line differ only in a comment at the end of the line,
+    /// for example when an explanatory comment is appended to a line of code, only the comment is
+    /// emphasized, with this style. Comments are recognized using the syntax definition of the
+    /// file. Unset by default. See STYLES section.
+    pub trailing_comment_emph_style: Option<String>,
+
+    #[arg(long = "truncate-lines")]
+    /// Truncate lines that are too long for the terminal, instead of letting them overflow.
+    ///
+    /// In unified (not side-by-side) mode, a diff line wider than the terminal is cut between
+    /// characters so that it occupies exactly one row, ending with '…'. Cannot be used together
+    /// with --wrap-lines.
+    pub truncate_lines: bool,
+
     #[arg(
         long = "true-color",
         default_value = "auto",
@@ -889,7 +1655,8 @@ pub struct Opt {
     /// the environment variable COLORTERM has the value "truecolor" or "24bit". If your terminal
     /// application (the application you use to enter commands at a shell prompt) supports 24 bit
     /// colors, then it probably already sets this environment variable, in which case you don't
-    /// need to do anything.
+    /// need to do anything. Without 24-bit color codes, RGB colors are approximated by the closest
+    /// colors of the 256-color palette, near-gray colors by grays.
     pub true_color: String,
 
     #[arg(
@@ -902,6 +1669,20 @@ pub struct Opt {
     /// Defaults to color.diff.whitespace if that is set in git config, or else 'magenta reverse'.
     pub whitespace_error_style: String,
 
+    #[arg(
+        long = "whitespace-only-changes",
+        default_value = "show",
+        value_name = "show|compact|hide",
+        value_parser = ["show", "compact", "hide"]
+    )]
+    /// How to display changes that only alter whitespace.
+    ///
+    /// A run of removed and added lines is a whitespace-only change if the lines are identical
+    /// once all whitespace is removed, as in a reformatting commit. With 'compact', such a change
+    /// is displayed as a notice followed by the added lines only; with 'hide', only the notice is
+    /// displayed. The default, 'show', displays the change like any other.
+    pub whitespace_only_changes: String,
+
     #[arg(short = 'w', long = "width", value_name = "N")]
     /// The width of underline/overline decorations.
     ///
@@ -927,6 +1708,15 @@ pub struct Opt {
     /// line and continues left-aligned.
     pub wrap_left_symbol: String,
 
+    #[arg(long = "wrap-lines")]
+    /// Wrap lines that are too long for the terminal, instead of letting them overflow.
+    ///
+    /// In unified (not side-by-side) mode, a diff line wider than the terminal is broken between
+    /// characters into several rows, each keeping the styles and background color of the line.
+    /// Continuation rows leave the line numbers and +/- marker columns blank, and end them with
+    /// --wrap-right-prefix-symbol. Side-by-side mode always wraps lines, see --wrap-max-lines.
+    pub wrap_lines: bool,
+
     #[arg(long = "wrap-max-lines", default_value = "2", value_name = "N")]
     /// How often a line should be wrapped if it does not fit.
     ///
@@ -962,16 +1752,42 @@ pub struct Opt {
     /// line and continues right-aligned.
     pub wrap_right_symbol: String,
 
+    #[arg(long = "zero-marker", default_value = " ", value_name = "STRING")]
+    /// Marker prefixing unchanged lines with --keep-plus-minus-markers.
+    ///
+    /// The marker is displayed in zero-style, without syntax highlighting.
+    pub zero_marker: String,
+
     #[arg(
         long = "zero-style",
-        default_value = "syntax normal",
+        default_value = "syntax dim",
         value_name = "STYLE"
     )]
     /// Style string for unchanged lines.
     ///
-    /// See STYLES section.
+    /// By default, unchanged lines are dimmed so that the changed lines stand out, keeping their
+    /// syntax highlighting. See STYLES section.
     pub zero_style: String,
 
+    #[arg(long = "zebra")]
+    /// Paint every other unchanged line of a hunk with zebra-style.
+    ///
+    /// The alternating background, in a hunk with long runs of unchanged lines, helps the eye follow
+    /// a row across the screen. The first unchanged line of each hunk is painted with zero-style, so
+    /// that the striping does not depend on the preceding hunks.
+    pub zebra: bool,
+
+    #[arg(
+        long = "zebra-style",
+        default_value = "syntax auto",
+        value_name = "STYLE"
+    )]
+    /// Style string for every other unchanged line with --zebra.
+    ///
+    /// The default background color, "auto", is a shade close to that of a dark or light terminal,
+    /// according to --dark or --light. See STYLES section.
+    pub zebra_style: String,
+
     #[arg(long = "24-bit-color", value_name = "auto|always|never", value_parser = ["auto", "always", "never"])]
     /// Deprecated: use --true-color.
     pub _24_bit_color: Option<String>,
@@ -1402,6 +2218,7 @@ lazy_static! {
         "generate-completion",
         "list-languages",
         "list-syntax-themes",
+        "preview-styles",
         "show-config",
         "show-syntax-themes",
     ]
@@ -4,6 +4,7 @@ use std::str::FromStr;
 use ansi_term::Color;
 use lazy_static::lazy_static;
 use syntect::highlighting::Color as SyntectColor;
+use syntect::highlighting::Theme as SyntaxTheme;
 
 use crate::fatal;
 use crate::git_config::GitConfig;
@@ -153,6 +154,114 @@ pub fn get_plus_emph_background_color_default(mode: ColorMode, is_true_color: bo
     }
 }
 
+pub fn get_zebra_background_color_default(mode: ColorMode, is_true_color: bool) -> Color {
+    match (mode, is_true_color) {
+        (Light, true) => LIGHT_THEME_ZEBRA_COLOR,
+        (Light, false) => LIGHT_THEME_ZEBRA_COLOR_256,
+        (Dark, true) => DARK_THEME_ZEBRA_COLOR,
+        (Dark, false) => DARK_THEME_ZEBRA_COLOR_256,
+    }
+}
+
+/// Return the background color of the syntax theme, or `fallback` if the theme does not define one.
+pub fn get_syntax_theme_background_color(
+    syntax_theme: Option<&SyntaxTheme>,
+    fallback: Option<Color>,
+    is_true_color: bool,
+) -> Option<Color> {
+    syntax_theme
+        .and_then(|theme| theme.settings.background)
+        .and_then(|color| utils::bat::terminal::to_ansi_color(color, is_true_color))
+        .or(fallback)
+}
+
+/// Return the color a fraction `t`, from 0 to 1, of the way from `from` to `to`. The result is an
+/// approximation from the 256-color palette unless `is_true_color`.
+pub fn mix(from: Color, to: Color, t: f64, is_true_color: bool) -> Color {
+    let (from, to) = (to_rgb(from), to_rgb(to));
+    let channel = |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * t).round() as u8;
+    let color = SyntectColor {
+        r: channel(from.0, to.0),
+        g: channel(from.1, to.1),
+        b: channel(from.2, to.2),
+        a: 0xff,
+    };
+    utils::bat::terminal::to_ansi_color(color, is_true_color).unwrap()
+}
+
+/// Return the background color of emphasized text in lines whose background color is `base`. It
+/// has the hue of `base`, but its lightness and saturation are moved some way toward those of the
+/// most saturated shade of that hue, so that it is more vivid: brighter if `base` is dark, and
+/// darker if it is light. The result is an approximation from the 256-color palette unless
+/// `is_true_color`.
+pub fn emph_background_color(base: Color, is_true_color: bool) -> Color {
+    const SHIFT: f64 = 0.4;
+    let (r, g, b) = to_rgb(base);
+    let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let lightness = (max + min) / 2.0;
+    let chroma = max - min;
+    let saturation = if chroma == 0.0 {
+        0.0
+    } else {
+        chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+    };
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    let lightness = lightness + (0.5 - lightness) * SHIFT;
+    // A gray stays gray.
+    let saturation = if chroma == 0.0 {
+        0.0
+    } else {
+        saturation + (1.0 - saturation) * SHIFT
+    };
+
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - (hue.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match hue as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |c: f64| ((c + m) * 255.0).round() as u8;
+    let color = SyntectColor {
+        r: channel(r),
+        g: channel(g),
+        b: channel(b),
+        a: 0xff,
+    };
+    utils::bat::terminal::to_ansi_color(color, is_true_color).unwrap()
+}
+
+/// Return the red, green and blue components of `color`, taking those of the xterm palette for
+/// palette colors.
+pub fn to_rgb(color: Color) -> (u8, u8, u8) {
+    let n = match color {
+        Color::RGB(r, g, b) => return (r, g, b),
+        Color::Fixed(n) => n,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Purple => 5,
+        Color::Cyan => 6,
+        Color::White => 7,
+    };
+    ansi_colours::rgb_from_ansi256(n)
+}
+
 const LIGHT_THEME_MINUS_COLOR: Color = Color::RGB(0xff, 0xe0, 0xe0);
 
 const LIGHT_THEME_MINUS_COLOR_256: Color = Color::Fixed(224);
@@ -185,8 +294,58 @@ const DARK_THEME_PLUS_EMPH_COLOR: Color = Color::RGB(0x00, 0x60, 0x00);
 
 const DARK_THEME_PLUS_EMPH_COLOR_256: Color = Color::Fixed(28);
 
+const LIGHT_THEME_ZEBRA_COLOR: Color = Color::RGB(0xf0, 0xf0, 0xf0);
+
+const LIGHT_THEME_ZEBRA_COLOR_256: Color = Color::Fixed(255);
+
+const DARK_THEME_ZEBRA_COLOR: Color = Color::RGB(0x1c, 0x1c, 0x1c);
+
+const DARK_THEME_ZEBRA_COLOR_256: Color = Color::Fixed(234);
+
 // blame
 
 pub const LIGHT_THEME_BLAME_PALETTE: &[&str] = &["#FFFFFF", "#DDDDDD", "#BBBBBB"];
 
 pub const DARK_THEME_BLAME_PALETTE: &[&str] = &["#000000", "#222222", "#444444"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mix() {
+        let (from, to) = (Color::RGB(0x00, 0x10, 0xff), Color::RGB(0xff, 0x30, 0x00));
+        assert_eq!(mix(from, to, 0.0, true), from);
+        assert_eq!(mix(from, to, 1.0, true), to);
+        assert_eq!(mix(from, to, 0.5, true), Color::RGB(0x80, 0x20, 0x80));
+        assert_eq!(
+            mix(Color::Black, Color::Fixed(15), 1.0, true),
+            Color::RGB(0xff, 0xff, 0xff)
+        );
+        assert_eq!(mix(from, from, 0.5, false), Color::Fixed(21));
+    }
+
+    #[test]
+    fn test_emph_background_color() {
+        let chroma = |color: Color| {
+            let (r, g, b) = to_rgb(color);
+            r.max(g).max(b) - r.min(g).min(b)
+        };
+        for (base, emph) in [
+            (DARK_THEME_MINUS_COLOR, Color::RGB(0x8c, 0x00, 0x02)),
+            (LIGHT_THEME_PLUS_COLOR, Color::RGB(0x7d, 0xff, 0x7d)),
+            (Color::RGB(0x40, 0x40, 0x40), Color::RGB(0x59, 0x59, 0x59)),
+        ] {
+            assert_eq!(emph_background_color(base, true), emph);
+            assert!(chroma(emph) >= chroma(base));
+        }
+        assert!(
+            chroma(emph_background_color(DARK_THEME_PLUS_COLOR, true))
+                > chroma(DARK_THEME_PLUS_COLOR)
+        );
+        assert_eq!(
+            emph_background_color(DARK_THEME_MINUS_COLOR, false),
+            Color::Fixed(88)
+        );
+    }
+}
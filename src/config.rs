@@ -36,6 +36,7 @@ pub const SYNTAX_FALLBACK_LANG: &str = "txt";
 
 #[cfg_attr(test, derive(Clone))]
 pub struct Config {
+    pub added_line_label: String,
     pub available_terminal_width: usize,
     pub background_color_extends_to_terminal_width: bool,
     pub blame_code_style: Option<Style>,
@@ -45,25 +46,48 @@ pub struct Config {
     pub blame_separator_style: Option<Style>,
     pub blame_timestamp_format: String,
     pub blame_timestamp_output_format: Option<String>,
+    pub changed_line_label: String,
+    pub color_moved: bool,
     pub color_only: bool,
+    pub collapse_leading_context: usize,
+    pub color_output: bool,
+    pub comment_emphasis: CommentEmphasis,
     pub commit_regex: Regex,
+    pub context_line_label: String,
     pub commit_style: Style,
+    // The escape sequence to write after the output (see --completion-notification).
+    pub completion_escape_sequence: Option<String>,
     pub cwd_of_delta_process: Option<PathBuf>,
     pub cwd_of_user_shell_process: Option<PathBuf>,
     pub cwd_relative_to_repo_root: Option<String>,
     pub decorations_width: cli::Width,
     pub default_language: String,
+    pub density_column: bool,
+    pub density_column_shades: Vec<char>,
+    pub density_column_window: usize,
     pub diff_args: String,
     pub diff_stat_align_width: usize,
+    pub diff_stat_colors: bool,
+    pub emph_gradient: Option<(ansi_term::Color, ansi_term::Color)>,
+    pub emph_merge_gap: usize,
+    pub emph_token_regex: Option<Regex>,
+    pub emphasis_algorithm: EmphasisAlgorithm,
+    pub emphasis_records_file: Option<PathBuf>,
+    pub emphasize_additions_only: bool,
+    pub empty_file_style: Style,
     pub error_exit_code: i32,
     pub file_added_label: String,
     pub file_copied_label: String,
     pub file_modified_label: String,
+    pub file_order: FileOrder,
     pub file_removed_label: String,
     pub file_renamed_label: String,
+    pub file_separator: String,
+    pub file_separator_style: Style,
     pub file_regex_replacement: Option<RegexReplacement>,
     pub right_arrow: String,
     pub file_style: Style,
+    pub formatting_emph_style: Option<Style>,
     pub git_config: Option<GitConfig>,
     pub git_minus_style: Style,
     pub git_plus_style: Style,
@@ -77,22 +101,39 @@ pub struct Config {
     pub grep_match_word_style: Style,
     pub grep_output_type: Option<GrepType>,
     pub grep_separator_symbol: String,
+    pub grid_width: usize,
+    pub gutter: bool,
+    pub gutter_changed_style: Style,
+    pub gutter_minus_style: Style,
+    pub gutter_plus_style: Style,
+    pub gutter_symbol: String,
+    pub gutter_zero_style: Style,
     pub handle_merge_conflicts: bool,
     pub hostname: Option<String>,
     pub hunk_header_file_style: Style,
     pub hunk_header_line_number_style: Style,
+    pub hunk_header_marker: Option<String>,
     pub hunk_header_style_include_file_path: HunkHeaderIncludeFilePath,
     pub hunk_header_style_include_line_number: HunkHeaderIncludeLineNumber,
     pub hunk_header_style_include_code_fragment: HunkHeaderIncludeCodeFragment,
     pub hunk_header_style: Style,
     pub hunk_label: String,
+    pub hunk_separator: String,
+    pub hunk_separator_style: Style,
+    pub hunk_size_format: String,
     pub hyperlinks_commit_link_format: Option<String>,
     pub hyperlinks_file_link_format: String,
+    pub indent_guide_style: Option<Style>,
+    pub indent_normalization_style: Option<Style>,
     pub hyperlinks: bool,
+    pub inline_edits: bool,
     pub inline_hint_style: Style,
+    pub insertion_point_style: Option<Style>,
     pub inspect_raw_lines: cli::InspectRawLines,
     pub keep_plus_minus_markers: bool,
     pub line_buffer_size: usize,
+    pub line_ending_changes: CollapsibleChanges,
+    pub line_kind_labels: bool,
     pub line_fill_method: BgFillMethod,
     pub line_numbers_format: LeftRight<String>,
     pub line_numbers_style_leftright: LeftRight<Style>,
@@ -100,10 +141,14 @@ pub struct Config {
     pub line_numbers_zero_style: Style,
     pub line_numbers: bool,
     pub styles_map: Option<HashMap<style::AnsiTermStyleEqualityKey, Style>>,
+    pub matching_bracket_style: Option<Style>,
+    pub max_common_prefix_width: usize,
     pub max_line_distance_for_naively_paired_lines: f64,
     pub max_line_distance: f64,
     pub max_line_length: usize,
+    pub max_section_length: usize,
     pub max_syntax_length: usize,
+    pub max_syntax_scope_depth: usize,
     pub merge_conflict_begin_symbol: String,
     pub merge_conflict_ours_diff_header_style: Style,
     pub merge_conflict_theirs_diff_header_style: Style,
@@ -111,31 +156,61 @@ pub struct Config {
     pub minus_emph_style: Style,
     pub minus_empty_line_marker_style: Style,
     pub minus_file: Option<PathBuf>,
+    pub minus_marker: String,
     pub minus_non_emph_style: Style,
     pub minus_style: Style,
+    pub moved_style: Style,
     pub navigate_regex: Option<String>,
     pub navigate: bool,
+    pub no_newline_marker_style: Style,
     pub null_style: Style,
     pub null_syntect_style: SyntectStyle,
+    pub operator_emph_style: Option<Style>,
+    pub operator_tokens: Vec<String>,
+    pub output_buffer_limit: Option<usize>,
+    pub output_format: OutputFormat,
     pub pager: Option<String>,
     pub paging_mode: PagingMode,
+    pub pair_swap_emph_style: Option<Style>,
+    pub paired_tokens: Vec<(String, String)>,
     pub plus_emph_style: Style,
     pub plus_empty_line_marker_style: Style,
     pub plus_file: Option<PathBuf>,
+    pub plus_lines_first: bool,
+    pub plus_marker: String,
     pub plus_non_emph_style: Style,
     pub plus_style: Style,
+    pub preserve_missing_final_newline: bool,
+    pub punctuation_emph_style: Option<Style>,
     pub relative_paths: bool,
+    pub removed_line_label: String,
+    pub rename_emph_style: Option<Style>,
+    pub rename_map: Vec<(String, String)>,
+    pub reordered_lines_style: Option<Style>,
+    pub scope_change_emph_style: Option<Style>,
+    pub scrollback_lines: Option<usize>,
+    pub shell_integration_marks: bool,
     pub show_themes: bool,
+    pub show_whitespace: bool,
     pub side_by_side_data: side_by_side::SideBySideData,
     pub side_by_side: bool,
+    pub syntax_font_styles: bool,
+    pub syntax_map: HashMap<String, String>,
     pub syntax_set: SyntaxSet,
     pub syntax_theme: Option<SyntaxTheme>,
     pub tab_cfg: utils::tabs::TabCfg,
     pub tokenization_regex: Regex,
+    pub trailing_comment_emph_style: Option<Style>,
     pub true_color: bool,
+    pub truncate_lines: bool,
     pub truncation_symbol: String,
     pub whitespace_error_style: Style,
+    pub whitespace_only_changes: CollapsibleChanges,
     pub wrap_config: WrapConfig,
+    pub wrap_lines: bool,
+    pub zebra: bool,
+    pub zebra_style: Style,
+    pub zero_marker: String,
     pub zero_style: Style,
 }
 
@@ -145,6 +220,49 @@ pub enum GrepType {
     Classic,
 }
 
+#[cfg_attr(test, derive(Clone))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum CommentEmphasis {
+    Normal,
+    Suppress,
+    Only,
+}
+
+/// How the changed sections of a pair of lines are inferred (see --emphasis-algorithm).
+#[cfg_attr(test, derive(Clone))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum EmphasisAlgorithm {
+    EditDistance,
+    Lcs,
+}
+
+/// The order in which the files of each commit are displayed (see --file-order).
+#[cfg_attr(test, derive(Clone))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum FileOrder {
+    Input,
+    Size,
+    Path,
+}
+
+/// The format in which the output is written (see --output-format).
+#[cfg_attr(test, derive(Clone))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Ansi,
+    Html,
+}
+
+/// How to display a kind of change that can be summarized by a notice (see
+/// --whitespace-only-changes and --line-ending-changes).
+#[cfg_attr(test, derive(Clone))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum CollapsibleChanges {
+    Show,
+    Compact,
+    Hide,
+}
+
 #[cfg_attr(test, derive(Clone))]
 pub enum HunkHeaderIncludeFilePath {
     Yes,
@@ -154,6 +272,8 @@ pub enum HunkHeaderIncludeFilePath {
 #[cfg_attr(test, derive(Clone))]
 pub enum HunkHeaderIncludeLineNumber {
     Yes,
+    // Include the ranges of lines covered by the hunk instead (see --hunk-header-line-ranges).
+    LineRanges,
     No,
 }
 
@@ -215,6 +335,90 @@ impl From<cli::Opt> for Config {
             ));
         });
 
+        let density_column = opt.density_column && !opt.side_by_side;
+        let density_column_shades: Vec<char> = opt.density_column_shades.chars().collect();
+        if density_column_shades.len() < 2 {
+            fatal("Invalid option for density-column-shades: Expected at least two characters.");
+        }
+        if opt.density_column_window == 0 {
+            fatal("Invalid option for density-column-window: Expected a positive integer.");
+        }
+        if opt.wrap_lines && opt.truncate_lines {
+            fatal("--wrap-lines and --truncate-lines cannot be used together.");
+        }
+
+        let emph_gradient = opt.emph_gradient.as_ref().map(|gradient| {
+            match gradient
+                .split_whitespace()
+                .map(|s| color::parse_color(s, true, opt.git_config()))
+                .collect::<Vec<_>>()[..]
+            {
+                [Some(from), Some(to)] => (from, to),
+                _ => fatal(format!(
+                    "Invalid option for emph-gradient: {gradient}. Expected two colors."
+                )),
+            }
+        });
+
+        let paired_tokens = opt
+            .paired_tokens
+            .split_whitespace()
+            .flat_map(|pair| match pair.split_once('=') {
+                Some((a, b)) if !a.is_empty() && !b.is_empty() => [
+                    (a.to_string(), b.to_string()),
+                    (b.to_string(), a.to_string()),
+                ],
+                _ => fatal(format!(
+                    "Invalid option for paired-tokens: {pair}. Expected entries of the form A=B."
+                )),
+            })
+            .collect();
+
+        let syntax_map = opt
+            .map_syntax
+            .iter()
+            .flat_map(|map_syntax| map_syntax.split(','))
+            .map(|pair| match pair.split_once("=>") {
+                Some((name, syntax))
+                    if !name.trim().is_empty()
+                        && opt
+                            .computed
+                            .syntax_set
+                            .find_syntax_by_token(syntax.trim())
+                            .is_some() =>
+                {
+                    (name.trim().to_string(), syntax.trim().to_string())
+                }
+                _ => fatal(format!(
+                    "Invalid option for map-syntax: {pair}. Expected entries of the form NAME => \
+                     SYNTAX, with SYNTAX one of --list-languages."
+                )),
+            })
+            .collect();
+
+        let rename_map = opt
+            .rename_map
+            .split_whitespace()
+            .map(|rename| match rename.split_once('=') {
+                Some((old, new)) if !old.is_empty() && !new.is_empty() => {
+                    (old.to_string(), new.to_string())
+                }
+                _ => fatal(format!(
+                    "Invalid option for rename-map: {rename}. Expected entries of the form OLD=NEW."
+                )),
+            })
+            .collect();
+
+        let emph_token_regex = opt.emph_token_regex.as_ref().map(|regex| {
+            Regex::new(regex).unwrap_or_else(|_| {
+                fatal(format!(
+                    "Invalid emph-token-regex: {regex}. \
+                     The value must be a valid Rust regular expression. \
+                     See https://docs.rs/regex.",
+                ));
+            })
+        });
+
         let blame_palette = make_blame_palette(opt.blame_palette, opt.computed.color_mode);
 
         if blame_palette.is_empty() {
@@ -236,9 +440,105 @@ impl From<cli::Opt> for Config {
             _ => fatal("Invalid option for line-fill-method: Expected \"ansi\" or \"spaces\"."),
         };
 
+        let emphasis_algorithm = match opt.emphasis_algorithm.as_ref() {
+            "edit-distance" => EmphasisAlgorithm::EditDistance,
+            "lcs" => EmphasisAlgorithm::Lcs,
+            _ => fatal(
+                "Invalid option for emphasis-algorithm: Expected \"edit-distance\" or \"lcs\".",
+            ),
+        };
+
+        let output_format = match opt.output_format.as_ref() {
+            "ansi" => OutputFormat::Ansi,
+            "html" => OutputFormat::Html,
+            _ => fatal("Invalid option for output-format: Expected \"ansi\" or \"html\"."),
+        };
+        if output_format == OutputFormat::Html {
+            for (is_set, option) in [
+                (opt.side_by_side, "--side-by-side"),
+                (opt.wrap_lines, "--wrap-lines"),
+                (opt.truncate_lines, "--truncate-lines"),
+                (opt.grid_width > 0, "--grid-width"),
+                (opt.shell_integration_marks, "--shell-integration-marks"),
+                (
+                    opt.completion_notification != "none",
+                    "--completion-notification",
+                ),
+            ] {
+                if is_set {
+                    fatal(format!(
+                        "{option} cannot be used with --output-format=html."
+                    ));
+                }
+            }
+        }
+
+        let file_order = match opt.file_order.as_ref() {
+            "input" => FileOrder::Input,
+            "size" => FileOrder::Size,
+            "path" => FileOrder::Path,
+            _ => fatal("Invalid option for file-order: Expected \"input\", \"size\", or \"path\"."),
+        };
+
+        let comment_emphasis = match opt.comment_emphasis.as_ref() {
+            "normal" => CommentEmphasis::Normal,
+            "suppress" => CommentEmphasis::Suppress,
+            "only" => CommentEmphasis::Only,
+            _ => fatal(
+                "Invalid option for comment-emphasis: Expected \"normal\", \"suppress\", or \"only\".",
+            ),
+        };
+
+        let stdout_is_term = opt.computed.stdout_is_term;
+        let completion_escape_sequence = match opt.completion_notification.as_str() {
+            "none" => None,
+            "bell" => Some("\x07".to_string()),
+            notification => match notification.strip_prefix("title:") {
+                Some(title) => {
+                    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+                    Some(format!("\x1b]0;{title}\x07"))
+                }
+                None => fatal(
+                    "Invalid option for completion-notification: Expected \"none\", \"bell\", or \"title:TEXT\".",
+                ),
+            },
+        }
+        .filter(|_| stdout_is_term || TESTING);
+
+        let line_ending_changes = match opt.line_ending_changes.as_ref() {
+            "show" => CollapsibleChanges::Show,
+            "compact" => CollapsibleChanges::Compact,
+            "hide" => CollapsibleChanges::Hide,
+            _ => fatal(
+                "Invalid option for line-ending-changes: Expected \"show\", \"compact\", or \"hide\".",
+            ),
+        };
+
+        let whitespace_only_changes = match opt.whitespace_only_changes.as_ref() {
+            "show" => CollapsibleChanges::Show,
+            "compact" => CollapsibleChanges::Compact,
+            "hide" => CollapsibleChanges::Hide,
+            _ => fatal(
+                "Invalid option for whitespace-only-changes: Expected \"show\", \"compact\", or \"hide\".",
+            ),
+        };
+
+        let side_by_side_ratio = opt
+            .side_by_side_ratio
+            .split_once('/')
+            .and_then(|(left, right)| Some((left.trim().parse().ok()?, right.trim().parse().ok()?)))
+            .filter(|&(left, right): &(usize, usize)| left > 0 && right > 0)
+            .unwrap_or_else(|| {
+                fatal(
+                    "Invalid value for side-by-side-ratio: Expected two positive integers separated \
+                     by '/', e.g. \"40/60\".",
+                )
+            });
+
         let side_by_side_data = side_by_side::SideBySideData::new_sbs(
             &opt.computed.decorations_width,
             &opt.computed.available_terminal_width,
+            side_by_side_ratio,
         );
         let side_by_side_data = ansifill::UseFullPanelWidth::sbs_odd_fix(
             &opt.computed.decorations_width,
@@ -281,10 +581,18 @@ impl From<cli::Opt> for Config {
         );
 
         Self {
-            available_terminal_width: opt.computed.available_terminal_width,
+            added_line_label: opt.added_line_label,
+            // Keep lines filled with spaces clear of the density column.
+            available_terminal_width: if density_column {
+                opt.computed.available_terminal_width.saturating_sub(1)
+            } else {
+                opt.computed.available_terminal_width
+            },
+            // An html fragment has no terminal to extend background colors to.
             background_color_extends_to_terminal_width: opt
                 .computed
-                .background_color_extends_to_terminal_width,
+                .background_color_extends_to_terminal_width
+                && output_format != OutputFormat::Html,
             blame_format: opt.blame_format,
             blame_code_style: styles.remove("blame-code-style"),
             blame_palette,
@@ -292,29 +600,62 @@ impl From<cli::Opt> for Config {
             blame_separator_style: styles.remove("blame-separator-style"),
             blame_timestamp_format: opt.blame_timestamp_format,
             blame_timestamp_output_format: opt.blame_timestamp_output_format,
+            changed_line_label: opt.changed_line_label,
             commit_style: styles["commit-style"],
+            color_moved: opt.color_moved,
             color_only: opt.color_only,
+            collapse_leading_context: opt.collapse_leading_context,
+            color_output: match opt.color.as_ref() {
+                "always" => true,
+                "never" => false,
+                "auto" => opt.computed.stdout_is_term,
+                _ => {
+                    fatal("Invalid option for color: Expected \"auto\", \"always\", or \"never\".")
+                }
+            },
+            comment_emphasis,
+            completion_escape_sequence,
             commit_regex,
+            context_line_label: opt.context_line_label,
             cwd_of_delta_process,
             cwd_of_user_shell_process,
             cwd_relative_to_repo_root,
             decorations_width: opt.computed.decorations_width,
             default_language: opt.default_language,
+            density_column,
+            density_column_shades,
+            density_column_window: opt.density_column_window,
             diff_args: opt.diff_args,
             diff_stat_align_width: opt.diff_stat_align_width,
+            diff_stat_colors: opt.diff_stat_colors,
+            emph_gradient,
+            emph_merge_gap: opt.emph_merge_gap,
+            emph_token_regex,
+            emphasis_algorithm,
+            emphasis_records_file: opt.emphasis_records_file.map(PathBuf::from),
+            emphasize_additions_only: opt.emphasize_additions_only,
+            empty_file_style: styles["empty-file-style"],
             error_exit_code: 2, // Use 2 for error because diff uses 0 and 1 for non-error.
             file_added_label,
             file_copied_label,
             file_modified_label,
+            file_order,
             file_removed_label,
             file_renamed_label,
+            file_separator: opt.file_separator,
+            file_separator_style: styles["file-separator-style"],
             file_regex_replacement: opt
                 .file_regex_replacement
                 .as_deref()
                 .and_then(RegexReplacement::from_sed_command),
             right_arrow,
+            hunk_header_marker: opt.hunk_header_marker,
             hunk_label,
+            hunk_separator: opt.hunk_separator,
+            hunk_separator_style: styles["hunk-separator-style"],
+            hunk_size_format: opt.hunk_size_format,
             file_style: styles["file-style"],
+            formatting_emph_style: styles.get("formatting-emph-style").copied(),
             git_config: opt.git_config,
             grep_context_line_style: styles["grep-context-line-style"],
             grep_file_style: styles["grep-file-style"],
@@ -326,6 +667,13 @@ impl From<cli::Opt> for Config {
             grep_match_word_style: styles["grep-match-word-style"],
             grep_output_type,
             grep_separator_symbol: opt.grep_separator_symbol,
+            grid_width: opt.grid_width,
+            gutter: opt.gutter,
+            gutter_changed_style: styles["gutter-changed-style"],
+            gutter_minus_style: styles["gutter-minus-style"],
+            gutter_plus_style: styles["gutter-plus-style"],
+            gutter_symbol: opt.gutter_symbol,
+            gutter_zero_style: styles["gutter-zero-style"],
             handle_merge_conflicts: !opt.raw,
             hostname: opt.env.hostname,
             hunk_header_file_style: styles["hunk-header-file-style"],
@@ -345,7 +693,11 @@ impl From<cli::Opt> for Config {
                 .split(' ')
                 .any(|s| s == "line-number")
             {
-                HunkHeaderIncludeLineNumber::Yes
+                if opt.hunk_header_line_ranges {
+                    HunkHeaderIncludeLineNumber::LineRanges
+                } else {
+                    HunkHeaderIncludeLineNumber::Yes
+                }
             } else {
                 HunkHeaderIncludeLineNumber::No
             },
@@ -361,13 +713,18 @@ impl From<cli::Opt> for Config {
             hyperlinks: opt.hyperlinks,
             hyperlinks_commit_link_format: opt.hyperlinks_commit_link_format,
             hyperlinks_file_link_format: opt.hyperlinks_file_link_format,
+            indent_guide_style: styles.get("indent-guide-style").copied(),
+            indent_normalization_style: styles.get("indent-normalization-style").copied(),
             inspect_raw_lines: opt.computed.inspect_raw_lines,
+            inline_edits: opt.inline_edits,
             inline_hint_style: styles["inline-hint-style"],
+            insertion_point_style: styles.get("insertion-point-style").copied(),
             keep_plus_minus_markers: opt.keep_plus_minus_markers,
-            line_fill_method: if !opt.computed.stdout_is_term && !TESTING {
+            line_fill_method: if !opt.computed.stdout_is_term && !TESTING || opt.grid_width > 0 {
                 // Don't write ANSI sequences (which rely on the width of the
                 // current terminal) into a file. Also see UseFullPanelWidth.
-                // But when testing always use given value.
+                // But when testing always use given value. A grid cell is
+                // not as wide as the terminal.
                 BgFillMethod::Spaces
             } else {
                 line_fill_method
@@ -387,6 +744,11 @@ impl From<cli::Opt> for Config {
             ),
             line_numbers_zero_style: styles["line-numbers-zero-style"],
             line_buffer_size: opt.line_buffer_size,
+            line_ending_changes,
+            line_kind_labels: opt.line_kind_labels,
+            matching_bracket_style: styles.get("matching-bracket-style").copied(),
+            max_common_prefix_width: opt.max_common_prefix_width,
+            max_section_length: opt.max_section_length,
             max_line_distance: opt.max_line_distance,
             max_line_distance_for_naively_paired_lines,
             max_line_length: if opt.side_by_side {
@@ -398,6 +760,7 @@ impl From<cli::Opt> for Config {
                 opt.max_line_length
             },
             max_syntax_length: opt.max_syntax_length,
+            max_syntax_scope_depth: opt.max_syntax_scope_depth,
             merge_conflict_begin_symbol: opt.merge_conflict_begin_symbol,
             merge_conflict_ours_diff_header_style: styles["merge-conflict-ours-diff-header-style"],
             merge_conflict_theirs_diff_header_style: styles
@@ -406,34 +769,73 @@ impl From<cli::Opt> for Config {
             minus_emph_style: styles["minus-emph-style"],
             minus_empty_line_marker_style: styles["minus-empty-line-marker-style"],
             minus_file: opt.minus_file,
+            minus_marker: opt.minus_marker,
             minus_non_emph_style: styles["minus-non-emph-style"],
             minus_style: styles["minus-style"],
+            moved_style: styles["moved-style"],
             navigate: opt.navigate,
+            no_newline_marker_style: styles["no-newline-marker-style"],
             navigate_regex,
             null_style: Style::new(),
             null_syntect_style: SyntectStyle::default(),
+            operator_emph_style: styles.get("operator-emph-style").copied(),
+            operator_tokens: opt
+                .operator_tokens
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+            output_buffer_limit: (opt.output_buffer_limit != 0).then_some(opt.output_buffer_limit),
+            output_format,
             pager: opt.pager,
             paging_mode: opt.computed.paging_mode,
+            pair_swap_emph_style: styles.get("pair-swap-emph-style").copied(),
+            paired_tokens,
             plus_emph_style: styles["plus-emph-style"],
             plus_empty_line_marker_style: styles["plus-empty-line-marker-style"],
             plus_file: opt.plus_file,
+            plus_lines_first: opt.plus_lines_first,
+            plus_marker: opt.plus_marker,
             plus_non_emph_style: styles["plus-non-emph-style"],
             plus_style: styles["plus-style"],
+            preserve_missing_final_newline: opt.preserve_missing_final_newline,
             git_minus_style: styles["git-minus-style"],
             git_plus_style: styles["git-plus-style"],
+            punctuation_emph_style: styles.get("punctuation-emph-style").copied(),
             relative_paths: opt.relative_paths,
+            removed_line_label: opt.removed_line_label,
+            rename_emph_style: styles.get("rename-emph-style").copied(),
+            rename_map,
+            reordered_lines_style: styles.get("reordered-lines-style").copied(),
+            scope_change_emph_style: styles.get("scope-change-emph-style").copied(),
+            scrollback_lines: (opt.scrollback_lines != 0).then_some(opt.scrollback_lines),
+            shell_integration_marks: opt.shell_integration_marks && (stdout_is_term || TESTING),
             show_themes: opt.show_themes,
+            show_whitespace: opt.show_whitespace,
             side_by_side: opt.side_by_side && !handlers::hunk::is_word_diff(),
             side_by_side_data,
             styles_map,
+            syntax_font_styles: opt.syntax_font_styles,
+            syntax_map,
             syntax_set: opt.computed.syntax_set,
             syntax_theme: opt.computed.syntax_theme,
-            tab_cfg: utils::tabs::TabCfg::new(opt.tab_width),
+            tab_cfg: utils::tabs::TabCfg::new(opt.tab_width).with_symbol(
+                match opt.tab_symbol.as_str() {
+                    "" if opt.show_whitespace => "→",
+                    symbol => symbol,
+                },
+            ),
             tokenization_regex,
+            trailing_comment_emph_style: styles.get("trailing-comment-emph-style").copied(),
             true_color: opt.computed.true_color,
+            truncate_lines: opt.truncate_lines,
             truncation_symbol: format!("{}→{}", ansi::ANSI_SGR_REVERSE, ansi::ANSI_SGR_RESET),
             wrap_config,
+            wrap_lines: opt.wrap_lines,
             whitespace_error_style: styles["whitespace-error-style"],
+            whitespace_only_changes,
+            zebra: opt.zebra,
+            zebra_style: styles["zebra-style"],
+            zero_marker: opt.zero_marker,
             zero_style: styles["zero-style"],
         }
     }
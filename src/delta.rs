@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{self, BufRead, IsTerminal, Write};
 
 use bytelines::ByteLines;
@@ -7,12 +7,16 @@ use bytelines::ByteLines;
 use crate::ansi;
 use crate::config::delta_unreachable;
 use crate::config::Config;
-use crate::config::GrepType;
+use crate::config::{FileOrder, GrepType, OutputFormat};
+use crate::emitter::{AnsiEmitter, Emitter, HtmlEmitter};
 use crate::features;
+use crate::file_order;
 use crate::handlers::grep;
 use crate::handlers::hunk_header::{AmbiguousDiffMinusCounter, ParsedHunkHeader};
 use crate::handlers::{self, merge_conflict};
+use crate::moved_lines;
 use crate::paint::Painter;
+use crate::scrollback::ScrollbackBuffer;
 use crate::style::DecorationStyle;
 use crate::utils;
 
@@ -89,7 +93,7 @@ pub enum Source {
 // | HunkMinus   | flush, emit | flush, emit | flush, emit | flush, emit | push        | push     |
 // | HunkPlus    | flush, emit | flush, emit | flush, emit | flush, emit | flush, push | push     |
 
-pub struct StateMachine<'a> {
+pub struct StateMachine<'a, E: Emitter = AnsiEmitter> {
     pub line: String,
     pub raw_line: String,
     pub state: State,
@@ -100,7 +104,7 @@ pub struct StateMachine<'a> {
     pub plus_file_event: handlers::diff_header::FileEvent,
     pub diff_line: String,
     pub mode_info: String,
-    pub painter: Painter<'a>,
+    pub painter: Painter<'a, E>,
     pub config: &'a Config,
 
     // When a file is modified, we use lines starting with '---' or '+++' to obtain the file name.
@@ -112,17 +116,211 @@ pub struct StateMachine<'a> {
     pub handled_diff_header_header_line_file_pair: Option<(String, String)>,
     pub blame_key_colors: HashMap<String, String>,
     pub minus_line_counter: AmbiguousDiffMinusCounter,
+    // The index of the current line in the input, and those of the moved lines (see
+    // --color-moved).
+    pub line_index: usize,
+    pub moved_lines: HashSet<usize>,
 }
 
+// Semantic prompt marks (see --shell-integration-marks).
+pub const OSC_133_COMMAND_OUTPUT_START: &str = "\x1b]133;C\x07";
+pub const OSC_133_COMMAND_FINISHED: &str = "\x1b]133;D\x07";
+
 pub fn delta<I>(lines: ByteLines<I>, writer: &mut dyn Write, config: &Config) -> std::io::Result<()>
 where
     I: BufRead,
 {
-    StateMachine::new(writer, config).consume(lines)
+    if config.file_order != FileOrder::Input || config.color_moved {
+        let input = file_order::reorder_files(read_lines(lines)?, &config.file_order);
+        let moved_lines = if config.color_moved {
+            moved_lines::find_moved_lines(&input)
+        } else {
+            HashSet::new()
+        };
+        let input: Vec<u8> = input
+            .into_iter()
+            .flat_map(|mut line| {
+                line.push(b'\n');
+                line
+            })
+            .collect();
+        return delta_in_order(
+            ByteLines::new(input.as_slice()),
+            writer,
+            config,
+            moved_lines,
+        );
+    }
+    delta_in_order(lines, writer, config, HashSet::new())
+}
+
+/// Read all of `lines`, for --file-order or --color-moved.
+fn read_lines<I>(mut lines: ByteLines<I>) -> io::Result<Vec<Vec<u8>>>
+where
+    I: BufRead,
+{
+    let mut input = Vec::new();
+    while let Some(line) = lines.next() {
+        input.push(line?.to_vec());
+    }
+    Ok(input)
+}
+
+fn delta_in_order<I>(
+    lines: ByteLines<I>,
+    writer: &mut dyn Write,
+    config: &Config,
+    moved_lines: HashSet<usize>,
+) -> std::io::Result<()>
+where
+    I: BufRead,
+{
+    if let Some(n_lines) = config.scrollback_lines {
+        let mut scrollback = ScrollbackBuffer::new(n_lines);
+        delta_gridded(lines, &mut scrollback, config, moved_lines)?;
+        scrollback.write_to(writer)
+    } else {
+        delta_gridded(lines, writer, config, moved_lines)
+    }
+}
+
+fn delta_gridded<I>(
+    lines: ByteLines<I>,
+    writer: &mut dyn Write,
+    config: &Config,
+    moved_lines: HashSet<usize>,
+) -> std::io::Result<()>
+where
+    I: BufRead,
+{
+    if config.grid_width > 0 {
+        let mut writer = ansi::GridWriter::new(writer, config.grid_width);
+        delta_ungridded(lines, &mut writer, config, moved_lines)?;
+        writer.finish()
+    } else {
+        delta_ungridded(lines, writer, config, moved_lines)
+    }
+}
+
+fn delta_ungridded<I>(
+    lines: ByteLines<I>,
+    writer: &mut dyn Write,
+    config: &Config,
+    moved_lines: HashSet<usize>,
+) -> std::io::Result<()>
+where
+    I: BufRead,
+{
+    if config.output_format == OutputFormat::Html {
+        // Without --color-output, the emitter formats no color or font style.
+        let emitter = HtmlEmitter::new(config.color_output);
+        let mut machine = StateMachine::with_emitter(writer, config, emitter);
+        machine.moved_lines = moved_lines;
+        machine.consume(lines)
+    } else if config.color_output {
+        let mut machine = StateMachine::new(writer, config);
+        machine.moved_lines = moved_lines;
+        machine.consume(lines)
+    } else {
+        let mut writer = ansi::StripAnsiWriter::new(writer);
+        {
+            let mut machine = StateMachine::new(&mut writer, config);
+            machine.moved_lines = moved_lines;
+            machine.consume(lines)?;
+        }
+        writer.flush()
+    }
+}
+
+/// Like [`delta`], reading the lines of `reader`. If --preserve-missing-final-newline is in effect
+/// and `reader` does not end with a newline, neither does the output.
+pub fn delta_from_reader<R>(reader: R, writer: &mut dyn Write, config: &Config) -> io::Result<()>
+where
+    R: BufRead,
+{
+    if !config.preserve_missing_final_newline {
+        return delta(ByteLines::new(reader), writer, config);
+    }
+    let mut reader = LastByteReader {
+        reader,
+        last_byte: None,
+    };
+    let mut writer = FinalNewlineWriter {
+        writer,
+        has_pending_newline: false,
+    };
+    delta(ByteLines::new(&mut reader), &mut writer, config)?;
+    if writer.has_pending_newline && matches!(reader.last_byte, Some(b'\n') | None) {
+        writer.writer.write_all(b"\n")?;
+    }
+    writer.writer.flush()
+}
+
+/// A reader recording the last byte consumed from the underlying reader.
+struct LastByteReader<R> {
+    reader: R,
+    last_byte: Option<u8>,
+}
+
+impl<R: BufRead> io::Read for LastByteReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        if n > 0 {
+            self.last_byte = Some(buf[n - 1]);
+        }
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for LastByteReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if amt > 0 {
+            // The buffer has already been filled, so this does not read.
+            if let Ok(buf) = self.reader.fill_buf() {
+                self.last_byte = buf.get(amt - 1).copied();
+            }
+        }
+        self.reader.consume(amt)
+    }
+}
+
+/// A writer holding back a newline ending what has been written to it, until more is written.
+struct FinalNewlineWriter<'a> {
+    writer: &'a mut dyn Write,
+    has_pending_newline: bool,
+}
+
+impl Write for FinalNewlineWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some((last, rest)) = buf.split_last() else {
+            return Ok(0);
+        };
+        if self.has_pending_newline {
+            self.writer.write_all(b"\n")?;
+        }
+        self.has_pending_newline = *last == b'\n';
+        self.writer
+            .write_all(if self.has_pending_newline { rest } else { buf })?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
 }
 
 impl<'a> StateMachine<'a> {
     pub fn new(writer: &'a mut dyn Write, config: &'a Config) -> Self {
+        Self::with_emitter(writer, config, AnsiEmitter)
+    }
+}
+
+impl<'a, E: Emitter> StateMachine<'a, E> {
+    pub fn with_emitter(writer: &'a mut dyn Write, config: &'a Config, emitter: E) -> Self {
         Self {
             line: "".to_string(),
             raw_line: "".to_string(),
@@ -136,10 +334,12 @@ impl<'a> StateMachine<'a> {
             mode_info: "".to_string(),
             current_file_pair: None,
             handled_diff_header_header_line_file_pair: None,
-            painter: Painter::new(writer, config),
+            painter: Painter::with_emitter(writer, config, emitter),
             config,
             blame_key_colors: HashMap::new(),
             minus_line_counter: AmbiguousDiffMinusCounter::not_needed(),
+            line_index: 0,
+            moved_lines: HashSet::new(),
         }
     }
 
@@ -147,6 +347,9 @@ impl<'a> StateMachine<'a> {
     where
         I: BufRead,
     {
+        if self.config.shell_integration_marks {
+            write!(self.painter.writer, "{OSC_133_COMMAND_OUTPUT_START}")?;
+        }
         while let Some(Ok(raw_line_bytes)) = lines.next() {
             self.ingest_line(raw_line_bytes);
 
@@ -181,12 +384,20 @@ impl<'a> StateMachine<'a> {
                 || self.handle_grep_line()?
                 || self.should_skip_line()
                 || self.emit_line_unchanged()?;
+            self.line_index += 1;
         }
 
         self.handle_pending_line_with_diff_name()?;
-        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.paint_end_of_hunk();
         self.painter.emit()?;
-        Ok(())
+        self.painter.write_emphasis_records()?;
+        if self.config.shell_integration_marks {
+            write!(self.painter.writer, "{OSC_133_COMMAND_FINISHED}")?;
+        }
+        if let Some(escape_sequence) = &self.config.completion_escape_sequence {
+            write!(self.painter.writer, "{escape_sequence}")?;
+        }
+        self.painter.writer.finish()
     }
 
     fn ingest_line(&mut self, raw_line_bytes: &[u8]) {
@@ -245,11 +456,11 @@ impl<'a> StateMachine<'a> {
     /// Emit unchanged any line that delta does not handle.
     pub fn emit_line_unchanged(&mut self) -> std::io::Result<bool> {
         self.painter.emit()?;
-        writeln!(
-            self.painter.writer,
-            "{}",
-            format_raw_line(&self.raw_line, self.config)
-        )?;
+        let mut line = String::new();
+        self.painter
+            .emitter
+            .emit_ansi_text(&format_raw_line(&self.raw_line, self.config), &mut line);
+        writeln!(self.painter.writer, "{line}")?;
         let handled_line = true;
         Ok(handled_line)
     }
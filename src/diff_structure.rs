@@ -0,0 +1,248 @@
+use std::ops::Range;
+
+use crate::ansi;
+use crate::handlers::diff_header::{get_repeated_file_path_from_diff_line, parse_diff_header_line};
+use crate::handlers::hunk_header::parse_hunk_header;
+use crate::minusplus::*;
+
+/// The structure of the diff of one file: its paths in the minus and plus versions, and the line
+/// ranges of its hunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    pub paths: MinusPlus<String>,
+    /// For each hunk, the (1-based, end-exclusive) range of lines it covers in the minus and plus
+    /// files. For a combined diff, the minus range is that of the first parent.
+    pub hunks: Vec<MinusPlus<Range<usize>>>,
+}
+
+impl FileDiff {
+    /// Return the file diff as a JSON object, as written by --parse-structure.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "paths": {
+                "minus": self.paths[Minus],
+                "plus": self.paths[Plus],
+            },
+            "hunks": self
+                .hunks
+                .iter()
+                .map(|hunk| {
+                    serde_json::json!({
+                        "minus": [hunk[Minus].start, hunk[Minus].end],
+                        "plus": [hunk[Plus].start, hunk[Plus].end],
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Return the files and hunks of `diff`, without painting or syntax highlighting anything.
+pub fn parse_structure(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut is_git_diff = false;
+    // Numbers of lines remaining in the current hunk of the first minus file and the plus file,
+    // and the number of merge parents.
+    let mut hunk_remainder: Option<(MinusPlus<usize>, usize)> = None;
+
+    for line in diff.lines() {
+        let line = ansi::strip_ansi_codes(line);
+        if let Some((remainder, n_parents)) = hunk_remainder.as_mut() {
+            // E.g. "\ No newline at end of file"
+            if line.starts_with('\\') {
+                continue;
+            }
+            if let Some(prefix) = line.get(..*n_parents) {
+                if !prefix.contains('-') {
+                    remainder[Plus] = remainder[Plus].saturating_sub(1);
+                }
+                if !prefix.starts_with('+') {
+                    remainder[Minus] = remainder[Minus].saturating_sub(1);
+                }
+            }
+            if remainder[Minus] == 0 && remainder[Plus] == 0 {
+                hunk_remainder = None;
+            }
+            continue;
+        }
+        if line.starts_with("diff ") {
+            // Git prefixes the paths of the "---" and "+++" lines with "a/" and "b/".
+            is_git_diff = line.starts_with("diff --git ")
+                || line.starts_with("diff --cc ")
+                || line.starts_with("diff --combined ");
+            let path = get_repeated_file_path_from_diff_line(&line)
+                .or_else(|| {
+                    line.strip_prefix("diff --cc ")
+                        .or_else(|| line.strip_prefix("diff --combined "))
+                        .map(str::to_string)
+                })
+                .unwrap_or_default();
+            files.push(FileDiff {
+                paths: MinusPlus::new(path.clone(), path),
+                hunks: Vec::new(),
+            });
+        } else if line.starts_with("@@") {
+            let coordinates = match parse_hunk_header(&line) {
+                Some(parsed) => parsed.line_numbers_and_hunk_lengths,
+                None => continue,
+            };
+            if let (Some(file), Some(&(minus_start, minus_len)), Some(&(plus_start, plus_len))) =
+                (files.last_mut(), coordinates.first(), coordinates.last())
+            {
+                file.hunks.push(MinusPlus::new(
+                    minus_start..minus_start + minus_len,
+                    plus_start..plus_start + plus_len,
+                ));
+                hunk_remainder = Some((MinusPlus::new(minus_len, plus_len), coordinates.len() - 1));
+                if minus_len == 0 && plus_len == 0 {
+                    hunk_remainder = None;
+                }
+            }
+        } else {
+            let minus_or_plus = if line.starts_with("--- ")
+                || line.starts_with("rename from ")
+                || line.starts_with("copy from ")
+            {
+                Minus
+            } else if line.starts_with("+++ ")
+                || line.starts_with("rename to ")
+                || line.starts_with("copy to ")
+            {
+                Plus
+            } else {
+                continue;
+            };
+            // The output of `diff -u` has no "diff" line preceding each file.
+            if line.starts_with("--- ") && files.last().is_none_or(|file| !file.hunks.is_empty()) {
+                is_git_diff = false;
+                files.push(FileDiff {
+                    paths: MinusPlus::new(String::new(), String::new()),
+                    hunks: Vec::new(),
+                });
+            }
+            if let Some(file) = files.last_mut() {
+                file.paths[minus_or_plus] = parse_diff_header_line(&line, is_git_diff).0;
+            }
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_diff(minus: &str, plus: &str, hunks: &[(Range<usize>, Range<usize>)]) -> FileDiff {
+        FileDiff {
+            paths: MinusPlus::new(minus.to_string(), plus.to_string()),
+            hunks: hunks
+                .iter()
+                .map(|(minus, plus)| MinusPlus::new(minus.clone(), plus.clone()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_structure() {
+        let diff = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Sat Mar 7 15:44:16 2020 -0500
+
+    Add files
+
+diff --git a/src/one.rs b/src/one.rs
+index 1111111..2222222 100644
+--- a/src/one.rs
++++ b/src/one.rs
+@@ -1,3 +1,3 @@
+ a
+--- b
++++ b
+ c
+@@ -10,2 +10,3 @@ fn f() {
+ d
++e
+ f
+\\ No newline at end of file
+diff --git a/old.rs b/new.rs
+similarity index 90%
+rename from old.rs
+rename to new.rs
+index 3333333..4444444 100644
+--- a/old.rs
++++ b/new.rs
+@@ -5 +5 @@
+-x
++y
+diff --git a/image.png b/image.png
+new file mode 100644
+index 0000000..5555555
+Binary files /dev/null and b/image.png differ
+diff --git a/gone.rs b/gone.rs
+deleted file mode 100644
+index 6666666..0000000
+--- a/gone.rs
++++ /dev/null
+@@ -1,2 +0,0 @@
+-g
+-h
+";
+        assert_eq!(
+            parse_structure(diff),
+            vec![
+                file_diff(
+                    "src/one.rs",
+                    "src/one.rs",
+                    &[(1..4, 1..4), (10..12, 10..13)]
+                ),
+                file_diff("old.rs", "new.rs", &[(5..6, 5..6)]),
+                file_diff("image.png", "image.png", &[]),
+                file_diff("gone.rs", "/dev/null", &[(1..3, 0..0)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_structure_diff_unified() {
+        let diff = "\
+--- one.lua\t2024-01-01 00:00:00.000000000 +0000
++++ two.lua\t2024-01-01 00:00:00.000000000 +0000
+@@ -1,2 +1,2 @@
+--- a comment
++-- another comment
+ x = 1
+--- three.lua\t2024-01-01 00:00:00.000000000 +0000
++++ four.lua\t2024-01-01 00:00:00.000000000 +0000
+@@ -3 +3,2 @@
+ y = 2
++z = 3
+";
+        assert_eq!(
+            parse_structure(diff),
+            vec![
+                file_diff("one.lua", "two.lua", &[(1..3, 1..3)]),
+                file_diff("three.lua", "four.lua", &[(3..4, 3..5)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_structure_combined_diff() {
+        let diff = "\
+diff --cc src/main.rs
+index 1111111,2222222..3333333
+--- a/src/main.rs
++++ b/src/main.rs
+@@@ -1,3 -1,2 +1,3 @@@
+  a
+- b
+ +c
+++d
+";
+        assert_eq!(
+            parse_structure(diff),
+            vec![file_diff("src/main.rs", "src/main.rs", &[(1..4, 1..4)])]
+        );
+    }
+}
@@ -1,10 +1,12 @@
+use std::ops::Range;
+
 use regex::Regex;
 
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::align;
-use crate::minusplus::MinusPlus;
+use crate::minusplus::*;
 
 /// Infer the edit operations responsible for the differences between a collection of old and new
 /// lines. A "line" is a string. An annotated line is a Vec of (op, &str) pairs, where the &str
@@ -105,6 +107,140 @@ where
     (annotated_minus_lines, annotated_plus_lines, line_alignment)
 }
 
+/// Change to `emph` the operation of each run of sections lying between two `emph` sections of an
+/// annotated line, if the run is at most `max_gap_width` wide. This favors fewer, larger emphasized
+/// regions over many scattered ones.
+pub fn merge_emph_gaps<EditOperation>(
+    annotated_line: &mut [(EditOperation, &str)],
+    emph: EditOperation,
+    max_gap_width: usize,
+) where
+    EditOperation: Copy + PartialEq,
+{
+    let mut prev_emph_index = None;
+    for i in 0..annotated_line.len() {
+        if annotated_line[i].0 != emph {
+            continue;
+        }
+        if let Some(prev) = prev_emph_index {
+            let gap = &mut annotated_line[prev + 1..i];
+            let gap_width: usize = gap.iter().map(|(_, s)| s.width()).sum();
+            if gap_width <= max_gap_width && !gap.iter().any(|(_, s)| s.contains('\n')) {
+                for (op, _) in gap.iter_mut() {
+                    *op = emph;
+                }
+            }
+        }
+        prev_emph_index = Some(i);
+    }
+}
+
+/// Return `line` converted to lowercase, without leading and trailing whitespace, and with each run
+/// of whitespace replaced by a single space. Also return, for each byte of the normalized line, the
+/// byte offset in `line` of the character it derives from.
+pub fn normalize_case_and_whitespace(line: &str) -> (String, Vec<usize>) {
+    let trimmed_offset = line.len() - line.trim_start().len();
+    let mut normalized = String::with_capacity(line.len());
+    let mut offsets = Vec::with_capacity(line.len());
+    let mut whitespace_offset = None;
+    for (i, c) in line.trim().char_indices() {
+        let i = trimmed_offset + i;
+        if c.is_whitespace() {
+            whitespace_offset.get_or_insert(i);
+            continue;
+        }
+        if let Some(whitespace_offset) = whitespace_offset.take() {
+            normalized.push(' ');
+            offsets.push(whitespace_offset);
+        }
+        for c in c.to_lowercase() {
+            normalized.push(c);
+            offsets.resize(normalized.len(), i);
+        }
+    }
+    (normalized, offsets)
+}
+
+/// The byte ranges of a token in a line and in the line returned by [`apply_renames`].
+pub type RenamedToken = (Range<usize>, Range<usize>);
+
+/// Return `line` with each token (as defined by `tokenization_regex`) that is the old name of one
+/// of `renames` replaced by its new name. Also return, for each replaced token, its byte range in
+/// `line` and in the returned line.
+pub fn apply_renames(
+    line: &str,
+    tokenization_regex: &Regex,
+    renames: &[(String, String)],
+) -> (String, Vec<RenamedToken>) {
+    let mut renamed = String::with_capacity(line.len());
+    let mut renamed_tokens = Vec::new();
+    let mut offset = 0;
+    for token in tokenization_regex.find_iter(line) {
+        let Some((_, new)) = renames.iter().find(|(old, _)| old == token.as_str()) else {
+            continue;
+        };
+        renamed.push_str(&line[offset..token.start()]);
+        let start = renamed.len();
+        renamed.push_str(new);
+        renamed_tokens.push((token.range(), start..renamed.len()));
+        offset = token.end();
+    }
+    renamed.push_str(&line[offset..]);
+    (renamed, renamed_tokens)
+}
+
+/// The largest number of cells of the table computed by [`lcs_emphasis_ranges`].
+const MAX_LCS_TABLE_SIZE: usize = 1 << 22;
+
+/// Return the byte ranges of the characters of `minus_line` and of `plus_line` that are not on a
+/// longest common subsequence of the characters of the two lines, or `None` if the lines are too
+/// long for this to be computed cheaply.
+pub fn lcs_emphasis_ranges(
+    minus_line: &str,
+    plus_line: &str,
+) -> Option<MinusPlus<Vec<Range<usize>>>> {
+    let x: Vec<(usize, char)> = minus_line.char_indices().collect();
+    let y: Vec<(usize, char)> = plus_line.char_indices().collect();
+    let width = y.len() + 1;
+    if (x.len() + 1).saturating_mul(width) > MAX_LCS_TABLE_SIZE {
+        return None;
+    }
+    // lengths[i * width + j] is the length of a longest common subsequence of x[i..] and y[j..].
+    // It is at most sqrt(MAX_LCS_TABLE_SIZE), so fits in a u16.
+    let mut lengths = vec![0_u16; (x.len() + 1) * width];
+    for i in (0..x.len()).rev() {
+        for j in (0..y.len()).rev() {
+            lengths[i * width + j] = if x[i].1 == y[j].1 {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+    let mut ranges = MinusPlus::new(Vec::new(), Vec::new());
+    let emphasize =
+        |ranges: &mut Vec<Range<usize>>, (offset, c): (usize, char)| match ranges.last_mut() {
+            Some(range) if range.end == offset => range.end += c.len_utf8(),
+            _ => ranges.push(offset..offset + c.len_utf8()),
+        };
+    let (mut i, mut j) = (0, 0);
+    while i < x.len() || j < y.len() {
+        if i < x.len() && j < y.len() && x[i].1 == y[j].1 {
+            i += 1;
+            j += 1;
+        } else if j == y.len()
+            || (i < x.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            emphasize(&mut ranges[Minus], x[i]);
+            i += 1;
+        } else {
+            emphasize(&mut ranges[Plus], y[j]);
+            j += 1;
+        }
+    }
+    Some(ranges)
+}
+
 // Return `None` if there is no trailing whitespace.
 // Return `Some(content)` where content is trimmed if there was some trailing whitespace
 fn get_contents_before_trailing_whitespace(line: &str) -> Option<&str> {
@@ -314,6 +450,18 @@ mod tests {
 
     use EditOperation::*;
 
+    #[test]
+    fn test_lcs_emphasis_ranges() {
+        let ranges = lcs_emphasis_ranges("a_b_c", "xa_yb_c").unwrap();
+        assert_eq!(ranges[Minus], Vec::<Range<usize>>::new());
+        assert_eq!(ranges[Plus], [0..1, 3..4]);
+        let ranges = lcs_emphasis_ranges("café = 1;", "cafe = 12").unwrap();
+        assert_eq!(ranges[Minus], [3..5, 9..10]);
+        assert_eq!(ranges[Plus], [3..4, 8..9]);
+        let long_line = "x".repeat(MAX_LCS_TABLE_SIZE);
+        assert_eq!(lcs_emphasis_ranges(&long_line, "x"), None);
+    }
+
     #[test]
     fn test_tokenize_0() {
         assert_tokenize("", &[]);
@@ -530,6 +678,50 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_infer_edits_reordered_lines_are_not_paired_positionally() {
+        let minus_lines = vec!["let total = compute(items);", "println!(\"done\");"];
+        let plus_lines = vec!["println!(\"finished\");", "let total = compute(all_items);"];
+        let (_, annotated_plus_lines, line_alignment) = infer_edits(
+            minus_lines,
+            plus_lines.clone(),
+            vec![MinusNoop; 2],
+            Deletion,
+            vec![PlusNoop; 2],
+            Insertion,
+            &DEFAULT_TOKENIZATION_REGEXP,
+            0.6,
+            0.0,
+        );
+        // The first minus line is paired with its homolog, the second plus line; the lines
+        // between them keep the plain minus and plus styles.
+        assert_eq!(
+            line_alignment,
+            vec![(None, Some(0)), (Some(0), Some(1)), (Some(1), None)]
+        );
+        assert_eq!(annotated_plus_lines[0], vec![(PlusNoop, plus_lines[0])]);
+    }
+
+    #[test]
+    fn test_infer_edits_max_line_distance_boundary() {
+        // 6 of the 12 characters counted on the two lines are changed: the distance is 0.5.
+        let (minus_lines, plus_lines) = (vec!["aaa ccc"], vec!["aba ccc"]);
+        assert_edits(
+            minus_lines.clone(),
+            plus_lines.clone(),
+            (
+                vec![vec![
+                    (MinusNoop, ""),
+                    (Deletion, "aaa"),
+                    (MinusNoop, " ccc"),
+                ]],
+                vec![vec![(PlusNoop, ""), (Insertion, "aba"), (PlusNoop, " ccc")]],
+            ),
+            0.5,
+        );
+        assert_no_edits(minus_lines, plus_lines, 0.49);
+    }
+
     #[test]
     fn test_infer_edits_2() {
         assert_paired_edits(
@@ -895,6 +1087,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_case_and_whitespace() {
+        assert_eq!(
+            normalize_case_and_whitespace("  Let  X =\t1;\n"),
+            (
+                "let x = 1;".to_string(),
+                vec![2, 3, 4, 5, 7, 8, 9, 10, 11, 12]
+            )
+        );
+        // 'İ' (2 bytes) lowercases to 'i̇' (3 bytes).
+        assert_eq!(
+            normalize_case_and_whitespace("İa"),
+            ("i̇a".to_string(), vec![0, 0, 0, 2])
+        );
+        assert_eq!(
+            normalize_case_and_whitespace(" \n"),
+            (String::new(), vec![])
+        );
+    }
+
+    #[test]
+    fn test_apply_renames() {
+        let renames = [
+            ("old".to_string(), "newer".to_string()),
+            ("x".to_string(), "y".to_string()),
+        ];
+        assert_eq!(
+            apply_renames(
+                "old(x, oldest) + x\n",
+                &DEFAULT_TOKENIZATION_REGEXP,
+                &renames
+            ),
+            (
+                "newer(y, oldest) + y\n".to_string(),
+                vec![(0..3, 0..5), (4..5, 6..7), (17..18, 19..20)]
+            )
+        );
+        assert_eq!(
+            apply_renames("z\n", &DEFAULT_TOKENIZATION_REGEXP, &renames),
+            ("z\n".to_string(), vec![])
+        );
+    }
+
+    #[test]
+    fn test_merge_emph_gaps() {
+        let line = || {
+            vec![
+                (1, "foo("),
+                (2, "a"),
+                (1, ", "),
+                (2, "b"),
+                (1, ", c, "),
+                (2, "d"),
+                (1, ")"),
+            ]
+        };
+        let mut annotated_line = line();
+        merge_emph_gaps(&mut annotated_line, 2, 0);
+        assert_eq!(annotated_line, line());
+        merge_emph_gaps(&mut annotated_line, 2, 2);
+        assert_eq!(
+            annotated_line,
+            vec![
+                (1, "foo("),
+                (2, "a"),
+                (2, ", "),
+                (2, "b"),
+                (1, ", c, "),
+                (2, "d"),
+                (1, ")")
+            ]
+        );
+        merge_emph_gaps(&mut annotated_line, 2, 5);
+        assert_eq!(
+            annotated_line,
+            vec![
+                (1, "foo("),
+                (2, "a"),
+                (2, ", "),
+                (2, "b"),
+                (2, ", c, "),
+                (2, "d"),
+                (1, ")")
+            ]
+        );
+    }
+
     fn assert_edits(
         minus_lines: Vec<&str>,
         plus_lines: Vec<&str>,
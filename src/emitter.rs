@@ -0,0 +1,416 @@
+use std::io::{self, Write};
+use std::marker::PhantomData;
+
+use ansi_term::{ANSIStrings, Color, Style};
+use unicode_width::UnicodeWidthChar;
+
+use crate::ansi;
+use crate::color;
+use crate::features::hyperlinks;
+
+/// An output backend, formatting the styled sections of the lines written by delta (see
+/// --output-format).
+///
+/// Formatted lines do not include their newline: [`EmitterWriter`] frames each line written
+/// between the [`LINE_START`](Self::LINE_START) and [`LINE_END`](Self::LINE_END) of the emitter.
+pub trait Emitter {
+    /// The text written at the start of each line.
+    const LINE_START: &'static str = "";
+
+    /// The text written at the end of each line, before its newline.
+    const LINE_END: &'static str = "";
+
+    /// Append to `output` `sections`, the text of each formatted in its style.
+    fn emit_sections(&self, sections: &[(Style, &str)], output: &mut String);
+
+    /// Append to `output` `text`, styled with ANSI escape sequences, e.g. a line of git's output
+    /// written unchanged.
+    fn emit_ansi_text(&self, text: &str, output: &mut String);
+
+    /// Return `formatted`, text formatted by the emitter, as a hyperlink to `url`.
+    fn hyperlink(&self, url: &str, formatted: &str) -> String;
+
+    /// Return the number of columns occupied by `formatted`, text formatted by the emitter.
+    fn text_width(&self, formatted: &str) -> usize;
+
+    /// Return `text` formatted in `style`.
+    fn paint(&self, style: Style, text: &str) -> String {
+        let mut output = String::new();
+        self.emit_sections(&[(style, text)], &mut output);
+        output
+    }
+}
+
+/// The backend of delta's own output: text with ANSI escape sequences.
+pub struct AnsiEmitter;
+
+impl Emitter for AnsiEmitter {
+    fn emit_sections(&self, sections: &[(Style, &str)], output: &mut String) {
+        let painted: Vec<_> = sections
+            .iter()
+            .map(|(style, text)| style.paint(*text))
+            .collect();
+        output.push_str(&ANSIStrings(&painted).to_string());
+    }
+
+    fn emit_ansi_text(&self, text: &str, output: &mut String) {
+        output.push_str(text);
+    }
+
+    fn hyperlink(&self, url: &str, formatted: &str) -> String {
+        hyperlinks::format_osc8_hyperlink(url, formatted)
+    }
+
+    fn text_width(&self, formatted: &str) -> usize {
+        ansi::measure_text_width(formatted)
+    }
+}
+
+/// An HTML backend, formatting each line as a `<div>` containing a `<span>` for each section with a
+/// color or font style, or no `<span>` at all if colors are disabled (see --color). Text is
+/// HTML-escaped, OSC 8 hyperlinks become `<a>` elements, and any other escape sequence is
+/// neutralized. Whitespace is significant, so the fragment should be displayed with
+/// `white-space: pre`, and in a monospace font.
+pub struct HtmlEmitter {
+    color_output: bool,
+}
+
+impl HtmlEmitter {
+    pub fn new(color_output: bool) -> Self {
+        Self { color_output }
+    }
+
+    // Push `sections`, joining adjacent sections with the same style and hyperlink into one
+    // element, as ANSIStrings does with escape sequences.
+    fn push_sections<'a>(
+        &self,
+        sections: impl IntoIterator<Item = (Style, Option<&'a str>, &'a str)>,
+        output: &mut String,
+    ) {
+        let mut pending: Option<(Style, Option<&str>, String)> = None;
+        for (style, url, text) in sections {
+            match &mut pending {
+                Some((pending_style, pending_url, pending_text))
+                    if *pending_style == style && *pending_url == url =>
+                {
+                    pending_text.push_str(text)
+                }
+                _ => {
+                    if let Some((style, url, text)) = pending.take() {
+                        self.push_section(&style, url, &text, output);
+                    }
+                    pending = Some((style, url, text.to_string()));
+                }
+            }
+        }
+        if let Some((style, url, text)) = pending {
+            self.push_section(&style, url, &text, output);
+        }
+    }
+
+    fn push_section(&self, style: &Style, url: Option<&str>, text: &str, output: &mut String) {
+        if text.is_empty() {
+            return;
+        }
+        let css = if self.color_output {
+            css_declarations(style)
+        } else {
+            String::new()
+        };
+        // An element must not span a newline, which ends the <div> of its line.
+        for (i, text) in text.split('\n').enumerate() {
+            if i > 0 {
+                output.push('\n');
+            }
+            if text.is_empty() {
+                continue;
+            }
+            let mut element = html_escape(&ansi::neutralize_escape_sequences(text));
+            if !css.is_empty() {
+                element = format!("<span style=\"{css}\">{element}</span>");
+            }
+            match url {
+                Some(url) => output.push_str(&self.hyperlink(url, &element)),
+                None => output.push_str(&element),
+            }
+        }
+    }
+}
+
+impl Emitter for HtmlEmitter {
+    const LINE_START: &'static str = "<div>";
+    const LINE_END: &'static str = "</div>";
+
+    fn emit_sections(&self, sections: &[(Style, &str)], output: &mut String) {
+        // The text of a section may contain hyperlinks, e.g. to the file of a line number.
+        self.push_sections(
+            sections.iter().flat_map(|(style, text)| {
+                ansi::parse_hyperlinked_style_sections(text)
+                    .into_iter()
+                    .map(move |(_, url, text)| (*style, url, text))
+            }),
+            output,
+        );
+    }
+
+    fn emit_ansi_text(&self, text: &str, output: &mut String) {
+        self.push_sections(ansi::parse_hyperlinked_style_sections(text), output);
+    }
+
+    fn hyperlink(&self, url: &str, formatted: &str) -> String {
+        format!("<a href=\"{}\">{formatted}</a>", html_escape(url))
+    }
+
+    fn text_width(&self, formatted: &str) -> usize {
+        let mut width = 0;
+        let mut chars = formatted.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '<' => {
+                    chars.find(|&c| c == '>');
+                }
+                // Each entity written by html_escape stands for a one-column character.
+                '&' => {
+                    chars.find(|&c| c == ';');
+                    width += 1;
+                }
+                _ => width += c.width().unwrap_or(0),
+            }
+        }
+        width
+    }
+}
+
+fn css_declarations(style: &Style) -> String {
+    let (foreground, background) = if style.is_reverse {
+        (style.background, style.foreground)
+    } else {
+        (style.foreground, style.background)
+    };
+    let mut declarations = Vec::new();
+    if let Some(color) = foreground {
+        declarations.push(format!("color:{}", css_color(color)));
+    }
+    if let Some(color) = background {
+        declarations.push(format!("background:{}", css_color(color)));
+    }
+    if style.is_bold {
+        declarations.push("font-weight:bold".to_string());
+    }
+    if style.is_dimmed {
+        declarations.push("opacity:0.5".to_string());
+    }
+    if style.is_italic {
+        declarations.push("font-style:italic".to_string());
+    }
+    match (style.is_underline, style.is_strikethrough) {
+        (true, true) => declarations.push("text-decoration:underline line-through".to_string()),
+        (true, false) => declarations.push("text-decoration:underline".to_string()),
+        (false, true) => declarations.push("text-decoration:line-through".to_string()),
+        (false, false) => {}
+    }
+    declarations.join(";")
+}
+
+fn css_color(color: Color) -> String {
+    let (r, g, b) = color::to_rgb(color);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A writer framing each line written to it between the `LINE_START` and `LINE_END` of the emitter
+/// `E`.
+///
+/// It is the writer of a [`Painter`](crate::paint::Painter), so that the lines written by handlers,
+/// such as file and hunk headers, are framed like the lines painted into the output buffer.
+pub struct EmitterWriter<'a, E> {
+    writer: &'a mut dyn Write,
+    // Whether what is written next starts a new line.
+    at_line_start: bool,
+    emitter: PhantomData<E>,
+}
+
+impl<'a, E: Emitter> EmitterWriter<'a, E> {
+    pub fn new(writer: &'a mut dyn Write) -> Self {
+        Self {
+            writer,
+            at_line_start: true,
+            emitter: PhantomData,
+        }
+    }
+
+    /// End the final line, if it does not end with a newline, and flush.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if !self.at_line_start {
+            self.writer.write_all(E::LINE_END.as_bytes())?;
+            self.at_line_start = true;
+        }
+        self.writer.flush()
+    }
+}
+
+impl<E: Emitter> Write for EmitterWriter<'_, E> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if E::LINE_START.is_empty() && E::LINE_END.is_empty() {
+            return self.writer.write(buf);
+        }
+        // The lines of `buf` are framed together, and written with a single write.
+        let mut framed = Vec::with_capacity(buf.len());
+        for line in buf.split_inclusive(|&b| b == b'\n') {
+            if self.at_line_start {
+                framed.extend_from_slice(E::LINE_START.as_bytes());
+            }
+            match line.strip_suffix(b"\n") {
+                Some(line) => {
+                    framed.extend_from_slice(line);
+                    framed.extend_from_slice(E::LINE_END.as_bytes());
+                    framed.push(b'\n');
+                    self.at_line_start = true;
+                }
+                None => {
+                    framed.extend_from_slice(line);
+                    self.at_line_start = false;
+                }
+            }
+        }
+        self.writer.write_all(&framed)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delta::{DiffType, State};
+    use crate::paint::Painter;
+    use crate::tests::integration_test_utils::make_config_from_args;
+
+    #[test]
+    fn test_html_emitter() {
+        let mut output = String::new();
+        HtmlEmitter::new(true).emit_sections(
+            &[
+                (Style::new().fg(Color::Red), "if a < b && c"),
+                (Style::new(), " > \"d\"\x1b[31m"),
+            ],
+            &mut output,
+        );
+        assert_eq!(
+            output,
+            "<span style=\"color:#cd0000\">if a &lt; b &amp;&amp; c</span> &gt; &quot;d&quot;"
+        );
+        assert_eq!(HtmlEmitter::new(true).text_width(&output), 19);
+    }
+
+    #[test]
+    fn test_html_emitter_styles() {
+        let mut output = String::new();
+        HtmlEmitter::new(true).emit_sections(
+            &[(
+                Style::new()
+                    .fg(Color::RGB(0x12, 0x34, 0x56))
+                    .on(Color::Fixed(22))
+                    .bold()
+                    .underline(),
+                "x",
+            )],
+            &mut output,
+        );
+        assert_eq!(
+            output,
+            "<span style=\"color:#123456;background:#005f00;font-weight:bold;\
+             text-decoration:underline\">x</span>"
+        );
+    }
+
+    #[test]
+    fn test_html_emitter_without_color() {
+        let mut output = String::new();
+        HtmlEmitter::new(false).emit_sections(&[(Style::new().fg(Color::Red), "a<b")], &mut output);
+        assert_eq!(output, "a&lt;b");
+    }
+
+    #[test]
+    fn test_html_emitter_hyperlinks() {
+        let emitter = HtmlEmitter::new(true);
+        let hyperlink = hyperlinks::format_osc8_hyperlink("file:///a&b.rs", "a&b.rs");
+        let mut output = String::new();
+        emitter.emit_sections(
+            &[(Style::new().bold(), &format!("{hyperlink}:1"))],
+            &mut output,
+        );
+        assert_eq!(
+            output,
+            "<a href=\"file:///a&amp;b.rs\"><span style=\"font-weight:bold\">a&amp;b.rs</span></a>\
+             <span style=\"font-weight:bold\">:1</span>"
+        );
+        let mut output = String::new();
+        emitter.emit_ansi_text(&format!("\x1b[31m{hyperlink}\x1b[0m x"), &mut output);
+        assert_eq!(
+            output,
+            "<a href=\"file:///a&amp;b.rs\"><span style=\"color:#cd0000\">a&amp;b.rs</span></a> x"
+        );
+    }
+
+    #[test]
+    fn test_ansi_emitter() {
+        let sections = [
+            (Style::new().fg(Color::Red), "a"),
+            (Style::new().fg(Color::Red).on(Color::Blue), "b"),
+        ];
+        let mut output = String::new();
+        AnsiEmitter.emit_sections(&sections, &mut output);
+        assert_eq!(output, "\x1b[31ma\x1b[44mb\x1b[0m");
+        assert_eq!(AnsiEmitter.text_width(&output), 2);
+    }
+
+    #[test]
+    fn test_html_painter() {
+        let config = make_config_from_args(&[
+            "--keep-plus-minus-markers",
+            "--zero-style=red",
+            "--output-format=html",
+        ]);
+        let mut output = Vec::new();
+        {
+            let mut painter = Painter::with_emitter(&mut output, &config, HtmlEmitter::new(true));
+            painter.paint_zero_line("a<b\n", &[], State::HunkZero(DiffType::Unified, None));
+            painter.emit().unwrap();
+            write!(painter.writer, "last").unwrap();
+            painter.writer.finish().unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<div><span style=\"color:#cd0000\"> a&lt;b</span></div>\n<div>last</div>"
+        );
+    }
+
+    #[test]
+    fn test_emitter_writer_passes_ansi_through() {
+        let mut output = Vec::new();
+        {
+            let mut writer = EmitterWriter::<AnsiEmitter>::new(&mut output);
+            write!(writer, "\x1b[31ma\x1b[0m\nb").unwrap();
+            writer.finish().unwrap();
+        }
+        assert_eq!(String::from_utf8(output).unwrap(), "\x1b[31ma\x1b[0m\nb");
+    }
+}
@@ -1,5 +1,6 @@
 use std::env;
 
+const COLORFGBG: &str = "COLORFGBG";
 const COLORTERM: &str = "COLORTERM";
 const BAT_THEME: &str = "BAT_THEME";
 const GIT_CONFIG_PARAMETERS: &str = "GIT_CONFIG_PARAMETERS";
@@ -9,10 +10,12 @@ const DELTA_NAVIGATE: &str = "DELTA_NAVIGATE";
 const DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES: &str =
     "DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES";
 const DELTA_PAGER: &str = "DELTA_PAGER";
+const NO_COLOR: &str = "NO_COLOR";
 
 #[derive(Default, Clone)]
 pub struct DeltaEnv {
     pub bat_theme: Option<String>,
+    pub colorfgbg: Option<String>,
     pub colorterm: Option<String>,
     pub current_dir: Option<std::path::PathBuf>,
     pub experimental_max_line_distance_for_naively_paired_lines: Option<String>,
@@ -21,6 +24,7 @@ pub struct DeltaEnv {
     pub git_prefix: Option<String>,
     pub hostname: Option<String>,
     pub navigate: Option<String>,
+    pub no_color: Option<String>,
     pub pagers: (Option<String>, Option<String>),
 }
 
@@ -28,6 +32,7 @@ impl DeltaEnv {
     /// Create a structure with current environment variable
     pub fn init() -> Self {
         let bat_theme = env::var(BAT_THEME).ok();
+        let colorfgbg = env::var(COLORFGBG).ok();
         let colorterm = env::var(COLORTERM).ok();
         let experimental_max_line_distance_for_naively_paired_lines =
             env::var(DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES).ok();
@@ -36,6 +41,8 @@ impl DeltaEnv {
         let git_prefix = env::var(GIT_PREFIX).ok();
         let hostname = hostname();
         let navigate = env::var(DELTA_NAVIGATE).ok();
+        // See https://no-color.org: an empty value is the same as no value.
+        let no_color = env::var(NO_COLOR).ok().filter(|value| !value.is_empty());
 
         let current_dir = env::current_dir().ok();
         let pagers = (
@@ -49,6 +56,7 @@ impl DeltaEnv {
 
         Self {
             bat_theme,
+            colorfgbg,
             colorterm,
             current_dir,
             experimental_max_line_distance_for_naively_paired_lines,
@@ -57,6 +65,7 @@ impl DeltaEnv {
             git_prefix,
             hostname,
             navigate,
+            no_color,
             pagers,
         }
     }
@@ -88,6 +97,16 @@ pub mod tests {
         assert_eq!(env.current_dir, env::current_dir().ok());
     }
 
+    #[test]
+    fn test_env_parsing_no_color() {
+        let _guard = ENV_ACCESS.lock().unwrap();
+        env::set_var("NO_COLOR", "1");
+        assert_eq!(DeltaEnv::init().no_color, Some("1".into()));
+        env::set_var("NO_COLOR", "");
+        assert_eq!(DeltaEnv::init().no_color, None);
+        env::remove_var("NO_COLOR");
+    }
+
     #[test]
     fn test_env_parsing_with_pager_set_to_bat() {
         let _guard = ENV_ACCESS.lock().unwrap();
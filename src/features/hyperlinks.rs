@@ -98,6 +98,22 @@ pub fn format_osc8_file_hyperlink<'a, P>(
     text: &str,
     config: &Config,
 ) -> Cow<'a, str>
+where
+    P: AsRef<Path>,
+    P: std::fmt::Debug,
+{
+    Cow::from(format_osc8_hyperlink(
+        &file_hyperlink_url(absolute_path, line_number, config),
+        text,
+    ))
+}
+
+/// Return the URL of a hyperlink to the file at `absolute_path` (see --hyperlinks-file-link-format).
+pub fn file_hyperlink_url<P>(
+    absolute_path: P,
+    line_number: Option<usize>,
+    config: &Config,
+) -> String
 where
     P: AsRef<Path>,
     P: std::fmt::Debug,
@@ -114,10 +130,10 @@ where
     } else {
         url = url.replace("{line}", "")
     };
-    Cow::from(format_osc8_hyperlink(&url, text))
+    url
 }
 
-fn format_osc8_hyperlink(url: &str, text: &str) -> String {
+pub fn format_osc8_hyperlink(url: &str, text: &str) -> String {
     format!(
         "{osc}8;;{url}{st}{text}{osc}8;;{st}",
         url = url,
@@ -74,6 +74,24 @@ pub fn make_feature() -> Vec<(String, OptionValueFunction)> {
             None,
             _opt => true
         ),
+        (
+            "minus-marker",
+            String,
+            None,
+            _opt => "-"
+        ),
+        (
+            "plus-marker",
+            String,
+            None,
+            _opt => "+"
+        ),
+        (
+            "zero-marker",
+            String,
+            None,
+            _opt => " "
+        ),
         (
             "tabs",
             usize,
@@ -8,9 +8,10 @@ use crate::config::{self, delta_unreachable, Config};
 use crate::delta::DiffType;
 use crate::delta::State;
 use crate::edits;
+use crate::emitter::AnsiEmitter;
 use crate::features::{line_numbers, OptionValueFunction};
 use crate::minusplus::*;
-use crate::paint::{BgFillMethod, BgShouldFill, LineSections, Painter};
+use crate::paint::{BgFillMethod, BgShouldFill, LineInfo, LineSections, Painter};
 use crate::style::Style;
 use crate::wrapping::{wrap_minusplus_block, wrap_zero_block};
 
@@ -46,13 +47,26 @@ pub type LeftRight<T> = MinusPlus<T>;
 pub type SideBySideData = LeftRight<Panel>;
 
 impl SideBySideData {
-    /// Create a [`LeftRight<Panel>`](LeftRight<Panel>) named [`SideBySideData`].
-    pub fn new_sbs(decorations_width: &cli::Width, available_terminal_width: &usize) -> Self {
-        let panel_width = match decorations_width {
-            cli::Width::Fixed(w) => w / 2,
-            _ => available_terminal_width / 2,
+    /// Create a [`LeftRight<Panel>`](LeftRight<Panel>) named [`SideBySideData`], dividing the
+    /// width between the panels according to the (left, right) `ratio`.
+    pub fn new_sbs(
+        decorations_width: &cli::Width,
+        available_terminal_width: &usize,
+        ratio: (usize, usize),
+    ) -> Self {
+        let width = match decorations_width {
+            cli::Width::Fixed(w) => *w,
+            _ => *available_terminal_width,
         };
-        SideBySideData::new(Panel { width: panel_width }, Panel { width: panel_width })
+        // The panels sum up to an even width, see `UseFullPanelWidth`.
+        let width = width - width % 2;
+        let left_width = width * ratio.0 / (ratio.0 + ratio.1);
+        SideBySideData::new(
+            Panel { width: left_width },
+            Panel {
+                width: width - left_width,
+            },
+        )
     }
 }
 
@@ -63,17 +77,31 @@ pub fn available_line_width(
     let line_numbers_width = data.formatted_width();
 
     // The width can be reduced by the line numbers and/or
-    // a possibly added/restored 1-wide "+/-/ " prefix.
+    // a possibly added/restored "+/-" prefix.
     let line_width = |side: PanelSide| {
         config.side_by_side_data[side]
             .width
             .saturating_sub(line_numbers_width[side])
-            .saturating_sub(config.keep_plus_minus_markers as usize)
+            .saturating_sub(marker_width(side, config))
     };
 
     LeftRight::new(line_width(Left), line_width(Right))
 }
 
+/// The width of the widest marker prefixing the lines of the `side` panel with
+/// --keep-plus-minus-markers, or 0.
+fn marker_width(side: PanelSide, config: &Config) -> usize {
+    let marker = match side {
+        Left => &config.minus_marker,
+        Right => &config.plus_marker,
+    };
+    if config.keep_plus_minus_markers {
+        marker.width().max(config.zero_marker.width())
+    } else {
+        0
+    }
+}
+
 pub fn line_is_too_long(line: &str, line_width: usize) -> bool {
     debug_assert!(line.ends_with('\n'));
 
@@ -85,17 +113,17 @@ pub fn line_is_too_long(line: &str, line_width: usize) -> bool {
 /// structure indicating which of the input lines are too long. This avoids
 /// recalculating the length later.
 pub fn has_long_lines(
-    lines: &LeftRight<&Vec<(String, State)>>,
+    lines: &LeftRight<&Vec<(String, State, LineInfo)>>,
     line_width: &line_numbers::SideBySideLineWidth,
 ) -> (bool, LeftRight<Vec<bool>>) {
     let mut wrap_any = LeftRight::default();
     let mut wrapping_lines = LeftRight::default();
 
     let mut check_if_too_long = |side| {
-        let lines_side: &[(String, State)] = lines[side];
+        let lines_side: &[(String, State, LineInfo)] = lines[side];
         wrapping_lines[side] = lines_side
             .iter()
-            .map(|(line, _)| line_is_too_long(line, line_width[side]))
+            .map(|(line, _, _)| line_is_too_long(line, line_width[side]))
             .inspect(|b| wrap_any[side] |= b)
             .collect();
     };
@@ -108,20 +136,24 @@ pub fn has_long_lines(
 
 #[allow(clippy::too_many_arguments)]
 pub fn paint_minus_and_plus_lines_side_by_side(
-    lines: LeftRight<&Vec<(String, State)>>,
+    lines: LeftRight<&Vec<(String, State, LineInfo)>>,
     syntax_sections: LeftRight<Vec<LineSections<SyntectStyle>>>,
     diff_sections: LeftRight<Vec<LineSections<Style>>>,
     lines_have_homolog: LeftRight<Vec<bool>>,
+    lines_style: LeftRight<Option<Style>>,
     line_alignment: Vec<(Option<usize>, Option<usize>)>,
     line_numbers_data: &mut Option<LineNumbersData>,
     output_buffer: &mut String,
     config: &config::Config,
 ) {
     let line_states = LeftRight::new(
-        lines[Left].iter().map(|(_, state)| state.clone()).collect(),
+        lines[Left]
+            .iter()
+            .map(|(_, state, _)| state.clone())
+            .collect(),
         lines[Right]
             .iter()
-            .map(|(_, state)| state.clone())
+            .map(|(_, state, _)| state.clone())
             .collect(),
     );
 
@@ -181,6 +213,7 @@ pub fn paint_minus_and_plus_lines_side_by_side(
             &syntax_sections[Left],
             &diff_sections[Left],
             &lines_have_homolog[Left],
+            lines_style[Left],
             left_state,
             &mut Some(line_numbers_data),
             bg_should_fill[Left],
@@ -196,6 +229,7 @@ pub fn paint_minus_and_plus_lines_side_by_side(
             &syntax_sections[Right],
             &diff_sections[Right],
             &lines_have_homolog[Right],
+            lines_style[Right],
             right_state,
             &mut Some(line_numbers_data),
             bg_should_fill[Right],
@@ -230,6 +264,7 @@ pub fn paint_zero_lines_side_by_side<'a>(
     config: &Config,
     line_numbers_data: &mut Option<&mut line_numbers::LineNumbersData>,
     painted_prefix: Option<ansi_term::ANSIString>,
+    line_style: Option<Style>,
     background_color_extends_to_terminal_width: BgShouldFill,
 ) {
     let states = vec![State::HunkZero(DiffType::Unified, None)];
@@ -246,7 +281,7 @@ pub fn paint_zero_lines_side_by_side<'a>(
     for (line_index, ((syntax_sections, diff_sections), state)) in syntax_style_sections
         .into_iter()
         .zip_eq(diff_style_sections.iter())
-        .zip_eq(states.into_iter())
+        .zip_eq(states)
         .enumerate()
     {
         for panel_side in &[Left, Right] {
@@ -257,6 +292,7 @@ pub fn paint_zero_lines_side_by_side<'a>(
                 line_numbers_data,
                 Some(*panel_side),
                 painted_prefix.clone(),
+                &AnsiEmitter,
                 config,
             );
             pad_panel_line_to_width(
@@ -265,6 +301,7 @@ pub fn paint_zero_lines_side_by_side<'a>(
                 Some(line_index),
                 &diff_style_sections,
                 None,
+                line_style,
                 &state,
                 *panel_side,
                 background_color_extends_to_terminal_width,
@@ -282,6 +319,7 @@ fn paint_left_panel_minus_line<'a>(
     syntax_style_sections: &[LineSections<'a, SyntectStyle>],
     diff_style_sections: &[LineSections<'a, Style>],
     lines_have_homolog: &[bool],
+    line_style: Option<Style>,
     state: &'a State,
     line_numbers_data: &mut Option<&mut line_numbers::LineNumbersData>,
     background_color_extends_to_terminal_width: BgShouldFill,
@@ -302,6 +340,7 @@ fn paint_left_panel_minus_line<'a>(
         line_index,
         diff_style_sections,
         Some(lines_have_homolog),
+        line_style,
         state,
         Left,
         background_color_extends_to_terminal_width,
@@ -317,6 +356,7 @@ fn paint_right_panel_plus_line<'a>(
     syntax_style_sections: &[LineSections<'a, SyntectStyle>],
     diff_style_sections: &[LineSections<'a, Style>],
     lines_have_homolog: &[bool],
+    line_style: Option<Style>,
     state: &'a State,
     line_numbers_data: &mut Option<&mut line_numbers::LineNumbersData>,
     background_color_extends_to_terminal_width: BgShouldFill,
@@ -338,6 +378,7 @@ fn paint_right_panel_plus_line<'a>(
         line_index,
         diff_style_sections,
         Some(lines_have_homolog),
+        line_style,
         state,
         Right,
         background_color_extends_to_terminal_width,
@@ -353,6 +394,7 @@ fn get_right_fill_style_for_panel(
     line_index: Option<usize>,
     diff_style_sections: &[LineSections<'_, Style>],
     lines_have_homolog: Option<&[bool]>,
+    line_style: Option<Style>,
     state: &State,
     panel_side: PanelSide,
     background_color_extends_to_terminal_width: BgShouldFill,
@@ -373,6 +415,7 @@ fn get_right_fill_style_for_panel(
                 Painter::get_should_right_fill_background_color_and_fill_style(
                     &diff_style_sections[index],
                     lines_have_homolog.map(|h| h[index]),
+                    line_style,
                     state,
                     background_color_extends_to_terminal_width,
                     config,
@@ -443,11 +486,13 @@ fn paint_minus_or_plus_panel_line<'a>(
             )
         };
 
+    // Wrapped lines are prefixed with spaces of the width of the marker, to stay aligned.
+    let wrapped_prefix = " ".repeat(marker_width(panel_side, config));
     let painted_prefix = match (config.keep_plus_minus_markers, panel_side, state) {
-        (true, _, State::HunkPlusWrapped) => Some(config.plus_style.paint(" ")),
-        (true, _, State::HunkMinusWrapped) => Some(config.minus_style.paint(" ")),
-        (true, Left, _) => Some(config.minus_style.paint("-")),
-        (true, Right, _) => Some(config.plus_style.paint("+")),
+        (true, _, State::HunkPlusWrapped) => Some(config.plus_style.paint(wrapped_prefix)),
+        (true, _, State::HunkMinusWrapped) => Some(config.minus_style.paint(wrapped_prefix)),
+        (true, Left, _) => Some(config.minus_style.paint(config.minus_marker.as_str())),
+        (true, Right, _) => Some(config.plus_style.paint(config.plus_marker.as_str())),
         _ => None,
     };
 
@@ -458,6 +503,7 @@ fn paint_minus_or_plus_panel_line<'a>(
         line_numbers_data,
         Some(panel_side),
         painted_prefix,
+        &AnsiEmitter,
         config,
     );
 
@@ -474,6 +520,7 @@ fn pad_panel_line_to_width(
     line_index: Option<usize>,
     diff_style_sections: &[LineSections<'_, Style>],
     lines_have_homolog: Option<&[bool]>,
+    line_style: Option<Style>,
     state: &State,
     panel_side: PanelSide,
     background_color_extends_to_terminal_width: BgShouldFill,
@@ -488,11 +535,13 @@ fn pad_panel_line_to_width(
                 &config.minus_empty_line_marker_style,
                 panel_line,
                 Some(" "),
+                &AnsiEmitter,
             ),
             State::HunkPlus(_, _) => Painter::mark_empty_line(
                 &config.plus_empty_line_marker_style,
                 panel_line,
                 Some(" "),
+                &AnsiEmitter,
             ),
             State::HunkZero(_, _) => {}
             _ => unreachable!(),
@@ -512,6 +561,7 @@ fn pad_panel_line_to_width(
         line_index,
         diff_style_sections,
         lines_have_homolog,
+        line_style,
         state,
         panel_side,
         background_color_extends_to_terminal_width,
@@ -592,7 +642,9 @@ pub mod ansifill {
 pub mod tests {
     use crate::ansi::strip_ansi_codes;
     use crate::features::line_numbers::tests::*;
-    use crate::tests::integration_test_utils::{make_config_from_args, run_delta, DeltaTest};
+    use crate::tests::integration_test_utils::{
+        make_config_from_args, run_delta, with_minus_plus_test_styles, DeltaTest,
+    };
     use insta::assert_snapshot;
 
     #[test]
@@ -689,6 +741,48 @@ pub mod tests {
         assert_eq!("│    │         │  2 │b = 2345>", strip_ansi_codes(line_2));
     }
 
+    #[test]
+    fn test_side_by_side_ratio() {
+        for (ratio, width, line_fill_method, expected_widths) in [
+            ("40/60", "100", "spaces", (40, 60)),
+            ("40/60", "101", "ansi", (40, 61)),
+            ("40/60", "101", "spaces", (40, 60)),
+            ("1/2", "100", "ansi", (33, 67)),
+            ("50/50", "41", "ansi", (20, 21)),
+        ] {
+            let config = make_config_from_args(&[
+                "--side-by-side",
+                "--side-by-side-ratio",
+                ratio,
+                "--width",
+                width,
+                "--line-fill-method",
+                line_fill_method,
+            ]);
+            let data = &config.side_by_side_data;
+            assert_eq!(
+                (data[super::Left].width, data[super::Right].width),
+                expected_widths
+            );
+        }
+
+        let config = make_config_from_args(&[
+            "--side-by-side",
+            "--side-by-side-ratio=40/60",
+            "--width",
+            "50",
+            "--line-fill-method=spaces",
+        ]);
+        let output = run_delta(TWO_PLUS_LINES_DIFF, &config);
+        let line = output.lines().nth(crate::config::HEADER_LEN).unwrap();
+        let line = strip_ansi_codes(line);
+        assert_eq!(line.chars().count(), 50);
+        assert_eq!(
+            line.trim_end(),
+            format!("│    │{}│  1 │a = 1", " ".repeat(20 - 6))
+        );
+    }
+
     #[test]
     fn test_two_plus_lines_exact_fit() {
         let config =
@@ -717,6 +811,21 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_one_minus_one_plus_line_emph() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--side-by-side",
+            "--width",
+            "40",
+            "--line-fill-method=spaces",
+        ]))
+        .explain_ansi()
+        .with_input(ONE_MINUS_ONE_PLUS_LINE_DIFF)
+        .expect_after_header(r#"
+        (blue)│(238)  1 (blue)│(dim normal)a = 1(normal)         (blue)│(238)  1 (blue)│(dim normal)a = 1(normal)
+        (blue)│(88)  2 (blue)│(ul red)b(red) = 2(normal)         (blue)│(28)  2 (blue)│(ul green)bb(green) = 2(normal)"#);
+    }
+
     #[test]
     fn test_two_minus_lines_unicode_truncated() {
         DeltaTest::with_args(&[
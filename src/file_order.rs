@@ -0,0 +1,184 @@
+use std::cmp::Reverse;
+
+use crate::ansi;
+use crate::config::FileOrder;
+use crate::handlers::diff_header::get_repeated_file_path_from_diff_line;
+
+/// The lines of the diff of one file, from its "diff" line up to the next file or commit.
+struct FileSection {
+    lines: Vec<Vec<u8>>,
+    // The number of removed and added lines of its hunks.
+    n_changed_lines: usize,
+    // The column prefixing each hunk line with its markers, per parent.
+    n_marker_columns: Option<usize>,
+}
+
+impl FileSection {
+    fn new(line: Vec<u8>) -> Self {
+        Self {
+            lines: vec![line],
+            n_changed_lines: 0,
+            n_marker_columns: None,
+        }
+    }
+
+    fn push(&mut self, line: Vec<u8>, stripped: &str) {
+        if stripped.starts_with("@@") {
+            self.n_marker_columns = Some(stripped.chars().take_while(|&c| c == '@').count() - 1);
+        } else if let Some(n) = self.n_marker_columns {
+            if stripped
+                .get(..n)
+                .is_some_and(|markers| markers.contains(['-', '+']))
+            {
+                self.n_changed_lines += 1;
+            }
+        }
+        self.lines.push(line);
+    }
+
+    fn path(&self) -> String {
+        let line = ansi::strip_ansi_codes(&String::from_utf8_lossy(&self.lines[0]));
+        get_repeated_file_path_from_diff_line(&line).unwrap_or_else(|| {
+            // E.g. a rename, for which the path of the minus file comes first.
+            let paths = ["diff --git ", "diff --cc ", "diff --combined ", "diff "]
+                .iter()
+                .find_map(|prefix| line.strip_prefix(prefix))
+                .unwrap_or(&line);
+            paths.strip_prefix("a/").unwrap_or(paths).to_string()
+        })
+    }
+}
+
+/// Return `lines` with the file sections of each commit sorted according to `order` (see
+/// --file-order). The sort is stable, and lines preceding the first file section of a commit
+/// keep their place.
+pub fn reorder_files(lines: Vec<Vec<u8>>, order: &FileOrder) -> Vec<Vec<u8>> {
+    if *order == FileOrder::Input {
+        return lines;
+    }
+    let mut reordered = Vec::with_capacity(lines.len());
+    let mut file_sections: Vec<FileSection> = Vec::new();
+    let sort_and_append = |file_sections: &mut Vec<FileSection>, reordered: &mut Vec<Vec<u8>>| {
+        match order {
+            FileOrder::Size => file_sections.sort_by_key(|file| Reverse(file.n_changed_lines)),
+            FileOrder::Path => file_sections.sort_by_cached_key(FileSection::path),
+            FileOrder::Input => {}
+        }
+        reordered.extend(file_sections.drain(..).flat_map(|file| file.lines));
+    };
+    for line in lines {
+        let stripped = ansi::strip_ansi_codes(&String::from_utf8_lossy(&line));
+        if stripped.starts_with("diff ") {
+            file_sections.push(FileSection::new(line));
+        } else if stripped.starts_with("commit ") || file_sections.is_empty() {
+            sort_and_append(&mut file_sections, &mut reordered);
+            reordered.push(line);
+        } else if let Some(file) = file_sections.last_mut() {
+            file.push(line, &stripped);
+        }
+    }
+    sort_and_append(&mut file_sections, &mut reordered);
+    reordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reorder(input: &str, order: FileOrder) -> String {
+        let lines = input.lines().map(|line| line.as_bytes().to_vec()).collect();
+        reorder_files(lines, &order)
+            .into_iter()
+            .map(|line| String::from_utf8(line).unwrap() + "\n")
+            .collect()
+    }
+
+    const DIFF: &str = "\
+commit 1111111
+diff --git a/b.rs b/b.rs
+@@ -1 +1 @@
+-x
++y
+diff --git a/c.rs b/c.rs
+@@ -1,2 +1,3 @@
+ x
+--- y
++++ y
++z
+diff --git a/a.rs b/a.rs
+@@ -1 +1 @@
+-x
++y
+commit 2222222
+diff --git a/e.rs b/e.rs
+@@ -1 +1 @@
+-x
++y
+diff --cc d.rs
+@@@ -1,1 -1,1 +1,2 @@@
+- x
+ -y
+++z
+";
+
+    #[test]
+    fn test_reorder_files_by_size() {
+        assert_eq!(
+            reorder(DIFF, FileOrder::Size),
+            "\
+commit 1111111
+diff --git a/c.rs b/c.rs
+@@ -1,2 +1,3 @@
+ x
+--- y
++++ y
++z
+diff --git a/b.rs b/b.rs
+@@ -1 +1 @@
+-x
++y
+diff --git a/a.rs b/a.rs
+@@ -1 +1 @@
+-x
++y
+commit 2222222
+diff --cc d.rs
+@@@ -1,1 -1,1 +1,2 @@@
+- x
+ -y
+++z
+diff --git a/e.rs b/e.rs
+@@ -1 +1 @@
+-x
++y
+"
+        );
+    }
+
+    #[test]
+    fn test_reorder_files_by_path() {
+        let reordered = reorder(DIFF, FileOrder::Path);
+        let diff_lines: Vec<&str> = reordered
+            .lines()
+            .filter(|line| line.starts_with("diff ") || line.starts_with("commit "))
+            .collect();
+        assert_eq!(
+            diff_lines,
+            [
+                "commit 1111111",
+                "diff --git a/a.rs b/a.rs",
+                "diff --git a/b.rs b/b.rs",
+                "diff --git a/c.rs b/c.rs",
+                "commit 2222222",
+                "diff --cc d.rs",
+                "diff --git a/e.rs b/e.rs",
+            ]
+        );
+        assert_eq!(reordered.lines().count(), DIFF.lines().count());
+    }
+
+    #[test]
+    fn test_reorder_files_input_order() {
+        assert_eq!(reorder(DIFF, FileOrder::Input), DIFF);
+    }
+}
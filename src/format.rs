@@ -1,9 +1,10 @@
+use std::borrow::Cow;
 use std::convert::{TryFrom, TryInto};
 
 use regex::Regex;
 use smol_str::SmolStr;
-use unicode_segmentation::UnicodeSegmentation;
 
+use crate::ansi;
 use crate::features::side_by_side::ansifill::ODD_PAD_CHAR;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -83,7 +84,7 @@ impl<T> FormatStringPlaceholderDataAnyPlaceholder<T> {
     pub fn only_string(s: &str) -> Self {
         Self {
             suffix: s.into(),
-            suffix_len: s.graphemes(true).count(),
+            suffix_len: ansi::measure_text_width(s),
             ..Self::default()
         }
     }
@@ -176,9 +177,9 @@ pub fn parse_line_number_format<'a>(
         let match_ = captures.get(0).unwrap();
         let prefix = SmolStr::new(&format_string[offset..match_.start()]);
         let prefix = expand_first_prefix(prefix);
-        let prefix_len = prefix.graphemes(true).count();
+        let prefix_len = ansi::measure_text_width(&prefix);
         let suffix = SmolStr::new(&format_string[match_.end()..]);
-        let suffix_len = suffix.graphemes(true).count();
+        let suffix_len = ansi::measure_text_width(&suffix);
         format_data.push(FormatStringPlaceholderData {
             prefix,
             prefix_len,
@@ -206,13 +207,13 @@ pub fn parse_line_number_format<'a>(
     if offset == 0 {
         let prefix = SmolStr::new("");
         let prefix = expand_first_prefix(prefix);
-        let prefix_len = prefix.graphemes(true).count();
+        let prefix_len = ansi::measure_text_width(&prefix);
         // No placeholders
         format_data.push(FormatStringPlaceholderData {
             prefix,
             prefix_len,
             suffix: SmolStr::new(format_string),
-            suffix_len: format_string.graphemes(true).count(),
+            suffix_len: ansi::measure_text_width(format_string),
             ..Default::default()
         })
     }
@@ -291,8 +292,8 @@ impl CenterRightNumbers for usize {
     }
 }
 
-// Note that in this case of a string `s`, `precision` means "max width".
-// See https://doc.rust-lang.org/std/fmt/index.html
+// Note that in this case of a string `s`, `precision` means "max width". Widths are display
+// widths, in terminal columns, so that wide characters and escape sequences are accounted for.
 pub fn pad<T: std::fmt::Display + CenterRightNumbers>(
     s: T,
     width: usize,
@@ -300,18 +301,18 @@ pub fn pad<T: std::fmt::Display + CenterRightNumbers>(
     precision: Option<usize>,
 ) -> String {
     let space = s.center_right_space(alignment, width);
-    let mut result = match precision {
-        None => match alignment {
-            Align::Left => format!("{space}{s:<width$}"),
-            Align::Center => format!("{space}{s:^width$}"),
-            Align::Right => format!("{space}{s:>width$}"),
-        },
-        Some(precision) => match alignment {
-            Align::Left => format!("{space}{s:<width$.precision$}"),
-            Align::Center => format!("{space}{s:^width$.precision$}"),
-            Align::Right => format!("{space}{s:>width$.precision$}"),
-        },
+    let text = s.to_string();
+    let text = match precision {
+        Some(precision) => ansi::truncate_str_short(&text, precision),
+        None => Cow::from(text.as_str()),
     };
+    let padding = width.saturating_sub(ansi::measure_text_width(&text));
+    let (left, right) = match alignment {
+        Align::Left => (0, padding),
+        Align::Center => (padding / 2, padding - padding / 2),
+        Align::Right => (padding, 0),
+    };
+    let mut result = format!("{space}{}{text}{}", " ".repeat(left), " ".repeat(right));
     if space == " " {
         result.pop();
     }
@@ -366,6 +367,22 @@ mod tests {
         assert_eq!(pad("abc", 5, Align::Right, None), "  abc");
     }
 
+    #[test]
+    fn test_pad_wide_characters() {
+        assert_eq!(pad("日本語", 8, Align::Left, None), "日本語  ");
+        assert_eq!(pad("日本語", 9, Align::Center, None), " 日本語  ");
+        assert_eq!(pad("日本語", 4, Align::Right, None), "日本語");
+        assert_eq!(pad("日本語", 8, Align::Left, Some(5)), "日本    ");
+        assert_eq!(
+            pad("e\u{301}t\u{e9}", 4, Align::Right, None),
+            " e\u{301}t\u{e9}"
+        );
+        assert_eq!(
+            pad("\x1b[31mab\x1b[0m", 4, Align::Left, None),
+            "\x1b[31mab\x1b[0m  "
+        );
+    }
+
     #[test]
     fn test_placeholder_with_notype() {
         let regex = make_placeholder_regex(&["placeholder"]);
@@ -2,13 +2,14 @@ use chrono::{DateTime, FixedOffset};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::borrow::Cow;
-use unicode_width::UnicodeWidthStr;
+use std::io::Write;
 
 use crate::ansi::measure_text_width;
 use crate::color;
 use crate::config;
 use crate::config::delta_unreachable;
 use crate::delta::{self, State, StateMachine};
+use crate::emitter::Emitter;
 use crate::fatal;
 use crate::format::{self, FormatStringSimple, Placeholder};
 use crate::format::{make_placeholder_regex, parse_line_number_format};
@@ -24,7 +25,7 @@ pub enum BlameLineNumbers {
     Every(usize, FormatStringSimple),
 }
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     /// If this is a line of git blame output then render it accordingly. If
     /// this is the first blame line, then set the syntax-highlighter language
     /// according to delta.default-language.
@@ -66,14 +67,17 @@ impl StateMachine<'_> {
                     is_repeat,
                 );
 
-                write!(
-                    self.painter.writer,
-                    "{}{}{}{}",
-                    metadata_style.paint(&formatted_blame_metadata),
-                    separator_style.paint(nr_prefix),
-                    metadata_style.paint(&line_number),
-                    separator_style.paint(nr_suffix),
-                )?;
+                let mut blame_prefix = String::new();
+                self.painter.emitter.emit_sections(
+                    &[
+                        (metadata_style.ansi_term_style, &formatted_blame_metadata),
+                        (separator_style.ansi_term_style, nr_prefix),
+                        (metadata_style.ansi_term_style, &line_number),
+                        (separator_style.ansi_term_style, nr_suffix),
+                    ],
+                    &mut blame_prefix,
+                );
+                write!(self.painter.writer, "{blame_prefix}")?;
 
                 // Emit syntax-highlighted code
                 if self.state == State::Unknown {
@@ -284,12 +288,9 @@ pub fn format_blame_metadata(
             _ => unreachable!("Unexpected `git blame` input"),
         };
         if let Some(field) = field {
-            // Unicode modifier should not be counted as character to allow a consistent padding
-            let unicode_modifier_width =
-                field.as_ref().chars().count() - UnicodeWidthStr::width(field.as_ref());
             s.push_str(&format::pad(
                 &field,
-                width + unicode_modifier_width,
+                width,
                 alignment_spec,
                 placeholder.precision,
             ))
@@ -307,19 +308,16 @@ pub fn format_blame_line_number(
 ) -> (&str, String, &str) {
     let (format, empty) = match &format {
         BlameLineNumbers::PerBlock(format) => (format, is_repeat),
-        BlameLineNumbers::Every(n, format) => (format, is_repeat && line_number % n != 0),
+        BlameLineNumbers::Every(n, format) => {
+            (format, is_repeat && !line_number.is_multiple_of(*n))
+        }
         BlameLineNumbers::On(format) => (format, false),
     };
     let mut result = String::new();
 
     // depends on defaults being set when parsing arguments
-    let line_number = if format.width.is_some() {
-        format::pad(
-            line_number,
-            format.width.unwrap(),
-            format.alignment_spec.unwrap(),
-            None,
-        )
+    let line_number = if let Some(width) = format.width {
+        format::pad(line_number, width, format.alignment_spec.unwrap(), None)
     } else {
         String::new()
     };
@@ -407,6 +405,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_blame_line_variants() {
+        // `git blame -l`: full hash; boundary commit; renamed file.
+        let line = "^ea82f2d0a9c7a5e8f6b0d1c2e3f4a5b6c7d8e9f0 src/old.rs (Dan Davison 2021-08-22 18:20:19 -0700 12) fn f() {}";
+        let blame_line = parse_git_blame_line(line, "%Y-%m-%d %H:%M:%S %z").unwrap();
+        assert_eq!(
+            blame_line.commit,
+            "^ea82f2d0a9c7a5e8f6b0d1c2e3f4a5b6c7d8e9f0"
+        );
+        assert_eq!(blame_line.author, "Dan Davison");
+        assert_eq!(blame_line.line_number, 12);
+        assert_eq!(blame_line.code, " fn f() {}");
+
+        // `git blame --porcelain` lines are not blame lines, and are left alone.
+        for line in &[
+            "ea82f2d0a9c7a5e8f6b0d1c2e3f4a5b6c7d8e9f0 12 12 1",
+            "author Dan Davison",
+            "author-time 1629681619",
+            "\tfn f() {}",
+        ] {
+            assert!(parse_git_blame_line(line, "%Y-%m-%d %H:%M:%S %z").is_none());
+        }
+    }
+
     #[test]
     fn test_blame_line_with_parens_in_name() {
         let line =
@@ -456,6 +478,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_blame_metadata_with_wide_characters_in_name() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let format_data = make_format_data_with_placeholder("author");
+        let blame = make_blame_line_with_author("이강욱");
+        let result = format_blame_metadata(&[format_data], &blame, &config);
+        assert_eq!(
+            crate::ansi::measure_text_width(&result),
+            crate::ansi::measure_text_width(&format_blame_metadata(
+                &[make_format_data_with_placeholder("author")],
+                &make_blame_line_with_author("Edith"),
+                &config
+            ))
+        );
+    }
+
     #[test]
     fn test_color_assignment() {
         let mut writer = Cursor::new(vec![0; 512]);
@@ -546,7 +584,7 @@ mod tests {
             .collect()
     }
 
-    fn make_blame_line_with_time(timestamp: &str) -> BlameLine {
+    fn make_blame_line_with_time(timestamp: &str) -> BlameLine<'_> {
         let time = chrono::DateTime::parse_from_rfc3339(timestamp).unwrap();
         BlameLine {
             commit: "",
@@ -557,14 +595,16 @@ mod tests {
         }
     }
 
-    fn make_format_data_with_placeholder(placeholder: &str) -> format::FormatStringPlaceholderData {
+    fn make_format_data_with_placeholder(
+        placeholder: &str,
+    ) -> format::FormatStringPlaceholderData<'_> {
         format::FormatStringPlaceholderData {
             placeholder: Some(Placeholder::Str(placeholder)),
             ..Default::default()
         }
     }
 
-    fn make_blame_line_with_author(author: &str) -> BlameLine {
+    fn make_blame_line_with_author(author: &str) -> BlameLine<'_> {
         BlameLine {
             commit: "",
             author,
@@ -2,9 +2,10 @@ use std::borrow::Cow;
 
 use super::draw;
 use crate::delta::{State, StateMachine};
+use crate::emitter::Emitter;
 use crate::features;
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     #[inline]
     fn test_commit_meta_header_line(&self) -> bool {
         self.config.commit_regex.is_match(&self.line)
@@ -15,7 +16,7 @@ impl StateMachine<'_> {
             return Ok(false);
         }
         let mut handled_line = false;
-        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.paint_end_of_hunk();
         self.handle_pending_line_with_diff_name()?;
         self.state = State::CommitMeta;
         if self.should_handle() {
@@ -47,13 +48,19 @@ impl StateMachine<'_> {
             (Cow::from(&self.line), Cow::from(&self.raw_line))
         };
 
-        draw_fn(
-            self.painter.writer,
+        let (text, text_width) = draw::paint_text(
+            &self.painter.emitter,
             &format!("{}{}", formatted_line, if pad { " " } else { "" }),
             &format!("{}{}", formatted_raw_line, if pad { " " } else { "" }),
             "",
-            &self.config.decorations_width,
             self.config.commit_style,
+        );
+        draw_fn(
+            &mut self.painter.writer,
+            &self.painter.emitter,
+            &text,
+            text_width,
+            &self.config.decorations_width,
             decoration_ansi_term_style,
         )?;
         Ok(())
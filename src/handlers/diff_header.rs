@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::io::Write;
 use std::path::Path;
 
 use unicode_segmentation::UnicodeSegmentation;
@@ -6,6 +7,7 @@ use unicode_segmentation::UnicodeSegmentation;
 use super::draw;
 use crate::config::Config;
 use crate::delta::{DiffType, Source, State, StateMachine};
+use crate::emitter::Emitter;
 use crate::paint::Painter;
 use crate::{features, utils};
 
@@ -22,7 +24,7 @@ pub enum FileEvent {
     NoEvent,
 }
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     /// Check for the old mode|new mode lines and cache their info for later use.
     pub fn handle_diff_header_mode_line(&mut self) -> std::io::Result<bool> {
         let mut handled_line = false;
@@ -102,7 +104,7 @@ impl StateMachine<'_> {
                 ));
         }
 
-        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.paint_end_of_hunk();
         self.should_write_generic_diff_header_header_line()
     }
 
@@ -132,7 +134,10 @@ impl StateMachine<'_> {
             ));
         self.current_file_pair = Some((self.minus_file.clone(), self.plus_file.clone()));
 
-        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.paint_end_of_hunk();
+        if self.config.collapse_leading_context > 0 {
+            self.painter.leading_context = Some(Vec::new());
+        }
         if self.should_write_generic_diff_header_header_line()? {
             handled_line = true;
         } else if self.should_handle()
@@ -256,21 +261,52 @@ impl StateMachine<'_> {
             self._handle_diff_header_header_line(self.source == Source::DiffUnified)?;
             self.handled_diff_header_header_line_file_pair
                 .clone_from(&self.current_file_pair);
-            Ok(())
+            self.write_empty_file_notice()
         } else {
             Ok(())
         }
     }
+
+    /// If the pending file was created or deleted without any content, say so: there are no hunks
+    /// to display. (Binary files, whose names are annotated by the "Binary files" handler, have
+    /// no hunks either but are not empty.)
+    fn write_empty_file_notice(&mut self) -> std::io::Result<()> {
+        if self.config.empty_file_style.is_omitted || self.config.color_only {
+            return Ok(());
+        }
+        let is_binary = self.minus_file.ends_with(BINARY_FILE_SUFFIX)
+            || self.plus_file.ends_with(BINARY_FILE_SUFFIX);
+        let notice = match (self.minus_file.as_str(), self.plus_file.as_str()) {
+            _ if is_binary => return Ok(()),
+            ("/dev/null", "/dev/null") => return Ok(()),
+            ("/dev/null", _) => "new empty file",
+            (_, "/dev/null") => "deleted empty file",
+            _ => return Ok(()),
+        };
+        writeln!(
+            &mut self.painter.writer,
+            "{}",
+            self.painter
+                .emitter
+                .paint(self.config.empty_file_style.ansi_term_style, notice)
+        )
+    }
 }
 
+/// Appended to the names of binary files by the "Binary files" handler.
+pub const BINARY_FILE_SUFFIX: &str = " (binary file)";
+
 /// Write `line` with DiffHeader styling.
-pub fn write_generic_diff_header_header_line(
+pub fn write_generic_diff_header_header_line<E: Emitter>(
     line: &str,
     raw_line: &str,
-    painter: &mut Painter,
+    painter: &mut Painter<E>,
     mode_info: &mut String,
     config: &Config,
 ) -> std::io::Result<()> {
+    if !config.color_only {
+        painter.write_file_separator()?;
+    }
     // If file_style is "omit", we'll skip the process and print nothing.
     // However in the case of color_only mode,
     // we won't skip because we can't change raw_line structure.
@@ -283,13 +319,19 @@ pub fn write_generic_diff_header_header_line(
         // Maintain 1-1 correspondence between input and output lines.
         writeln!(painter.writer)?;
     }
-    draw_fn(
-        painter.writer,
+    let (text, text_width) = draw::paint_text(
+        &painter.emitter,
         &format!("{}{}", line, if pad { " " } else { "" }),
         &format!("{}{}", raw_line, if pad { " " } else { "" }),
         mode_info,
-        &config.decorations_width,
         config.file_style,
+    );
+    draw_fn(
+        &mut painter.writer,
+        &painter.emitter,
+        &text,
+        text_width,
+        &config.decorations_width,
         decoration_ansi_term_style,
     )?;
     if !mode_info.is_empty() {
@@ -319,7 +361,7 @@ fn get_filename_from_diff_header_line_file_path(path: &str) -> Option<&str> {
     })
 }
 
-fn parse_diff_header_line(line: &str, git_diff_name: bool) -> (String, FileEvent) {
+pub fn parse_diff_header_line(line: &str, git_diff_name: bool) -> (String, FileEvent) {
     match line {
         line if line.starts_with("--- ") || line.starts_with("+++ ") => {
             let offset = 4;
@@ -473,6 +515,36 @@ mod tests {
     use crate::tests::integration_test_utils::{make_config_from_args, DeltaTest};
     use insta::assert_snapshot;
 
+    #[test]
+    fn test_syntax_follows_file_path() {
+        DeltaTest::with_args(&["--syntax-theme=GitHub", "--plus-style=syntax"])
+            .explain_ansi()
+            .with_input(DIFF_OF_SAME_LINE_IN_FILES_OF_DIFFERENT_SYNTAXES)
+            // Rust: `def` is not a keyword, but `return` is.
+            .expect_contains("\n(236)def (31)f(236)(x): (125)return(236) (24)'x'(normal)\n")
+            // Python
+            .expect_contains(
+                "\n(125)def(236) (97)f(236)((31)x(236)): (125)return(236) (24)'x'(normal)\n",
+            )
+            // No syntax matches the file extension: plain text.
+            .expect_contains("\n(236)def f(x): return 'x'(normal)\n");
+    }
+
+    #[test]
+    fn test_map_syntax() {
+        let python_line =
+            "\n(125)def(236) (97)f(236)((31)x(236)): (125)return(236) (24)'x'(normal)\n";
+        let output = DeltaTest::with_args(&[
+            "--syntax-theme=GitHub",
+            "--plus-style=syntax",
+            "--map-syntax=rs => Python, a.unknown => py",
+        ])
+        .explain_ansi()
+        .with_input(DIFF_OF_SAME_LINE_IN_FILES_OF_DIFFERENT_SYNTAXES)
+        .output;
+        assert_eq!(output.matches(python_line).count(), 3, "{}", output);
+    }
+
     #[test]
     fn test_get_filename_from_marker_line() {
         assert_eq!(
@@ -733,11 +805,11 @@ index 0000000..323fae0
         (blue)───(blue)┐(normal)
         (blue)1(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (203)#(231)!(203)/(231)usr(203)/(231)bin(203)/(231)env lua(normal)
+        (dim 203)#(231)!(203)/(231)usr(203)/(231)bin(203)/(231)env lua(normal)
 
-        (81)print(231)((186)"Hello"(231))(normal)
+        (dim 81)print(231)((186)"Hello"(231))(normal)
         (normal 52)-- World?(normal)
-        (81)print(231)((186)".."(231))(normal)
+        (dim 81)print(231)((186)".."(231))(normal)
 
         "###);
     }
@@ -761,21 +833,21 @@ index 0000000..323fae0
         (blue)───(blue)┐(normal)
         (blue)3(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (81)print(231)((186)"Hello"(231))(normal)
+        (dim 81)print(231)((186)"Hello"(231))(normal)
         (normal 52)-- World?(normal)
-        (81)print(231)((186)""(231))(normal)
+        (dim 81)print(231)((186)""(231))(normal)
 
         (blue)───(blue)┐(normal)
         (blue)6(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (81)print(231)((186)""(231))(normal)
+        (dim 81)print(231)((186)""(231))(normal)
         (81 22)print(231)((186)"World"(231))(normal)
-        (81)print(231)((186)""(231))(normal)
+        (dim 81)print(231)((186)""(231))(normal)
 
         (blue)────(blue)┐(normal)
         (blue)10(normal): (blue)│(normal)
         (blue)────(blue)┘(normal)
-        (81)print(231)((186)""(231))(normal)
+        (dim 81)print(231)((186)""(231))(normal)
         (normal 52)-- End(normal)
 
         (blue)a.lua ⟶   b.lua(normal)
@@ -784,11 +856,11 @@ index 0000000..323fae0
         (blue)───(blue)┐(normal)
         (blue)1(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (203)#(231)!(203)/(231)usr(203)/(231)bin(203)/(231)env lua(normal)
+        (dim 203)#(231)!(203)/(231)usr(203)/(231)bin(203)/(231)env lua(normal)
 
-        (81)print(231)((186)"Hello"(231))(normal)
+        (dim 81)print(231)((186)"Hello"(231))(normal)
         (normal 52)-- World?(normal)
-        (81)print(231)((186)".."(231))(normal)
+        (dim 81)print(231)((186)".."(231))(normal)
 
         (blue)c.lua ⟶   d.lua(normal)
         (blue)───────────────────────────────────────────(normal)
@@ -796,9 +868,9 @@ index 0000000..323fae0
         (blue)───(blue)┐(normal)
         (blue)1(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (203)#(231)!(203)/(231)usr(203)/(231)bin(203)/(231)env lua(normal)
+        (dim 203)#(231)!(203)/(231)usr(203)/(231)bin(203)/(231)env lua(normal)
 
-        (81)print(231)((186)"Hello"(231))(normal)
+        (dim 81)print(231)((186)"Hello"(231))(normal)
         (normal 52)-- World?(normal)
         "###);
     }
@@ -824,21 +896,21 @@ index 0000000..323fae0
         (blue)───(blue)┐(normal)
         (blue)3(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (81)print(231)((186)"Hello"(231))(normal)
+        (dim 81)print(231)((186)"Hello"(231))(normal)
         (normal 52)-- World?(normal)
-        (81)print(231)((186)""(231))(normal)
+        (dim 81)print(231)((186)""(231))(normal)
 
         (blue)───(blue)┐(normal)
         (blue)6(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (81)print(231)((186)""(231))(normal)
+        (dim 81)print(231)((186)""(231))(normal)
         (81 22)print(231)((186)"World"(231))(normal)
-        (81)print(231)((186)""(231))(normal)
+        (dim 81)print(231)((186)""(231))(normal)
 
         (blue)────(blue)┐(normal)
         (blue)10(normal): (blue)│(normal)
         (blue)────(blue)┘(normal)
-        (81)print(231)((186)""(231))(normal)
+        (dim 81)print(231)((186)""(231))(normal)
         (normal 52)-- End(normal)
 
         extra 2
@@ -849,11 +921,11 @@ index 0000000..323fae0
         (blue)───(blue)┐(normal)
         (blue)1(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (203)#(231)!(203)/(231)usr(203)/(231)bin(203)/(231)env lua(normal)
+        (dim 203)#(231)!(203)/(231)usr(203)/(231)bin(203)/(231)env lua(normal)
 
-        (81)print(231)((186)"Hello"(231))(normal)
+        (dim 81)print(231)((186)"Hello"(231))(normal)
         (normal 52)-- World?(normal)
-        (81)print(231)((186)".."(231))(normal)
+        (dim 81)print(231)((186)".."(231))(normal)
 
         extra 3
 
@@ -863,10 +935,31 @@ index 0000000..323fae0
         (blue)───(blue)┐(normal)
         (blue)1(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (203)#(231)!(203)/(231)usr(203)/(231)bin(203)/(231)env lua(normal)
+        (dim 203)#(231)!(203)/(231)usr(203)/(231)bin(203)/(231)env lua(normal)
 
-        (81)print(231)((186)"Hello"(231))(normal)
+        (dim 81)print(231)((186)"Hello"(231))(normal)
         (normal 52)-- World?(normal)
         "###);
     }
+
+    const DIFF_OF_SAME_LINE_IN_FILES_OF_DIFFERENT_SYNTAXES: &str = "\
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -0,0 +1 @@
++def f(x): return 'x'
+diff --git a/a.py b/a.py
+index 1111111..2222222 100644
+--- a/a.py
++++ b/a.py
+@@ -0,0 +1 @@
++def f(x): return 'x'
+diff --git a/a.unknown b/a.unknown
+index 1111111..2222222 100644
+--- a/a.unknown
++++ b/a.unknown
+@@ -0,0 +1 @@
++def f(x): return 'x'
+";
 }
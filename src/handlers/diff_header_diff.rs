@@ -1,7 +1,8 @@
 use crate::delta::{DiffType, InMergeConflict, MergeParents, State, StateMachine};
+use crate::emitter::Emitter;
 use crate::handlers::diff_header::{get_repeated_file_path_from_diff_line, FileEvent};
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     #[inline]
     fn test_diff_header_diff_line(&self) -> bool {
         self.line.starts_with("diff ")
@@ -12,7 +13,7 @@ impl StateMachine<'_> {
         if !self.test_diff_header_diff_line() {
             return Ok(false);
         }
-        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.paint_end_of_hunk();
         self.state =
             if self.line.starts_with("diff --cc ") || self.line.starts_with("diff --combined ") {
                 // We will determine the number of parents when we see the hunk header.
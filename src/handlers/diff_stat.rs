@@ -1,13 +1,16 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::borrow::Cow;
+use std::io::Write;
 
+use crate::ansi;
 use crate::config::Config;
 use crate::delta::{State, StateMachine};
+use crate::emitter::Emitter;
 use crate::features;
 use crate::utils;
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     #[inline]
     fn test_diff_stat_line(&self) -> bool {
         (self.state == State::CommitMeta || self.state == State::Unknown)
@@ -18,18 +21,28 @@ impl StateMachine<'_> {
         if !self.test_diff_stat_line() {
             return Ok(false);
         }
-        let mut handled_line = false;
+        let mut replacement_line = None;
         if self.config.relative_paths {
             if let Some(cwd) = self.config.cwd_relative_to_repo_root.as_deref() {
-                if let Some(replacement_line) =
-                    relativize_path_in_diff_stat_line(&self.raw_line, cwd, self.config)
-                {
-                    self.painter.emit()?;
-                    writeln!(self.painter.writer, "{replacement_line}")?;
-                    handled_line = true
-                }
+                replacement_line =
+                    relativize_path_in_diff_stat_line(&self.raw_line, cwd, self.config);
             }
         }
+        if self.config.diff_stat_colors {
+            let line = replacement_line.as_deref().unwrap_or(&self.line);
+            if let Some(colored_line) = color_graph_in_diff_stat_line(line, self.config) {
+                replacement_line = Some(colored_line);
+            }
+        }
+        let handled_line = replacement_line.is_some();
+        if let Some(replacement_line) = replacement_line {
+            self.painter.emit()?;
+            let mut line = String::new();
+            self.painter
+                .emitter
+                .emit_ansi_text(&replacement_line, &mut line);
+            writeln!(self.painter.writer, "{line}")?;
+        }
         Ok(handled_line)
     }
 }
@@ -43,6 +56,25 @@ lazy_static! {
         Regex::new(r" ([^\| ][^\|]+[^\| ]) +(\| +[0-9]+ .+)").unwrap();
 }
 
+// A regex to capture the `+` and `-` bars at the end of a diff stat line.
+lazy_static! {
+    static ref DIFF_STAT_GRAPH_REGEX: Regex = Regex::new(r"\| +[0-9]+ (\+*)(-*)$").unwrap();
+}
+
+pub fn color_graph_in_diff_stat_line(line: &str, config: &Config) -> Option<String> {
+    let caps = DIFF_STAT_GRAPH_REGEX.captures(line)?;
+    let (plus, minus) = (caps.get(1).unwrap(), caps.get(2).unwrap());
+    if plus.as_str().is_empty() && minus.as_str().is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{}{}{}",
+        &line[..plus.start()],
+        config.plus_style.paint(plus.as_str()),
+        config.minus_style.paint(minus.as_str()),
+    ))
+}
+
 pub fn relativize_path_in_diff_stat_line(
     line: &str,
     cwd_relative_to_repo_root: &str,
@@ -69,7 +101,7 @@ pub fn relativize_path_in_diff_stat_line(
     let suffix = caps.get(2).unwrap().as_str();
     let pad_width = config
         .diff_stat_align_width
-        .saturating_sub(relative_path.len());
+        .saturating_sub(ansi::measure_text_width(relative_path));
     let padding = " ".repeat(pad_width);
     Some(format!(" {formatted_path}{padding}{suffix}"))
 }
@@ -77,6 +109,8 @@ pub fn relativize_path_in_diff_stat_line(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ansi;
+    use crate::tests::integration_test_utils::{make_config_from_args, DeltaTest};
 
     #[test]
     fn test_diff_stat_line_regex_1() {
@@ -96,6 +130,34 @@ mod tests {
         assert_eq!(caps.get(2).unwrap().as_str(), "|  2 ++");
     }
 
+    #[test]
+    fn test_color_graph_in_diff_stat_line() {
+        let config = make_config_from_args(&["--plus-style=green", "--minus-style=red"]);
+        let line = color_graph_in_diff_stat_line(" src/delta.rs  | 14 ++++++++++----", &config);
+        assert_eq!(
+            line.map(|line| ansi::explain_ansi(&line, false)),
+            Some("(normal) src/delta.rs  | 14 (green)++++++++++(red)----".to_string())
+        );
+        assert!(color_graph_in_diff_stat_line(" img.png | Bin 0 -> 12 bytes", &config).is_none());
+    }
+
+    #[test]
+    fn test_diff_stat_colors() {
+        let input = " src/delta.rs  | 14 ++++++++++----\n src/config.rs |  2 --\n";
+        DeltaTest::with_args(&[
+            "--diff-stat-colors",
+            "--plus-style=green",
+            "--minus-style=red",
+        ])
+        .explain_ansi()
+        .with_input(input)
+        .expect_contains(" src/delta.rs  | 14 (green)++++++++++(red)----(normal)\n")
+        .expect_contains(" src/config.rs |  2 (red)--(normal)\n");
+        DeltaTest::with_args(&[])
+            .with_input(input)
+            .expect_raw_contains(input);
+    }
+
     #[test]
     fn test_relative_path() {
         for (path, cwd_relative_to_repo_root, expected) in &[
@@ -3,81 +3,87 @@ use std::io::Write;
 
 use crate::ansi;
 use crate::cli::Width;
+use crate::emitter::Emitter;
 use crate::style::{DecorationStyle, Style};
 
-fn paint_text(text_style: Style, text: &str, addendum: &str) -> String {
-    if addendum.is_empty() {
-        text_style.paint(text).to_string()
+/// Format the text to be drawn, returning it together with the width the decoration must span.
+/// A raw `text_style` writes `raw_text` unchanged (save for the translation performed by the
+/// emitter).
+pub fn paint_text<E: Emitter>(
+    emitter: &E,
+    text: &str,
+    raw_text: &str,
+    addendum: &str,
+    text_style: Style,
+) -> (String, usize) {
+    let formatted = if text_style.is_raw {
+        let mut formatted = String::new();
+        emitter.emit_ansi_text(raw_text, &mut formatted);
+        formatted
+    } else if addendum.is_empty() {
+        emitter.paint(text_style.ansi_term_style, text)
     } else {
-        text_style
-            .paint(text.to_string() + " (" + addendum + ")")
-            .to_string()
-    }
+        emitter.paint(
+            text_style.ansi_term_style,
+            &(text.to_string() + " (" + addendum + ")"),
+        )
+    };
+    (formatted, ansi::measure_text_width(text))
 }
 
-pub type DrawFunction = dyn FnMut(
-    &mut dyn Write,
-    &str,
-    &str,
-    &str,
-    &Width,
-    Style,
-    ansi_term::Style,
-) -> std::io::Result<()>;
+/// Write formatted text, of the given width, with a decoration.
+pub type DrawFunction<'e, E> = dyn 'e
+    + FnMut(&mut dyn Write, &E, &str, usize, &Width, ansi_term::Style) -> std::io::Result<()>;
 
-pub fn get_draw_function(
+pub fn get_draw_function<'e, E: Emitter + 'e>(
     decoration_style: DecorationStyle,
-) -> (Box<DrawFunction>, bool, ansi_term::Style) {
+) -> (Box<DrawFunction<'e, E>>, bool, ansi_term::Style) {
     match decoration_style {
-        DecorationStyle::Box(style) => (Box::new(write_boxed), true, style),
+        DecorationStyle::Box(style) => (Box::new(write_boxed::<E>), true, style),
         DecorationStyle::BoxWithUnderline(style) => {
-            (Box::new(write_boxed_with_underline), true, style)
+            (Box::new(write_boxed_with_underline::<E>), true, style)
         }
         DecorationStyle::BoxWithOverline(style) => {
             // TODO: not implemented
-            (Box::new(write_boxed), true, style)
+            (Box::new(write_boxed::<E>), true, style)
         }
         DecorationStyle::BoxWithUnderOverline(style) => {
             // TODO: not implemented
-            (Box::new(write_boxed), true, style)
+            (Box::new(write_boxed::<E>), true, style)
+        }
+        DecorationStyle::Underline(style) => (Box::new(write_underlined::<E>), false, style),
+        DecorationStyle::Overline(style) => (Box::new(write_overlined::<E>), false, style),
+        DecorationStyle::UnderOverline(style) => {
+            (Box::new(write_underoverlined::<E>), false, style)
         }
-        DecorationStyle::Underline(style) => (Box::new(write_underlined), false, style),
-        DecorationStyle::Overline(style) => (Box::new(write_overlined), false, style),
-        DecorationStyle::UnderOverline(style) => (Box::new(write_underoverlined), false, style),
         DecorationStyle::NoDecoration => (
-            Box::new(write_no_decoration),
+            Box::new(write_no_decoration::<E>),
             false,
             ansi_term::Style::new(),
         ),
     }
 }
 
-fn write_no_decoration(
+fn write_no_decoration<E: Emitter>(
     writer: &mut dyn Write,
+    _emitter: &E,
     text: &str,
-    raw_text: &str,
-    addendum: &str,
+    _text_width: usize,
     _line_width: &Width, // ignored
-    text_style: Style,
     _decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
-    if text_style.is_raw {
-        writeln!(writer, "{raw_text}")?;
-    } else {
-        writeln!(writer, "{}", paint_text(text_style, text, addendum))?;
-    }
+    writeln!(writer, "{text}")?;
     Ok(())
 }
 
 /// Write text to stream, surrounded by a box, leaving the cursor just
 /// beyond the bottom right corner.
-pub fn write_boxed(
+pub fn write_boxed<E: Emitter>(
     writer: &mut dyn Write,
+    emitter: &E,
     text: &str,
-    raw_text: &str,
-    addendum: &str,
+    text_width: usize,
     _line_width: &Width, // ignored
-    text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
     let up_left = if decoration_style.is_bold {
@@ -85,53 +91,35 @@ pub fn write_boxed(
     } else {
         box_drawing::light::UP_LEFT
     };
-    let box_width = ansi::measure_text_width(text);
-    write_boxed_partial(
-        writer,
-        text,
-        raw_text,
-        addendum,
-        box_width,
-        text_style,
-        decoration_style,
-    )?;
-    writeln!(writer, "{}", decoration_style.paint(up_left))?;
+    write_boxed_partial(writer, emitter, text, text_width, decoration_style)?;
+    writeln!(writer, "{}", emitter.paint(decoration_style, up_left))?;
     Ok(())
 }
 
 /// Write text to stream, surrounded by a box, and extend a line from
 /// the bottom right corner.
-fn write_boxed_with_underline(
+fn write_boxed_with_underline<E: Emitter>(
     writer: &mut dyn Write,
+    emitter: &E,
     text: &str,
-    raw_text: &str,
-    addendum: &str,
+    text_width: usize,
     line_width: &Width,
-    text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
-    let box_width = ansi::measure_text_width(text);
-    write_boxed_with_horizontal_whisker(
-        writer,
-        text,
-        raw_text,
-        addendum,
-        box_width,
-        text_style,
-        decoration_style,
-    )?;
+    let box_width = text_width;
+    write_boxed_with_horizontal_whisker(writer, emitter, text, box_width, decoration_style)?;
     let line_width = match *line_width {
         Width::Fixed(n) => n,
         Width::Variable => box_width,
     };
     write_horizontal_line(
         writer,
+        emitter,
         if line_width > box_width {
             line_width - box_width - 1
         } else {
             0
         },
-        text_style,
         decoration_style,
     )?;
     writeln!(writer)?;
@@ -144,87 +132,78 @@ enum UnderOverline {
     Underover,
 }
 
-fn write_underlined(
+fn write_underlined<E: Emitter>(
     writer: &mut dyn Write,
+    emitter: &E,
     text: &str,
-    raw_text: &str,
-    addendum: &str,
+    text_width: usize,
     line_width: &Width,
-    text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
     _write_under_or_over_lined(
         UnderOverline::Under,
         writer,
+        emitter,
         text,
-        raw_text,
-        addendum,
+        text_width,
         line_width,
-        text_style,
         decoration_style,
     )
 }
 
-fn write_overlined(
+fn write_overlined<E: Emitter>(
     writer: &mut dyn Write,
+    emitter: &E,
     text: &str,
-    raw_text: &str,
-    addendum: &str,
+    text_width: usize,
     line_width: &Width,
-    text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
     _write_under_or_over_lined(
         UnderOverline::Over,
         writer,
+        emitter,
         text,
-        raw_text,
-        addendum,
+        text_width,
         line_width,
-        text_style,
         decoration_style,
     )
 }
 
-fn write_underoverlined(
+fn write_underoverlined<E: Emitter>(
     writer: &mut dyn Write,
+    emitter: &E,
     text: &str,
-    raw_text: &str,
-    addendum: &str,
+    text_width: usize,
     line_width: &Width,
-    text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
     _write_under_or_over_lined(
         UnderOverline::Underover,
         writer,
+        emitter,
         text,
-        raw_text,
-        addendum,
+        text_width,
         line_width,
-        text_style,
         decoration_style,
     )
 }
 
-#[allow(clippy::too_many_arguments)]
-fn _write_under_or_over_lined(
+fn _write_under_or_over_lined<E: Emitter>(
     underoverline: UnderOverline,
     writer: &mut dyn Write,
+    emitter: &E,
     text: &str,
-    raw_text: &str,
-    addendum: &str,
+    text_width: usize,
     line_width: &Width,
-    text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
-    let text_width = ansi::measure_text_width(text);
     let line_width = match *line_width {
         Width::Fixed(n) => max(n, text_width),
         Width::Variable => text_width,
     };
     let write_line = |writer: &mut dyn Write| -> std::io::Result<()> {
-        write_horizontal_line(writer, line_width, text_style, decoration_style)?;
+        write_horizontal_line(writer, emitter, line_width, decoration_style)?;
         writeln!(writer)?;
         Ok(())
     };
@@ -232,11 +211,7 @@ fn _write_under_or_over_lined(
         UnderOverline::Under => {}
         _ => write_line(writer)?,
     }
-    if text_style.is_raw {
-        writeln!(writer, "{raw_text}")?;
-    } else {
-        writeln!(writer, "{}", paint_text(text_style, text, addendum))?;
-    }
+    writeln!(writer, "{text}")?;
     match underoverline {
         UnderOverline::Over => {}
         _ => write_line(writer)?,
@@ -244,10 +219,10 @@ fn _write_under_or_over_lined(
     Ok(())
 }
 
-fn write_horizontal_line(
+fn write_horizontal_line<E: Emitter>(
     writer: &mut dyn Write,
+    emitter: &E,
     width: usize,
-    _text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
     let horizontal = if decoration_style.is_bold {
@@ -258,17 +233,15 @@ fn write_horizontal_line(
     write!(
         writer,
         "{}",
-        decoration_style.paint(horizontal.repeat(width))
+        emitter.paint(decoration_style, &horizontal.repeat(width))
     )
 }
 
-fn write_boxed_with_horizontal_whisker(
+fn write_boxed_with_horizontal_whisker<E: Emitter>(
     writer: &mut dyn Write,
+    emitter: &E,
     text: &str,
-    raw_text: &str,
-    addendum: &str,
     box_width: usize,
-    text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
     let up_horizontal = if decoration_style.is_bold {
@@ -276,26 +249,16 @@ fn write_boxed_with_horizontal_whisker(
     } else {
         box_drawing::light::UP_HORIZONTAL
     };
-    write_boxed_partial(
-        writer,
-        text,
-        raw_text,
-        addendum,
-        box_width,
-        text_style,
-        decoration_style,
-    )?;
-    write!(writer, "{}", decoration_style.paint(up_horizontal))?;
+    write_boxed_partial(writer, emitter, text, box_width, decoration_style)?;
+    write!(writer, "{}", emitter.paint(decoration_style, up_horizontal))?;
     Ok(())
 }
 
-fn write_boxed_partial(
+fn write_boxed_partial<E: Emitter>(
     writer: &mut dyn Write,
+    emitter: &E,
     text: &str,
-    raw_text: &str,
-    addendum: &str,
     box_width: usize,
-    text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
     let (horizontal, down_left, vertical) = if decoration_style.is_bold {
@@ -315,18 +278,14 @@ fn write_boxed_partial(
     writeln!(
         writer,
         "{}{}",
-        decoration_style.paint(&horizontal_edge),
-        decoration_style.paint(down_left),
+        emitter.paint(decoration_style, &horizontal_edge),
+        emitter.paint(decoration_style, down_left),
     )?;
-    if text_style.is_raw {
-        write!(writer, "{raw_text}")?;
-    } else {
-        write!(writer, "{}", paint_text(text_style, text, addendum))?;
-    }
+    write!(writer, "{text}")?;
     write!(
         writer,
         "{}\n{}",
-        decoration_style.paint(vertical),
-        decoration_style.paint(&horizontal_edge),
+        emitter.paint(decoration_style, vertical),
+        emitter.paint(decoration_style, &horizontal_edge),
     )
 }
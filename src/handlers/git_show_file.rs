@@ -1,8 +1,9 @@
 use crate::delta::{State, StateMachine};
+use crate::emitter::Emitter;
 use crate::paint::{BgShouldFill, StyleSectionSpecifier};
 use crate::utils::process;
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     // If this is a line of `git show $revision:/path/to/file.ext` output then
     // syntax-highlight it as language `ext`.
     pub fn handle_git_show_file_line(&mut self) -> std::io::Result<bool> {
@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::io::Write;
 
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -10,6 +11,7 @@ use crate::config::{
     HunkHeaderIncludeLineNumber,
 };
 use crate::delta::{State, StateMachine};
+use crate::emitter::Emitter;
 use crate::handlers::{self, ripgrep_json};
 use crate::paint::{self, BgShouldFill, StyleSectionSpecifier};
 use crate::style::Style;
@@ -22,6 +24,8 @@ pub struct GrepLine<'b> {
     pub grep_type: GrepType,
     pub path: Cow<'b, str>,
     pub line_number: Option<usize>,
+    // The column of the first match, with `git grep --column`.
+    pub column: Option<usize>,
     pub line_type: LineType,
     pub code: Cow<'b, str>,
     pub submatches: Option<Vec<(usize, usize)>>,
@@ -79,7 +83,7 @@ impl LineType {
     }
 }
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     // If this is a line of grep output then render it accordingly.
     pub fn handle_grep_line(&mut self) -> std::io::Result<bool> {
         self.painter.emit()?;
@@ -213,6 +217,7 @@ impl StateMachine<'_> {
                     self.config.grep_match_line_style,
                     &grep_line.path,
                     grep_line.line_number,
+                    grep_line.column,
                 )
                 .unwrap_or(StyleSectionSpecifier::Style(
                     self.config.grep_match_line_style,
@@ -312,14 +317,29 @@ impl StateMachine<'_> {
             paint::paint_file_path_with_line_number(
                 grep_line.line_number,
                 &grep_line.path,
-                OUTPUT_CONFIG.pad_line_number,
+                OUTPUT_CONFIG.pad_line_number && grep_line.column.is_none(),
                 separator,
                 true,
                 Some(self.config.grep_file_style),
                 Some(self.config.grep_line_number_style),
+                &self.painter.emitter,
                 self.config
             )
         )?;
+        if let Some(column) = grep_line.column {
+            let mut column_with_separator = String::new();
+            self.painter.emitter.emit_sections(
+                &[
+                    (
+                        self.config.grep_line_number_style.ansi_term_style,
+                        &column.to_string(),
+                    ),
+                    (ansi_term::Style::new(), separator),
+                ],
+                &mut column_with_separator,
+            );
+            write!(self.painter.writer, "{column_with_separator}")?;
+        }
         Ok(())
     }
 
@@ -354,6 +374,7 @@ impl StateMachine<'_> {
                     self.config.grep_match_line_style,
                     &grep_line.path,
                     grep_line.line_number,
+                    grep_line.column,
                 )
                 .unwrap_or(StyleSectionSpecifier::Style(
                     self.config.grep_match_line_style,
@@ -400,12 +421,14 @@ fn get_code_style_sections<'b>(
     non_match_style: Style,
     path: &str,
     line_number: Option<usize>,
+    column: Option<usize>,
 ) -> Option<StyleSectionSpecifier<'b>> {
     if let Some(prefix_end) = ansi::ansi_preserving_index(
         raw_line,
-        match line_number {
-            Some(n) => format!("{}:{}:", path, n).len() - 1,
-            None => path.len(),
+        match (line_number, column) {
+            (Some(n), Some(column)) => format!("{}:{}:{}:", path, n, column).len() - 1,
+            (Some(n), None) => format!("{}:{}:", path, n).len() - 1,
+            (None, _) => path.len(),
         },
     ) {
         let match_style_sections = ansi::parse_style_sections(&raw_line[(prefix_end + 1)..])
@@ -664,38 +687,41 @@ $
     .unwrap()
 }
 
-pub fn parse_grep_line(line: &str) -> Option<GrepLine> {
+pub fn parse_grep_line(line: &str) -> Option<GrepLine<'_>> {
     if line.starts_with('{') {
         ripgrep_json::parse_line(line)
     } else {
         match &*process::calling_process() {
-            process::CallingProcess::GitGrep(_) | process::CallingProcess::OtherGrep => [
+            calling_process @ (process::CallingProcess::GitGrep(_)
+            | process::CallingProcess::OtherGrep) => [
                 &*GREP_LINE_REGEX_ASSUMING_FILE_EXTENSION_AND_LINE_NUMBER,
                 &*GREP_LINE_REGEX_ASSUMING_FILE_EXTENSION_NO_SPACES,
                 &*GREP_LINE_REGEX_ASSUMING_FILE_EXTENSION,
                 &*GREP_LINE_REGEX_ASSUMING_NO_INTERNAL_SEPARATOR_CHARS,
             ]
             .iter()
-            .find_map(|regex| _parse_grep_line(regex, line)),
+            .find_map(|regex| _parse_grep_line(regex, line))
+            .map(|grep_line| split_column(grep_line, calling_process)),
             _ => None,
         }
     }
 }
 
-pub fn parse_raw_grep_line(raw_line: &str) -> Option<GrepLine> {
+pub fn parse_raw_grep_line(raw_line: &str) -> Option<GrepLine<'_>> {
     // Early exit if we don't have an escape sequence
     if !raw_line.starts_with('\x1b') {
         return None;
     }
+    let calling_process = process::calling_process();
     if !matches!(
-        &*process::calling_process(),
+        &*calling_process,
         process::CallingProcess::GitGrep(_) | process::CallingProcess::OtherGrep
     ) {
         return None;
     }
     _parse_grep_line(&GREP_LINE_REGEX_ASSUMING_COLOR, raw_line).map(|mut grep_line| {
         grep_line.code = ansi::strip_ansi_codes(&grep_line.code).into();
-        grep_line
+        split_column(grep_line, &calling_process)
     })
 }
 
@@ -723,17 +749,49 @@ pub fn _parse_grep_line<'b>(regex: &Regex, line: &'b str) -> Option<GrepLine<'b>
         grep_type: GrepType::Classic,
         path: file,
         line_number: *line_number,
+        column: None,
         line_type: *line_type,
         code,
         submatches: None,
     })
 }
 
+// With --column, grep tools emit the column of the first match after the line number of match
+// lines, e.g. "src/co-7-fig.rs:7:12:xxx". Since code may itself start with a number followed by a
+// colon, the column is only looked for in the output of `git grep --column`: the command line of
+// other grep tools is not known.
+fn split_column<'b>(
+    mut grep_line: GrepLine<'b>,
+    calling_process: &process::CallingProcess,
+) -> GrepLine<'b> {
+    if grep_line.line_type != LineType::Match || grep_line.line_number.is_none() {
+        return grep_line;
+    }
+    match calling_process {
+        process::CallingProcess::GitGrep(command_line)
+            if command_line.long_options.contains("--column") => {}
+        _ => return grep_line,
+    }
+    let Some((column, code)) = grep_line.code.split_once(':') else {
+        return grep_line;
+    };
+    if column.is_empty() || !column.bytes().all(|b| b.is_ascii_digit()) {
+        return grep_line;
+    }
+    grep_line.column = column.parse().ok();
+    grep_line.code = match grep_line.code {
+        Cow::Borrowed(code) => Cow::Borrowed(&code[column.len() + 1..]),
+        Cow::Owned(_) => Cow::Owned(code.to_string()),
+    };
+    grep_line
+}
+
 #[cfg(test)]
 mod tests {
     use crate::handlers::grep::{
         parse_grep_line, parse_raw_grep_line, GrepLine, GrepType, LineType,
     };
+    use crate::tests::integration_test_utils::DeltaTest;
     use crate::utils::process::tests::FakeParentArgs;
 
     #[test]
@@ -747,6 +805,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/co-7-fig.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Match,
                 code: "xxx".into(),
                 submatches: None,
@@ -759,6 +818,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/config.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Match,
                 code: "use crate::minusplus::MinusPlus;".into(),
                 submatches: None,
@@ -773,6 +833,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/config.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Match,
                 code: "    pub line_numbers_style_minusplus: MinusPlus<Style>,".into(),
                 submatches: None,
@@ -785,6 +846,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/con-fig.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Match,
                 code: "use crate::minusplus::MinusPlus;".into(),
                 submatches: None,
@@ -799,6 +861,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/con-fig.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Match,
                 code: "    pub line_numbers_style_minusplus: MinusPlus<Style>,".into(),
                 submatches: None,
@@ -813,6 +876,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                                 path: "src/de lta.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Match,
                 code: "pub fn delta<I>(lines: ByteLines<I>, writer: &mut dyn Write, config: &Config) -> std::io::Result<()>".into(),
                 submatches: None,
@@ -827,6 +891,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                                 path: "src/de lta.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Match,
                 code: "    pub fn new(writer: &'a mut dyn Write, config: &'a Config) -> Self {".into(),
                 submatches: None,
@@ -846,6 +911,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/co-7-fig.rs".into(),
                 line_number: Some(7),
+                column: None,
                 line_type: LineType::Match,
                 code: "xxx".into(),
                 submatches: None,
@@ -858,6 +924,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/config.rs".into(),
                 line_number: Some(21),
+                column: None,
                 line_type: LineType::Match,
                 code: "use crate::minusplus::MinusPlus;".into(),
                 submatches: None,
@@ -872,6 +939,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/config.rs".into(),
                 line_number: Some(95),
+                column: None,
                 line_type: LineType::Match,
                 code: "    pub line_numbers_style_minusplus: MinusPlus<Style>,".into(),
                 submatches: None,
@@ -884,6 +952,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "Makefile".into(),
                 line_number: Some(10),
+                column: None,
                 line_type: LineType::Match,
                 code: "test: unit-test end-to-end-test".into(),
                 submatches: None,
@@ -898,6 +967,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "Makefile".into(),
                 line_number: Some(16),
+                column: None,
                 line_type: LineType::Match,
                 code: "    ./tests/test_raw_output_matches_git_on_full_repo_history".into(),
                 submatches: None,
@@ -905,6 +975,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_grep_n_column_match() {
+        let _args = FakeParentArgs::for_scope("git grep -n --column xxx");
+
+        assert_eq!(
+            parse_grep_line("src/co-7-fig.rs:7:12:  xxx: 1"),
+            Some(GrepLine {
+                grep_type: GrepType::Classic,
+                path: "src/co-7-fig.rs".into(),
+                line_number: Some(7),
+                column: Some(12),
+                line_type: LineType::Match,
+                code: "  xxx: 1".into(),
+                submatches: None,
+            })
+        );
+
+        // Context lines have no column.
+        assert_eq!(
+            parse_grep_line("src/co-7-fig.rs-8-12: yyy"),
+            Some(GrepLine {
+                grep_type: GrepType::Classic,
+                path: "src/co-7-fig.rs".into(),
+                line_number: Some(8),
+                column: None,
+                line_type: LineType::Context,
+                code: "12: yyy".into(),
+                submatches: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_grep_n_match_without_column() {
+        let _args = FakeParentArgs::for_scope("git grep -n xxx");
+
+        assert_eq!(
+            parse_grep_line("src/config.yaml:7:12: xxx"),
+            Some(GrepLine {
+                grep_type: GrepType::Classic,
+                path: "src/config.yaml".into(),
+                line_number: Some(7),
+                column: None,
+                line_type: LineType::Match,
+                code: "12: xxx".into(),
+                submatches: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_other_grep_match_starting_with_number() {
+        let _args = FakeParentArgs::for_scope("rg -n xxx");
+
+        assert_eq!(
+            parse_grep_line("src/config.yaml:7:12: xxx"),
+            Some(GrepLine {
+                grep_type: GrepType::Classic,
+                path: "src/config.yaml".into(),
+                line_number: Some(7),
+                column: None,
+                line_type: LineType::Match,
+                code: "12: xxx".into(),
+                submatches: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_grep_n_column_output() {
+        DeltaTest::with_args(&[
+            "--grep-file-style=blue",
+            "--grep-line-number-style=green",
+            "--grep-match-line-style=normal",
+            "--grep-match-word-style=red",
+        ])
+        .with_calling_process("git grep -n --column --color=always xxx")
+        .explain_ansi()
+        .with_input(
+            "\x1b[35msrc/a.rs\x1b[m\x1b[36m:\x1b[m\x1b[32m7\x1b[m\x1b[36m:\x1b[m\x1b[32m5\x1b[m\x1b[36m:\x1b[mlet \x1b[1;31mxxx\x1b[m = 1;\n\
+             src/a.rs:9:9:    xxx();\n",
+        )
+        .expect(
+            "
+(blue)src/a.rs(normal):(green)7(normal):(green)5(normal):let (red)xxx(normal) = 1;
+(blue)src/a.rs(normal):(green)9(normal):(green)9(normal):    xxx();",
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_parse_grep_n_match_file_name_with_dashes_and_no_extension() {
@@ -920,6 +1079,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "etc/examples/119-within-line-edits".into(),
                 line_number: Some(4),
+                column: None,
                 line_type: LineType::Match,
                 code: "repo=$(mktemp -d)".into(),
                 submatches: None,
@@ -939,6 +1099,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "etc/META-INF/foo.properties".into(),
                 line_number: Some(4),
+                column: None,
                 line_type: LineType::Match,
                 code: "value=hi-there".into(),
                 submatches: None,
@@ -961,6 +1122,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/zlib-ng/configure".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Match,
                 code: r#"    -a*=* | --archs=*) ARCHS=$(echo $1 | sed 's/.*=//'); shift ;;"#.into(),
                 submatches: None,
@@ -975,6 +1137,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/zlib-ng/configure".into(),
                 line_number: Some(214),
+                column: None,
                 line_type: LineType::Match,
                 code: r#"    -a*=* | --archs=*) ARCHS=$(echo $1 | sed 's/.*=//'); shift ;;"#.into(),
                 submatches: None,
@@ -994,6 +1157,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/co-7-fig.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Context,
                 code: "xxx".into(),
                 submatches: None,
@@ -1006,6 +1170,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/config.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Context,
                 code: "    pub available_terminal_width: usize,".into(),
                 submatches: None,
@@ -1018,6 +1183,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/con-fig.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Context,
                 code: "use crate::minusplus::MinusPlus;".into(),
                 submatches: None,
@@ -1030,6 +1196,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "de-lta.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Context,
                 code: "            if self.source == Source::Unknown {".into(),
                 submatches: None,
@@ -1042,6 +1209,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "aaa/bbb.scala".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Context,
                 code: r#"              s"xxx.yyy.zzz: $ccc ddd""#.into(),
                 submatches: None,
@@ -1054,6 +1222,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "aaa/bbb.scala".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Context,
                 code: r#"  val atRegex = Regex.compile("(@.*)|(-shdw@.*)""#.into(),
                 submatches: None,
@@ -1074,6 +1243,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/co-7-fig.rs".into(),
                 line_number: Some(7),
+                column: None,
                 line_type: LineType::Context,
                 code: "xxx".into(),
                 submatches: None,
@@ -1086,6 +1256,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/config.rs".into(),
                 line_number: Some(58),
+                column: None,
                 line_type: LineType::Context,
                 code: "    pub available_terminal_width: usize,".into(),
                 submatches: None,
@@ -1098,6 +1269,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "foo.rs".into(),
                 line_number: Some(12),
+                column: None,
                 line_type: LineType::Context,
                 code: r#"  .x-"#.into(),
                 submatches: None,
@@ -1110,6 +1282,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "foo.rs".into(),
                 line_number: Some(12),
+                column: None,
                 line_type: LineType::Context,
                 code: r#".x-"#.into(),
                 submatches: None,
@@ -1129,6 +1302,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "Makefile".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::Match,
                 code: "xxx".into(),
                 submatches: None,
@@ -1148,6 +1322,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "Makefile".into(),
                 line_number: Some(7),
+                column: None,
                 line_type: LineType::Match,
                 code: "xxx".into(),
                 submatches: None,
@@ -1169,6 +1344,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/config.rs".into(),
                 line_number: None,
+                column: None,
                 line_type: LineType::ContextHeader,
                 code: "pub struct Config {".into(),
                 submatches: None,
@@ -1190,6 +1366,7 @@ mod tests {
                 grep_type: GrepType::Classic,
                 path: "src/config.rs".into(),
                 line_number: Some(57),
+                column: None,
                 line_type: LineType::ContextHeader,
                 code: "pub struct Config {".into(),
                 submatches: None,
@@ -1246,7 +1423,14 @@ mod tests {
         let grep = parse_grep_line(&stripped).unwrap();
 
         assert_eq!(
-            get_code_style_sections(&working_example, hit, miss, &grep.path, grep.line_number),
+            get_code_style_sections(
+                &working_example,
+                hit,
+                miss,
+                &grep.path,
+                grep.line_number,
+                grep.column
+            ),
             Some(StyleSectionSpecifier::StyleSections(vec![
                 (miss, "  - "),
                 (hit, "kind: Service"),
@@ -1264,7 +1448,8 @@ mod tests {
                 hit,
                 miss,
                 &broken_grep.path,
-                broken_grep.line_number
+                broken_grep.line_number,
+                broken_grep.column
             ),
             Some(StyleSectionSpecifier::StyleSections(vec![(
                 hit,
@@ -1282,7 +1467,8 @@ mod tests {
                 hit,
                 miss,
                 &plus_grep.path,
-                plus_grep.line_number
+                plus_grep.line_number,
+                plus_grep.column
             ),
             Some(StyleSectionSpecifier::StyleSections(vec![
                 (miss, " +        let (style, non_emph_style) = "),
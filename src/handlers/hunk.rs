@@ -5,7 +5,8 @@ use lazy_static::lazy_static;
 use crate::cli;
 use crate::config::{delta_unreachable, Config};
 use crate::delta::{DiffType, InMergeConflict, MergeParents, State, StateMachine};
-use crate::paint::{prepare, prepare_raw_line};
+use crate::emitter::Emitter;
+use crate::paint::{prepare_marking_tab_symbols, prepare_raw_line, LineInfo};
 use crate::style;
 use crate::utils::process::{self, CallingProcess};
 use crate::utils::tabs;
@@ -40,7 +41,7 @@ fn compute_is_word_diff() -> bool {
     }
 }
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     #[inline]
     fn test_hunk_line(&self) -> bool {
         matches!(
@@ -78,7 +79,12 @@ impl StateMachine<'_> {
         if let State::HunkHeader(_, parsed_hunk_header, line, raw_line) = &self.state.clone() {
             self.emit_hunk_header_line(parsed_hunk_header, line, raw_line)?;
         }
-        self.state = match new_line_state(&self.line, &self.raw_line, &self.state, self.config) {
+        let new_state = new_line_state(&self.line, &self.raw_line, &self.state, self.config);
+        if !matches!(new_state, Some(HunkZero(_, _))) {
+            // The leading context of the file, if held back, ends here.
+            self.painter.paint_leading_context();
+        }
+        self.state = match new_state {
             Some(HunkMinus(diff_type, raw_line)) => {
                 if let HunkPlus(_, _) = self.state {
                     // We have just entered a new subhunk; process the previous one
@@ -86,17 +92,31 @@ impl StateMachine<'_> {
                     self.painter.paint_buffered_minus_and_plus_lines();
                 }
                 let n_parents = diff_type.n_parents();
-                let line = prepare(&self.line, n_parents, self.config);
+                let (line, tab_symbols) =
+                    prepare_marking_tab_symbols(&self.line, n_parents, self.config);
                 let state = HunkMinus(diff_type, raw_line);
-                self.painter.minus_lines.push((line, state.clone()));
+                let info = LineInfo {
+                    raw_indentation: get_indentation(&self.line, n_parents),
+                    tab_symbols,
+                    is_moved: self.moved_lines.contains(&self.line_index),
+                    no_newline_marker: None,
+                };
+                self.painter.minus_lines.push((line, state.clone(), info));
                 self.minus_line_counter.count_line();
                 state
             }
             Some(HunkPlus(diff_type, raw_line)) => {
                 let n_parents = diff_type.n_parents();
-                let line = prepare(&self.line, n_parents, self.config);
+                let (line, tab_symbols) =
+                    prepare_marking_tab_symbols(&self.line, n_parents, self.config);
                 let state = HunkPlus(diff_type, raw_line);
-                self.painter.plus_lines.push((line, state.clone()));
+                let info = LineInfo {
+                    raw_indentation: get_indentation(&self.line, n_parents),
+                    tab_symbols,
+                    is_moved: self.moved_lines.contains(&self.line_index),
+                    no_newline_marker: None,
+                };
+                self.painter.plus_lines.push((line, state.clone(), info));
                 state
             }
             Some(HunkZero(diff_type, raw_line)) => {
@@ -109,20 +129,51 @@ impl StateMachine<'_> {
                 } else {
                     diff_type.n_parents()
                 };
-                let line = prepare(&self.line, n_parents, self.config);
+                let (line, tab_symbols) =
+                    prepare_marking_tab_symbols(&self.line, n_parents, self.config);
                 let state = State::HunkZero(diff_type, raw_line);
-                self.painter.paint_zero_line(&line, state.clone());
+                if let Some(leading_context) = self.painter.leading_context.as_mut() {
+                    leading_context.push((line, tab_symbols, state.clone()));
+                } else {
+                    self.painter
+                        .paint_zero_line(&line, &tab_symbols, state.clone());
+                }
                 self.minus_line_counter.count_line();
                 state
             }
+            _ if self.line.starts_with('\\') => {
+                // A '\ No newline at end of file' marker. If it follows a buffered minus or plus
+                // line, it is held with that line and written after the lines of its side, so
+                // that the lines of the subhunk can still be paired, unless the order of the input
+                // lines has to be preserved.
+                let marker = self.painter.emitter.paint(
+                    self.config.no_newline_marker_style.ansi_term_style,
+                    &tabs::expand(&self.line, &self.config.tab_cfg),
+                );
+                let preceding_line = match self.state {
+                    _ if self.config.color_only => None,
+                    HunkMinus(_, _) => self.painter.minus_lines.last_mut(),
+                    HunkPlus(_, _) => self.painter.plus_lines.last_mut(),
+                    _ => None,
+                };
+                if let Some((_, _, info)) = preceding_line {
+                    info.no_newline_marker = Some(marker);
+                    self.state.clone()
+                } else {
+                    self.painter.paint_buffered_minus_and_plus_lines();
+                    self.painter.output_buffer.push_str(&marker);
+                    self.painter.output_buffer.push('\n');
+                    State::HunkZero(Unified, None)
+                }
+            }
             _ => {
-                // The first character here could be e.g. '\' from '\ No newline at end of file'. This
-                // is not a hunk line, but the parser does not have a more accurate state corresponding
-                // to this.
+                // This is not a hunk line, but the parser does not have a more accurate state
+                // corresponding to this.
                 self.painter.paint_buffered_minus_and_plus_lines();
-                self.painter
-                    .output_buffer
-                    .push_str(&tabs::expand(&self.raw_line, &self.config.tab_cfg));
+                self.painter.emitter.emit_ansi_text(
+                    &tabs::expand(&self.raw_line, &self.config.tab_cfg),
+                    &mut self.painter.output_buffer,
+                );
                 self.painter.output_buffer.push('\n');
                 State::HunkZero(Unified, None)
             }
@@ -132,6 +183,14 @@ impl StateMachine<'_> {
     }
 }
 
+// Return the indentation of `line` after its `n_parents` prefix characters, before tab expansion.
+fn get_indentation(line: &str, n_parents: usize) -> String {
+    line.chars()
+        .skip(n_parents)
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect()
+}
+
 // Return Some(prepared_raw_line) if delta should emit this line raw.
 fn maybe_raw_line(
     raw_line: &str,
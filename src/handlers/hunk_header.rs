@@ -18,14 +18,18 @@
 // src/hunk_header.rs:119: fn write_to_output_buffer( │
 // ───────────────────────────────────────────────────┘
 // ```
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::fmt::Write as FmtWrite;
+use std::io::Write;
 
 use super::draw;
 use crate::config::{
     Config, HunkHeaderIncludeCodeFragment, HunkHeaderIncludeFilePath, HunkHeaderIncludeLineNumber,
 };
 use crate::delta::{self, DiffType, InMergeConflict, MergeParents, State, StateMachine};
+use crate::emitter::Emitter;
+use crate::minusplus::MinusPlus;
 use crate::paint::{self, BgShouldFill, Painter, StyleSectionSpecifier};
 use crate::style::{DecorationStyle, Style};
 use lazy_static::lazy_static;
@@ -34,7 +38,7 @@ use regex::Regex;
 #[derive(Clone, Default, Debug, PartialEq, Eq)]
 pub struct ParsedHunkHeader {
     code_fragment: String,
-    line_numbers_and_hunk_lengths: Vec<(usize, usize)>,
+    pub line_numbers_and_hunk_lengths: Vec<(usize, usize)>,
 }
 
 pub enum HunkHeaderIncludeHunkLabel {
@@ -98,7 +102,7 @@ impl AmbiguousDiffMinusCounter {
     }
 }
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     #[inline]
     fn test_hunk_header_line(&self) -> bool {
         self.line.starts_with("@@") &&
@@ -154,15 +158,29 @@ impl StateMachine<'_> {
         line: &str,
         raw_line: &str,
     ) -> std::io::Result<bool> {
-        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.paint_end_of_hunk();
         self.painter.set_highlighter();
         self.painter.emit()?;
+        if !self.config.color_only {
+            self.painter.write_hunk_separator()?;
+        }
 
         let ParsedHunkHeader {
             code_fragment,
             line_numbers_and_hunk_lengths,
         } = parsed_hunk_header;
 
+        if let (Some((minus_number, _)), Some((plus_number, _))) = (
+            line_numbers_and_hunk_lengths.first(),
+            line_numbers_and_hunk_lengths.last(),
+        ) {
+            self.painter.hunk_line_numbers = MinusPlus::new(*minus_number, *plus_number);
+        }
+        if self.painter.emphasis_records.is_some() {
+            self.painter.hunk_file_paths =
+                MinusPlus::new(self.minus_file.clone(), self.plus_file.clone());
+        }
+
         if self.config.line_numbers {
             self.painter
                 .line_numbers_data
@@ -205,6 +223,8 @@ impl StateMachine<'_> {
             )?;
         };
         self.painter.set_highlighter();
+        self.painter.hunk_size = Some((0, 0));
+        self.painter.zebra_stripe = false;
         Ok(true)
     }
 }
@@ -232,7 +252,7 @@ lazy_static! {
 /// Given input like
 /// "@@ -74,15 +74,14 @@ pub fn delta("
 /// Return " pub fn delta(" and a vector of (line_number, hunk_length) tuples.
-fn parse_hunk_header(line: &str) -> Option<ParsedHunkHeader> {
+pub fn parse_hunk_header(line: &str) -> Option<ParsedHunkHeader> {
     if let Some(caps) = HUNK_HEADER_REGEX.captures(line) {
         let file_coordinates = &caps[1];
         let line_numbers_and_hunk_lengths = HUNK_HEADER_FILE_COORDINATE_REGEX
@@ -259,35 +279,77 @@ fn parse_hunk_header(line: &str) -> Option<ParsedHunkHeader> {
     }
 }
 
-fn write_hunk_header_raw(
-    painter: &mut Painter,
+fn write_hunk_header_raw<E: Emitter>(
+    painter: &mut Painter<E>,
     line: &str,
     raw_line: &str,
     config: &Config,
 ) -> std::io::Result<()> {
+    let (line, raw_line) = match &config.hunk_header_marker {
+        Some(marker) if !config.color_only => (
+            Cow::from(replace_hunk_header_markers(line, marker)),
+            Cow::from(replace_hunk_header_markers(raw_line, marker)),
+        ),
+        _ => (Cow::from(line), Cow::from(raw_line)),
+    };
     let (mut draw_fn, pad, decoration_ansi_term_style) =
         draw::get_draw_function(config.hunk_header_style.decoration_style);
     if config.hunk_header_style.decoration_style != DecorationStyle::NoDecoration {
         writeln!(painter.writer)?;
     }
-    draw_fn(
-        painter.writer,
+    let (text, text_width) = draw::paint_text(
+        &painter.emitter,
         &format!("{}{}", line, if pad { " " } else { "" }),
         &format!("{}{}", raw_line, if pad { " " } else { "" }),
         "",
-        &config.decorations_width,
         config.hunk_header_style,
+    );
+    draw_fn(
+        &mut painter.writer,
+        &painter.emitter,
+        &text,
+        text_width,
+        &config.decorations_width,
         decoration_ansi_term_style,
     )?;
     Ok(())
 }
 
+/// Replace the runs of '@' before and after the line numbers of a (possibly colored) hunk header
+/// with `marker`. If `marker` is empty, the space separating each run from the line numbers is
+/// removed as well.
+fn replace_hunk_header_markers(line: &str, marker: &str) -> String {
+    let mut replaced = String::with_capacity(line.len());
+    let mut rest = line;
+    for is_opening in [true, false] {
+        let Some(start) = rest.find('@') else {
+            break;
+        };
+        let end = rest[start..]
+            .find(|c| c != '@')
+            .map_or(rest.len(), |len| start + len);
+        let (mut before, mut after) = (&rest[..start], &rest[end..]);
+        if marker.is_empty() {
+            if is_opening {
+                after = after.strip_prefix(' ').unwrap_or(after);
+            } else {
+                before = before.strip_suffix(' ').unwrap_or(before);
+            }
+        }
+        replaced.push_str(before);
+        replaced.push_str(marker);
+        rest = after;
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
 #[allow(clippy::too_many_arguments)]
-pub fn write_line_of_code_with_optional_path_and_line_number(
+pub fn write_line_of_code_with_optional_path_and_line_number<E: Emitter>(
     code_fragment: &str,
     line_numbers_and_hunk_lengths: &[(usize, usize)],
     style_sections: Option<StyleSectionSpecifier>,
-    painter: &mut Painter,
+    painter: &mut Painter<E>,
     line: &str,
     plus_file: &str,
     decoration_style: DecorationStyle,
@@ -311,15 +373,15 @@ pub fn write_line_of_code_with_optional_path_and_line_number(
         "".to_string()
     };
 
-    let plus_line_number = line_numbers_and_hunk_lengths[line_numbers_and_hunk_lengths.len() - 1].0;
     let file_with_line_number = paint_file_path_with_line_number(
-        Some(plus_line_number),
+        line_numbers_and_hunk_lengths,
         plus_file,
         file_style,
         line_number_style,
         include_file_path,
         include_line_number,
         file_path_separator,
+        &painter.emitter,
         config,
     );
 
@@ -334,12 +396,11 @@ pub fn write_line_of_code_with_optional_path_and_line_number(
             config,
         );
         draw_fn(
-            painter.writer,
-            &painter.output_buffer,
+            &mut painter.writer,
+            &painter.emitter,
             &painter.output_buffer,
-            "",
+            painter.emitter.text_width(&painter.output_buffer),
             &config.decorations_width,
-            config.null_style,
             decoration_ansi_term_style,
         )?;
         painter.output_buffer.clear();
@@ -349,22 +410,22 @@ pub fn write_line_of_code_with_optional_path_and_line_number(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn paint_file_path_with_line_number(
-    line_number: Option<usize>,
+fn paint_file_path_with_line_number<E: Emitter>(
+    line_numbers_and_hunk_lengths: &[(usize, usize)],
     plus_file: &str,
     file_style: &Style,
     line_number_style: &Style,
     include_file_path: &HunkHeaderIncludeFilePath,
     include_line_number: &HunkHeaderIncludeLineNumber,
     separator: &str,
+    emitter: &E,
     config: &Config,
 ) -> String {
     let file_style = match include_file_path {
         HunkHeaderIncludeFilePath::Yes => Some(*file_style),
         HunkHeaderIncludeFilePath::No => None,
     };
-    let line_number_style = if matches!(include_line_number, HunkHeaderIncludeLineNumber::Yes)
-        && line_number.is_some()
+    let line_number_style = if !matches!(include_line_number, HunkHeaderIncludeLineNumber::No)
         && !config.hunk_header_style.is_raw
         && !config.color_only
     {
@@ -373,35 +434,87 @@ fn paint_file_path_with_line_number(
         None
     };
 
+    if let (HunkHeaderIncludeLineNumber::LineRanges, Some(line_number_style)) =
+        (include_line_number, line_number_style)
+    {
+        let mut file_with_line_ranges = paint::paint_file_path_with_line_number(
+            None, plus_file, false, separator, false, file_style, None, emitter, config,
+        );
+        if !file_with_line_ranges.is_empty() {
+            file_with_line_ranges.push_str(&emitter.paint(ansi_term::Style::new(), separator));
+        }
+        let line_ranges = format_line_ranges(line_numbers_and_hunk_lengths);
+        file_with_line_ranges
+            .push_str(&emitter.paint(line_number_style.ansi_term_style, &line_ranges));
+        return file_with_line_ranges;
+    }
+
+    let plus_line_number = line_numbers_and_hunk_lengths.last().map(|(n, _)| *n);
     paint::paint_file_path_with_line_number(
-        line_number,
+        plus_line_number,
         plus_file,
         false,
         separator,
         false,
         file_style,
         line_number_style,
+        emitter,
         config,
     )
 }
 
-fn write_to_output_buffer(
+/// Format the ranges of lines covered by a hunk in the (first) minus file and the plus file, e.g.
+/// "lines 10–24 → 10–27" for "@@ -10,15 +10,18 @@".
+fn format_line_ranges(line_numbers_and_hunk_lengths: &[(usize, usize)]) -> String {
+    let format_range = |(start, length): (usize, usize)| match length {
+        1 => start.to_string(),
+        _ => format!("{}–{}", start, start + length - 1),
+    };
+    let format_labeled_range = |range: (usize, usize)| match range.1 {
+        0 => "no lines".to_string(),
+        1 => format!("line {}", format_range(range)),
+        _ => format!("lines {}", format_range(range)),
+    };
+    match (
+        line_numbers_and_hunk_lengths.first().copied(),
+        line_numbers_and_hunk_lengths.last().copied(),
+    ) {
+        (Some(minus), Some(plus)) if minus.1 > 0 && plus.1 > 0 => {
+            let label = if minus.1 == 1 && plus.1 == 1 {
+                "line"
+            } else {
+                "lines"
+            };
+            format!("{label} {} → {}", format_range(minus), format_range(plus))
+        }
+        (Some(minus), Some(plus)) => {
+            format!(
+                "{} → {}",
+                format_labeled_range(minus),
+                format_labeled_range(plus)
+            )
+        }
+        _ => String::new(),
+    }
+}
+
+fn write_to_output_buffer<E: Emitter>(
     file_with_line_number: &str,
     file_path_separator: &str,
     line: String,
     style_sections: Option<StyleSectionSpecifier>,
     include_hunk_label: &HunkHeaderIncludeHunkLabel,
-    painter: &mut Painter,
+    painter: &mut Painter<E>,
     config: &Config,
 ) {
     if matches!(include_hunk_label, HunkHeaderIncludeHunkLabel::Yes)
         && !config.hunk_label.is_empty()
     {
-        let _ = write!(
-            &mut painter.output_buffer,
-            "{} ",
-            config.hunk_header_file_style.paint(&config.hunk_label)
+        let hunk_label = painter.emitter.paint(
+            config.hunk_header_file_style.ansi_term_style,
+            &config.hunk_label,
         );
+        let _ = write!(&mut painter.output_buffer, "{hunk_label} ");
     }
     if !file_with_line_number.is_empty() {
         // The code fragment in "line" adds whitespace, but if only a line number is printed
@@ -409,7 +522,11 @@ fn write_to_output_buffer(
         let space = if line.is_empty() { " " } else { "" };
         let _ = write!(
             &mut painter.output_buffer,
-            "{file_with_line_number}{file_path_separator}{space}",
+            "{file_with_line_number}{}",
+            painter.emitter.paint(
+                ansi_term::Style::new(),
+                &format!("{file_path_separator}{space}")
+            ),
         );
     }
     if !line.is_empty() {
@@ -432,8 +549,30 @@ fn write_to_output_buffer(
 pub mod tests {
     use super::*;
     use crate::ansi::strip_ansi_codes;
+    use crate::emitter::AnsiEmitter;
     use crate::tests::integration_test_utils;
 
+    #[test]
+    fn test_replace_hunk_header_markers() {
+        let line = "@@ -10,15 +10,18 @@ def f(@decorated):";
+        assert_eq!(
+            replace_hunk_header_markers(line, "§"),
+            "§ -10,15 +10,18 § def f(@decorated):"
+        );
+        assert_eq!(
+            replace_hunk_header_markers(line, ""),
+            "-10,15 +10,18 def f(@decorated):"
+        );
+        assert_eq!(
+            replace_hunk_header_markers("@@@ -1 -1 +1,2 @@@", ""),
+            "-1 -1 +1,2"
+        );
+        assert_eq!(
+            replace_hunk_header_markers("\x1b[36m@@ -1 +1 @@\x1b[m f", "§"),
+            "\x1b[36m§ -1 +1 §\x1b[m f"
+        );
+    }
+
     #[test]
     fn test_parse_hunk_header() {
         let ParsedHunkHeader {
@@ -510,13 +649,14 @@ pub mod tests {
         let config = integration_test_utils::make_config_from_args(&[]);
 
         let result = paint_file_path_with_line_number(
-            Some(3),
+            &[(3, 1)],
             "some-file",
             &config.hunk_header_style,
             &config.hunk_header_line_number_style,
             &config.hunk_header_style_include_file_path,
             &config.hunk_header_style_include_line_number,
             ":",
+            &AnsiEmitter,
             &config,
         );
 
@@ -540,13 +680,14 @@ pub mod tests {
         let relative_path = PathBuf::from_iter(["some-dir", "some-file"]);
 
         let result = paint_file_path_with_line_number(
-            Some(3),
+            &[(3, 1)],
             &relative_path.to_string_lossy(),
             &config.hunk_header_style,
             &config.hunk_header_line_number_style,
             &config.hunk_header_style_include_file_path,
             &config.hunk_header_style_include_line_number,
             ":",
+            &AnsiEmitter,
             &config,
         );
 
@@ -573,13 +714,14 @@ pub mod tests {
         ]);
 
         let result = paint_file_path_with_line_number(
-            Some(3),
+            &[(3, 1)],
             "some-file",
             &config.hunk_header_style,
             &config.hunk_header_line_number_style,
             &config.hunk_header_style_include_file_path,
             &config.hunk_header_style_include_line_number,
             ":",
+            &AnsiEmitter,
             &config,
         );
 
@@ -604,13 +746,14 @@ pub mod tests {
         ]);
 
         let result = paint_file_path_with_line_number(
-            Some(3),
+            &[(3, 1)],
             "some-file",
             &config.hunk_header_style,
             &config.hunk_header_line_number_style,
             &config.hunk_header_style_include_file_path,
             &config.hunk_header_style_include_line_number,
             ":",
+            &AnsiEmitter,
             &config,
         );
 
@@ -628,13 +771,14 @@ pub mod tests {
         ]);
 
         let result = paint_file_path_with_line_number(
-            Some(3),
+            &[(3, 1)],
             "δ some-file",
             &config.hunk_header_style,
             &config.hunk_header_line_number_style,
             &config.hunk_header_style_include_file_path,
             &config.hunk_header_style_include_line_number,
             ":",
+            &AnsiEmitter,
             &config,
         );
 
@@ -652,6 +796,95 @@ pub mod tests {
         assert!(output.contains("@@@2021-12-05"));
     }
 
+    #[test]
+    fn test_hunk_size_annotation() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--hunk-size-format",
+            "(+{added}/-{removed})",
+        ]);
+        let output = integration_test_utils::run_delta(GIT_DIFF_WITH_TWO_HUNKS, &config);
+        let lines: Vec<_> = strip_ansi_codes(&output)
+            .lines()
+            .map(str::to_string)
+            .collect();
+        let annotations: Vec<_> = lines.iter().filter(|l| l.starts_with("(+")).collect();
+        assert_eq!(annotations, ["(+2/-1)", "(+0/-1)"]);
+        // Each annotation follows the last line of its hunk.
+        let i = lines.iter().position(|l| l == "(+2/-1)").unwrap();
+        assert_eq!(lines[i - 1], "    x = 3");
+        assert_eq!(lines.last().unwrap(), "(+0/-1)");
+
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let output = integration_test_utils::run_delta(GIT_DIFF_WITH_TWO_HUNKS, &config);
+        assert!(!strip_ansi_codes(&output).contains("(+"));
+    }
+
+    #[test]
+    fn test_format_line_ranges() {
+        assert_eq!(
+            format_line_ranges(&[(10, 15), (10, 18)]),
+            "lines 10–24 → 10–27"
+        );
+        assert_eq!(format_line_ranges(&[(5, 1), (5, 1)]), "line 5 → 5");
+        assert_eq!(format_line_ranges(&[(5, 1), (5, 2)]), "lines 5 → 5–6");
+        assert_eq!(
+            format_line_ranges(&[(0, 0), (1, 3)]),
+            "no lines → lines 1–3"
+        );
+        assert_eq!(format_line_ranges(&[(1, 1), (0, 0)]), "line 1 → no lines");
+        assert_eq!(
+            format_line_ranges(&[(293, 11), (358, 15), (358, 16)]),
+            "lines 293–303 → 358–373"
+        );
+    }
+
+    #[test]
+    fn test_hunk_header_line_ranges() {
+        let config = integration_test_utils::make_config_from_args(&["--hunk-header-line-ranges"]);
+        let output = integration_test_utils::run_delta(GIT_DIFF_WITH_SINGLE_LINE_HUNK, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(output.contains("\nlines 10–24 → 10–27: fn f() {"));
+        assert!(output.contains("\nline 30 → 33: "));
+
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let output = integration_test_utils::run_delta(GIT_DIFF_WITH_SINGLE_LINE_HUNK, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(output.contains("\n10: fn f() {"));
+        assert!(output.contains("\n33: "));
+    }
+
+    const GIT_DIFF_WITH_SINGLE_LINE_HUNK: &str = "\
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -10,15 +10,18 @@ fn f() {
+-a
++b
+@@ -30 +33 @@
+-c
++d
+";
+
+    const GIT_DIFF_WITH_TWO_HUNKS: &str = "\
+diff --git a/a.py b/a.py
+index 1111111..2222222 100644
+--- a/a.py
++++ b/a.py
+@@ -1,2 +1,3 @@ def f():
+-    x = 1
++    x = 2
++    y = 2
+     x = 3
+diff --git a/b.py b/b.py
+index 3333333..4444444 100644
+--- a/b.py
++++ b/b.py
+@@ -1,2 +1,1 @@
+-z = 1
+ z = 2
+";
+
     const GIT_LOG_OUTPUT_WITH_NOT_A_HUNK_HEADER: &str = "\
 @@@2021-12-05
 
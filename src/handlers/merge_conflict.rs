@@ -1,3 +1,4 @@
+use std::io::Write;
 use std::ops::{Index, IndexMut};
 
 use itertools::Itertools;
@@ -7,8 +8,9 @@ use super::draw;
 use crate::cli;
 use crate::config::{self, delta_unreachable};
 use crate::delta::{DiffType, InMergeConflict, MergeParents, State, StateMachine};
+use crate::emitter::Emitter;
 use crate::minusplus::MinusPlus;
-use crate::paint::{self, prepare};
+use crate::paint::{self, prepare, LineInfo};
 use crate::style::Style;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -24,11 +26,11 @@ pub struct MergeConflictCommits<T> {
     theirs: T,
 }
 
-pub type MergeConflictLines = MergeConflictCommits<Vec<(String, State)>>;
+pub type MergeConflictLines = MergeConflictCommits<Vec<(String, State, LineInfo)>>;
 
 pub type MergeConflictCommitNames = MergeConflictCommits<Option<String>>;
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     pub fn handle_merge_conflict_line(&mut self) -> std::io::Result<bool> {
         use DiffType::*;
         use MergeConflictCommit::*;
@@ -122,7 +124,7 @@ impl StateMachine<'_> {
         use State::*;
         if let HunkMinus(diff_type, _) | HunkZero(diff_type, _) | HunkPlus(diff_type, _) = &state {
             let line = prepare(&self.line, diff_type.n_parents(), self.config);
-            self.painter.merge_conflict_lines[commit].push((line, state));
+            self.painter.merge_conflict_lines[commit].push((line, state, LineInfo::default()));
             true
         } else {
             delta_unreachable(&format!("Invalid state: {state:?}"))
@@ -160,7 +162,10 @@ impl StateMachine<'_> {
                 ),
                 &mut self.painter.line_numbers_data,
                 &mut self.painter.highlighter,
+                self.painter.syntax,
+                None,
                 &mut self.painter.output_buffer,
+                &self.painter.emitter,
                 self.config,
             );
             self.painter.emit()?;
@@ -172,15 +177,16 @@ impl StateMachine<'_> {
             self.config,
         )?;
         self.painter.merge_conflict_lines.clear();
+        self.painter.merge_conflict_commit_names.clear();
         self.state = HunkZero(Combined(merge_parents.clone(), InMergeConflict::No), None);
         Ok(())
     }
 }
 
-fn write_diff_header(
+fn write_diff_header<E: Emitter>(
     derived_commit_type: &MergeConflictCommit,
     style: Style,
-    painter: &mut paint::Painter,
+    painter: &mut paint::Painter<E>,
     config: &config::Config,
 ) -> std::io::Result<()> {
     let (mut draw_fn, pad, decoration_ansi_term_style) =
@@ -194,33 +200,38 @@ fn write_diff_header(
             if pad { " " } else { "" }
         )
     } else {
-        derived_commit_name.as_deref().unwrap_or("?").to_string()
+        format!(
+            "{}{}",
+            derived_commit_name.as_deref().unwrap_or("?"),
+            if pad { " " } else { "" }
+        )
     };
+    let (text, text_width) = draw::paint_text(&painter.emitter, &text, &text, "", style);
     draw_fn(
-        painter.writer,
-        &text,
+        &mut painter.writer,
+        &painter.emitter,
         &text,
-        "",
+        text_width,
         &config.decorations_width,
-        style,
         decoration_ansi_term_style,
     )?;
     Ok(())
 }
 
-fn write_merge_conflict_bar(
+fn write_merge_conflict_bar<E: Emitter>(
     s: &str,
-    painter: &mut paint::Painter,
+    painter: &mut paint::Painter<E>,
     config: &config::Config,
 ) -> std::io::Result<()> {
     let width = match config.decorations_width {
         cli::Width::Fixed(width) => width,
         cli::Width::Variable => config.available_terminal_width,
     };
+    let bar = s.graphemes(true).cycle().take(width).join("");
     writeln!(
-        painter.writer,
+        &mut painter.writer,
         "{}",
-        &s.graphemes(true).cycle().take(width).join("")
+        painter.emitter.paint(ansi_term::Style::new(), &bar)
     )?;
     Ok(())
 }
@@ -297,6 +308,12 @@ impl MergeConflictCommitNames {
             theirs: None,
         }
     }
+
+    fn clear(&mut self) {
+        self[Ours] = None;
+        self[Ancestral] = None;
+        self[Theirs] = None;
+    }
 }
 
 #[cfg(test)]
@@ -353,6 +370,37 @@ ancestor ⟶   HEAD │
         assert!(output.contains("\n▲▲▲▲▲▲▲▲▲▲▲▲▲▲▲▲▲▲"));
     }
 
+    #[test]
+    fn test_three_way_merge_conflict_followed_by_two_way_merge_conflict() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let output = integration_test_utils::run_delta(
+            GIT_TOY_THREE_WAY_AND_TWO_WAY_MERGE_CONFLICTS,
+            &config,
+        );
+        let output = strip_ansi_codes(&output);
+        for marker in ["<<<<<<<", "|||||||", "=======", ">>>>>>>"] {
+            assert!(!output.contains(marker));
+        }
+        assert_eq!(output.matches("\n▼▼▼▼▼▼▼▼▼▼▼▼▼▼▼▼▼▼").count(), 2);
+        assert_eq!(output.matches("ancestor ⟶   HEAD │").count(), 1);
+        assert_eq!(output.matches("ancestor ⟶   0c20c9d... wip │").count(), 1);
+        // The second conflict has no ancestral section.
+        assert!(output.contains(
+            "\
+─────┐
+HEAD │
+─────┘
+"
+        ));
+        assert!(output.contains(
+            "\
+───────────────┐
+0c20c9d... wip │
+───────────────┘
+"
+        ));
+    }
+
     const GIT_TOY_MERGE_CONFLICT_NO_CONTEXT: &str = "\
 diff --cc file
 index 6178079,7898192..0000000
@@ -365,6 +413,26 @@ index 6178079,7898192..0000000
 ++=======
 + b
 ++>>>>>>> 0c20c9d... wip
+";
+
+    const GIT_TOY_THREE_WAY_AND_TWO_WAY_MERGE_CONFLICTS: &str = "\
+diff --cc file
+index 6178079,7898192..0000000
+--- a/file
++++ b/file
+@@@ -1,3 -1,3 +1,12 @@@
+++<<<<<<< HEAD
+ +a
+++||||||| parent of 0c20c9d... wip
+++=======
++ b
+++>>>>>>> 0c20c9d... wip
+  c
+++<<<<<<< HEAD
+ +d
+++=======
++ e
+++>>>>>>> 0c20c9d... wip
 ";
 
     const GIT_MERGE_CONFLICT: &str = r#"\
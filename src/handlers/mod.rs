@@ -16,8 +16,9 @@ mod ripgrep_json;
 pub mod submodule;
 
 use crate::delta::{State, StateMachine};
+use crate::emitter::Emitter;
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     pub fn handle_additional_cases(&mut self, to_state: State) -> std::io::Result<bool> {
         let mut handled_line = false;
 
@@ -36,7 +37,7 @@ impl StateMachine<'_> {
         // See https://github.com/dandavison/delta/issues/60#issuecomment-557485242 for a
         // proposal for more robust parsing logic.
 
-        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.paint_end_of_hunk();
         self.state = to_state;
         if self.should_handle() {
             self.painter.emit()?;
@@ -8,7 +8,7 @@ use crate::handlers::grep;
 use serde::Deserialize;
 use serde_json::Value;
 
-pub fn parse_line(line: &str) -> Option<grep::GrepLine> {
+pub fn parse_line(line: &str) -> Option<grep::GrepLine<'_>> {
     let ripgrep_line: Option<RipGrepLine> = serde_json::from_str(line).ok();
     match ripgrep_line {
         Some(ripgrep_line) => {
@@ -25,6 +25,7 @@ pub fn parse_line(line: &str) -> Option<grep::GrepLine> {
                 grep_type: crate::config::GrepType::Ripgrep,
                 line_type: ripgrep_line._type,
                 line_number: ripgrep_line.data.line_number,
+                column: None,
                 path: Cow::from(ripgrep_line.data.path.text),
                 code: Cow::from(code),
                 submatches: Some(
@@ -48,6 +49,7 @@ pub fn parse_line(line: &str) -> Option<grep::GrepLine> {
                         grep_type: crate::config::GrepType::Ripgrep,
                         line_type: grep::LineType::Ignore,
                         line_number: None,
+                        column: None,
                         path: "".into(),
                         code: "".into(),
                         submatches: None,
@@ -1,9 +1,12 @@
+use std::io::Write;
+
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::delta::{State, StateMachine};
+use crate::emitter::Emitter;
 
-impl StateMachine<'_> {
+impl<E: Emitter> StateMachine<'_, E> {
     #[inline]
     fn test_submodule_log(&self) -> bool {
         self.line.starts_with("Submodule ")
@@ -36,12 +39,14 @@ impl StateMachine<'_> {
                 writeln!(
                     self.painter.writer,
                     "{}..{}",
-                    self.config
-                        .minus_style
-                        .paint(minus_commit.chars().take(12).collect::<String>()),
-                    self.config
-                        .plus_style
-                        .paint(commit.chars().take(12).collect::<String>()),
+                    self.painter.emitter.paint(
+                        self.config.minus_style.ansi_term_style,
+                        &minus_commit.chars().take(12).collect::<String>()
+                    ),
+                    self.painter.emitter.paint(
+                        self.config.plus_style.ansi_term_style,
+                        &commit.chars().take(12).collect::<String>()
+                    ),
                 )?;
             }
         }
@@ -5,17 +5,22 @@ mod color;
 mod colors;
 mod config;
 mod delta;
+mod diff_structure;
 mod edits;
+mod emitter;
 mod env;
 mod features;
+mod file_order;
 mod format;
 mod git_config;
 mod handlers;
 mod minusplus;
+mod moved_lines;
 mod options;
 mod paint;
 mod parse_style;
 mod parse_styles;
+mod scrollback;
 mod style;
 mod utils;
 mod wrapping;
@@ -28,11 +33,9 @@ use std::ffi::{OsStr, OsString};
 use std::io::{self, BufRead, Cursor, ErrorKind, IsTerminal, Write};
 use std::process::{self, Command, Stdio};
 
-use bytelines::ByteLinesReader;
-
 use crate::cli::Call;
 use crate::config::delta_unreachable;
-use crate::delta::delta;
+use crate::delta::delta_from_reader;
 use crate::subcommands::{SubCmdKind, SubCommand};
 use crate::utils::bat::assets::list_languages;
 use crate::utils::bat::output::{OutputType, PagingMode};
@@ -123,6 +126,8 @@ pub fn run_app(
         Some(subcommands::show_colors::show_colors())
     } else if opt.parse_ansi {
         Some(subcommands::parse_ansi::parse_ansi())
+    } else if opt.parse_structure {
+        Some(subcommands::parse_structure::parse_structure())
     } else {
         None
     };
@@ -137,6 +142,7 @@ pub fn run_app(
     };
 
     let _show_config = opt.show_config;
+    let _preview_styles = opt.preview_styles;
     let config = config::Config::from(opt);
 
     if _show_config {
@@ -146,6 +152,12 @@ pub fn run_app(
         return Ok(0);
     }
 
+    if _preview_styles {
+        let preview = subcommands::preview_styles::preview_styles(&config)?;
+        io::stdout().lock().write_all(preview.as_bytes())?;
+        return Ok(0);
+    }
+
     // The following block structure is because of `writer` and related lifetimes:
     let pager_cfg = (&config).into();
     let paging_mode = if capture_output.is_some() {
@@ -186,7 +198,7 @@ pub fn run_app(
             return Ok(config.error_exit_code);
         }
 
-        let res = delta(io::stdin().lock().byte_lines(), &mut writer, &config);
+        let res = delta_from_reader(io::stdin().lock(), &mut writer, &config);
 
         if let Err(error) = res {
             match error.kind() {
@@ -232,7 +244,7 @@ pub fn run_app(
             .unwrap_or_else(|| panic!("Failed to open stdout"));
         let cmd_stdout_buf = io::BufReader::new(cmd_stdout);
 
-        let res = delta(cmd_stdout_buf.byte_lines(), &mut writer, &config);
+        let res = delta_from_reader(cmd_stdout_buf, &mut writer, &config);
 
         if let Err(error) = res {
             let _ = cmd.wait(); // for clippy::zombie_processes
@@ -0,0 +1,179 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ansi;
+use crate::handlers::hunk_header::parse_hunk_header;
+use crate::minusplus::*;
+
+/// The minimum number of lines of a moved block (see --color-moved).
+const MIN_MOVED_BLOCK_LENGTH: usize = 3;
+
+/// A run of consecutive removed or added lines of a hunk.
+struct Run {
+    side: MinusPlusIndex,
+    // The indices of the lines in the input.
+    indices: Vec<usize>,
+}
+
+/// Return the indices, in `lines`, of the removed and added lines that are moved (see
+/// --color-moved): those of a run of at least MIN_MOVED_BLOCK_LENGTH consecutive removed lines for
+/// which the same hunk has a run of added lines with the same content, and those of that run.
+pub fn find_moved_lines(lines: &[Vec<u8>]) -> HashSet<usize> {
+    let stripped_lines: Vec<String> = lines
+        .iter()
+        .map(|line| ansi::strip_ansi_codes(&String::from_utf8_lossy(line)))
+        .collect();
+    let mut moved_lines = HashSet::new();
+    let mut runs: Vec<Run> = Vec::new();
+    // The numbers of removed and added lines of the current hunk that are still to be read.
+    let mut n_remaining = MinusPlus::new(0_usize, 0);
+    for (i, line) in stripped_lines.iter().enumerate() {
+        let side = match line.as_bytes().first() {
+            _ if n_remaining[Minus] == 0 && n_remaining[Plus] == 0 => None,
+            Some(b'-') if n_remaining[Minus] > 0 => Some(Minus),
+            Some(b'+') if n_remaining[Plus] > 0 => Some(Plus),
+            // A '\ No newline at end of file' marker neither ends nor extends a run.
+            Some(b'\\') => continue,
+            _ => {
+                n_remaining[Minus] = n_remaining[Minus].saturating_sub(1);
+                n_remaining[Plus] = n_remaining[Plus].saturating_sub(1);
+                None
+            }
+        };
+        match side {
+            Some(side) => {
+                n_remaining[side] -= 1;
+                match runs.last_mut() {
+                    Some(run) if run.side == side && run.indices.last() == Some(&(i - 1)) => {
+                        run.indices.push(i)
+                    }
+                    _ => runs.push(Run {
+                        side,
+                        indices: vec![i],
+                    }),
+                }
+            }
+            None if n_remaining[Minus] == 0 && n_remaining[Plus] == 0 => {
+                mark_moved_runs(&runs, &stripped_lines, &mut moved_lines);
+                runs.clear();
+                // Hunks of merge commits, with more than two coordinates, are not considered.
+                if let Some(hunk_header) = parse_hunk_header(line) {
+                    if let [(_, n_minus), (_, n_plus)] =
+                        hunk_header.line_numbers_and_hunk_lengths[..]
+                    {
+                        n_remaining = MinusPlus::new(n_minus, n_plus);
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+    mark_moved_runs(&runs, &stripped_lines, &mut moved_lines);
+    moved_lines
+}
+
+/// Add to `moved_lines` the lines of the runs of removed lines of a hunk that have the same
+/// content as one of its runs of added lines, and the lines of that run.
+fn mark_moved_runs(runs: &[Run], lines: &[String], moved_lines: &mut HashSet<usize>) {
+    let content =
+        |run: &Run| -> Vec<&str> { run.indices.iter().map(|&i| &lines[i][1..]).collect() };
+    let mut plus_runs: HashMap<Vec<&str>, Vec<&Run>> = HashMap::new();
+    for run in runs {
+        if run.side == Plus && run.indices.len() >= MIN_MOVED_BLOCK_LENGTH {
+            plus_runs.entry(content(run)).or_default().push(run);
+        }
+    }
+    for minus_run in runs.iter().filter(|run| run.side == Minus) {
+        let Some(plus_runs) = plus_runs.get_mut(&content(minus_run)) else {
+            continue;
+        };
+        // Each run of added lines is the destination of one run of removed lines.
+        if let Some(plus_run) = plus_runs.pop() {
+            moved_lines.extend(&minus_run.indices);
+            moved_lines.extend(&plus_run.indices);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moved_lines(input: &str) -> Vec<usize> {
+        let lines: Vec<Vec<u8>> = input.lines().map(|line| line.as_bytes().to_vec()).collect();
+        let mut moved_lines: Vec<usize> = find_moved_lines(&lines).into_iter().collect();
+        moved_lines.sort_unstable();
+        moved_lines
+    }
+
+    #[test]
+    fn test_find_moved_lines() {
+        assert_eq!(
+            moved_lines(
+                "\
+diff --git a/a.rs b/a.rs
+--- a/a.rs
++++ b/a.rs
+@@ -1,6 +1,6 @@
+-a
+-b
+-c
+ x
+ y
++a
++b
++c
+ z
+"
+            ),
+            [4, 5, 6, 9, 10, 11]
+        );
+    }
+
+    #[test]
+    fn test_find_moved_lines_requires_same_run() {
+        assert_eq!(
+            moved_lines(
+                "\
+@@ -1,5 +1,6 @@
+-a
+-b
+-c
+ x
++a
++b
++c
++d
+ y
+@@ -10,3 +11,3 @@
+-e
+-f
+ z
++e
++f
+"
+            ),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn test_find_moved_lines_within_hunk() {
+        assert_eq!(
+            moved_lines(
+                "\
+@@ -1,4 +1,1 @@
+-a
+-b
+---
+ x
+@@ -10,1 +7,4 @@
+ y
++a
++b
++--
+"
+            ),
+            Vec::<usize>::new()
+        );
+    }
+}
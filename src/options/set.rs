@@ -78,6 +78,10 @@ pub fn set_options(
         }
     }
     opt.navigate = opt.navigate || opt.env.navigate.is_some();
+    // NO_COLOR changes the default only: --color, including in git config, takes precedence.
+    if opt.env.no_color.is_some() && !config::user_supplied_option("color", arg_matches) {
+        opt.color = "never".to_string();
+    }
     if opt.syntax_theme.is_none() {
         opt.syntax_theme.clone_from(&opt.env.bat_theme);
     }
@@ -127,6 +131,8 @@ pub fn set_options(
 
     set_options!(
         [
+            added_line_label,
+            auto_emph_colors,
             blame_code_style,
             blame_format,
             blame_separator_format,
@@ -134,24 +140,49 @@ pub fn set_options(
             blame_separator_style,
             blame_timestamp_format,
             blame_timestamp_output_format,
+            changed_line_label,
+            color,
+            color_moved,
             color_only,
+            collapse_leading_context,
+            comment_emphasis,
+            completion_notification,
             config,
+            context_line_label,
             commit_decoration_style,
             commit_regex,
             commit_style,
             default_language,
+            density_column,
+            density_column_shades,
+            density_column_window,
             diff_args,
             diff_stat_align_width,
+            diff_stat_colors,
+            emph_gradient,
+            emph_merge_gap,
+            emph_token_regex,
+            emphasis_algorithm,
+            emphasis_records_file,
+            emphasize_additions_only,
+            empty_file_style,
             file_added_label,
             file_copied_label,
             file_decoration_style,
             file_modified_label,
+            file_order,
             file_removed_label,
             file_renamed_label,
+            file_separator,
+            file_separator_style,
             file_regex_replacement,
             right_arrow,
             hunk_label,
+            hunk_separator,
+            hunk_separator_style,
+            hunk_size_format,
             file_style,
+            formatting_emph_style,
             grep_context_line_style,
             grep_file_style,
             grep_header_decoration_style,
@@ -161,21 +192,40 @@ pub fn set_options(
             grep_match_line_style,
             grep_match_word_style,
             grep_separator_symbol,
+            grid_width,
+            gutter,
+            gutter_changed_style,
+            gutter_minus_style,
+            gutter_plus_style,
+            gutter_symbol,
+            gutter_zero_style,
             hunk_header_decoration_style,
             hunk_header_file_style,
             hunk_header_line_number_style,
+            hunk_header_line_ranges,
+            hunk_header_marker,
             hunk_header_style,
             hyperlinks,
             hyperlinks_commit_link_format,
             hyperlinks_file_link_format,
+            indent_guide_style,
+            indent_normalization_style,
+            inline_edits,
             inline_hint_style,
+            insertion_point_style,
             inspect_raw_lines,
             keep_plus_minus_markers,
             line_buffer_size,
+            line_ending_changes,
             map_styles,
+            map_syntax,
+            matching_bracket_style,
+            max_common_prefix_width,
             max_line_distance,
             max_line_length,
+            max_section_length,
             max_syntax_length,
+            max_syntax_scope_depth,
             // Hack: minus-style must come before minus-*emph-style because the latter default
             // dynamically to the value of the former.
             merge_conflict_begin_symbol,
@@ -184,14 +234,23 @@ pub fn set_options(
             merge_conflict_ours_diff_header_style,
             merge_conflict_theirs_diff_header_decoration_style,
             merge_conflict_theirs_diff_header_style,
+            min_terminal_width,
             minus_style,
             minus_emph_style,
             minus_empty_line_marker_style,
+            minus_marker,
             minus_non_emph_style,
             minus_non_emph_style,
+            moved_style,
             navigate,
             navigate_regex,
+            no_newline_marker_style,
+            operator_emph_style,
+            operator_tokens,
+            output_buffer_limit,
+            output_format,
             line_fill_method,
+            line_kind_labels,
             line_numbers,
             line_numbers_left_format,
             line_numbers_left_style,
@@ -202,28 +261,54 @@ pub fn set_options(
             line_numbers_zero_style,
             pager,
             paging_mode,
+            pair_swap_emph_style,
+            paired_tokens,
             parse_ansi,
+            parse_structure,
             // Hack: plus-style must come before plus-*emph-style because the latter default
             // dynamically to the value of the former.
             plus_style,
             plus_emph_style,
             plus_empty_line_marker_style,
+            plus_lines_first,
+            plus_marker,
             plus_non_emph_style,
+            preserve_missing_final_newline,
+            punctuation_emph_style,
             raw,
             relative_paths,
+            removed_line_label,
+            rename_emph_style,
+            rename_map,
+            reordered_lines_style,
+            scope_change_emph_style,
+            scrollback_lines,
+            shell_integration_marks,
             show_colors,
             show_themes,
+            show_whitespace,
             side_by_side,
+            side_by_side_ratio,
+            syntax_font_styles,
+            syntax_theme_background_fallback,
             wrap_max_lines,
             wrap_right_prefix_symbol,
             wrap_right_percent,
             wrap_right_symbol,
             wrap_left_symbol,
+            wrap_lines,
+            tab_symbol,
             tab_width,
             tokenization_regex,
+            trailing_comment_emph_style,
+            truncate_lines,
             true_color,
             whitespace_error_style,
+            whitespace_only_changes,
             width,
+            zebra,
+            zebra_style,
+            zero_marker,
             zero_style
         ],
         opt,
@@ -608,8 +693,18 @@ fn set_widths_and_isatty(opt: &mut cli::Opt) {
 
     // If one extra character for e.g. `less --status-column` is required use "-1"
     // as an argument, also see #41, #10, #115 and #727.
-    opt.computed.available_terminal_width =
+    let width =
         crate::utils::workarounds::windows_msys2_width_fix(term_stdout.size(), &term_stdout);
+    // Term::size() falls back to 79 columns when the width cannot be determined, a width which the
+    // MSYS2 workaround may however correct.
+    let detected_width = (term_stdout.size_checked().is_some()
+        || width != cli::TERM_FALLBACK_WIDTH)
+        .then_some(width);
+    opt.computed.available_terminal_width = if opt.grid_width > 0 {
+        opt.grid_width
+    } else {
+        available_terminal_width(detected_width, opt.min_terminal_width)
+    };
 
     let (decorations_width, background_color_extends_to_terminal_width) = match opt.width.as_deref()
     {
@@ -639,6 +734,16 @@ fn set_widths_and_isatty(opt: &mut cli::Opt) {
         background_color_extends_to_terminal_width;
 }
 
+/// Return the width of the terminal given the detected width, if detection succeeded, and the
+/// value of --min-terminal-width.
+fn available_terminal_width(detected_width: Option<usize>, min_width: usize) -> usize {
+    match detected_width {
+        Some(width) if width >= min_width => width,
+        None if min_width == 0 => cli::TERM_FALLBACK_WIDTH,
+        _ => min_width,
+    }
+}
+
 fn set_true_color(opt: &mut cli::Opt) {
     if opt.true_color == "auto" {
         // It's equal to its default, so the user might be using the deprecated
@@ -673,6 +778,8 @@ pub mod tests {
     use std::fs::remove_file;
 
     use crate::cli;
+    use crate::config;
+    use crate::env::DeltaEnv;
     use crate::tests::integration_test_utils;
     use crate::utils::bat::output::PagingMode;
 
@@ -828,6 +935,59 @@ pub mod tests {
         remove_file(git_config_path).unwrap();
     }
 
+    #[test]
+    fn test_no_color_env_var() {
+        let no_color_env = || DeltaEnv {
+            no_color: Some("1".into()),
+            ..DeltaEnv::default()
+        };
+        let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
+            no_color_env(),
+            &[],
+            None,
+            None,
+        );
+        assert_eq!(opt.color, "never");
+        let config = config::Config::from(opt);
+        let output = integration_test_utils::run_delta(ADDED_AND_REMOVED_LINES_DIFF, &config);
+        assert!(!output.contains('\x1b'), "{:?} contains escapes", output);
+
+        let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
+            no_color_env(),
+            &["--color=always"],
+            None,
+            None,
+        );
+        assert_eq!(opt.color, "always");
+
+        let git_config_contents = b"
+[delta]
+    color = always
+";
+        let git_config_path = "delta__test_no_color_env_var.gitconfig";
+        let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
+            no_color_env(),
+            &[],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        assert_eq!(opt.color, "always");
+        remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    fn test_available_terminal_width() {
+        use super::available_terminal_width;
+        // A zero width detected (e.g. in a pipe) falls back to the minimum width.
+        assert_eq!(available_terminal_width(Some(0), 60), 60);
+        assert_eq!(available_terminal_width(Some(20), 60), 60);
+        assert_eq!(available_terminal_width(None, 60), 60);
+        assert_eq!(available_terminal_width(Some(100), 60), 100);
+        // Without a minimum width, the detected width is used as is.
+        assert_eq!(available_terminal_width(Some(0), 0), 0);
+        assert_eq!(available_terminal_width(None, 0), cli::TERM_FALLBACK_WIDTH);
+    }
+
     #[test]
     fn test_parse_width_specifier() {
         use super::parse_width_specifier;
@@ -863,4 +1023,15 @@ pub mod tests {
         assert_eq!(parse_width_specifier(" - 12 ", term_width).unwrap(), 0);
         assert_eq!(parse_width_specifier(" 2 - 2 ", term_width).unwrap(), 0);
     }
+
+    const ADDED_AND_REMOVED_LINES_DIFF: &str = "\
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@ fn f() {
+ let x = 0;
+-let a = 1;
++let a = 2;
+";
 }
@@ -9,7 +9,8 @@
 //! 1. The theme is specified by the `--syntax-theme` option. If this isn't supplied then it is specified
 //!    by the `BAT_THEME` environment variable.
 //! 2. Light vs dark mode is specified by the `--light` or `--dark` options. If these aren't
-//!    supplied then it detected from the terminal. If this fails it is inferred from the chosen theme.
+//!    supplied then it detected from the terminal, or else from the `COLORFGBG` environment
+//!    variable. If this fails it is inferred from the chosen theme.
 //!
 //! In the absence of other factors, the default assumes a dark terminal background.
 
@@ -86,12 +87,26 @@ fn get_color_mode(opt: &cli::Opt) -> Option<ColorMode> {
     } else if opt.dark {
         Some(Dark)
     } else if should_detect_color_mode(opt) {
-        detect_color_mode()
+        detect_color_mode().or_else(|| color_mode_from_colorfgbg(opt))
+    } else if opt.detect_dark_light == DetectDarkLight::Auto {
+        // Unlike querying the terminal, reading COLORFGBG does not interfere with a pager.
+        color_mode_from_colorfgbg(opt)
     } else {
         None
     }
 }
 
+/// Infer the color mode from the COLORFGBG environment variable, of the form "FG;BG" or
+/// "FG;default;BG", BG being the number of an ANSI color, as set by rxvt and some other terminals.
+fn color_mode_from_colorfgbg(opt: &cli::Opt) -> Option<ColorMode> {
+    let colorfgbg = opt.env.colorfgbg.as_deref()?;
+    match colorfgbg.rsplit(';').next()?.parse::<u8>().ok()? {
+        0..=6 | 8 => Some(Dark),
+        7 | 9..=15 => Some(Light),
+        _ => None,
+    }
+}
+
 /// See [`cli::Opt::detect_dark_light`] for a detailed explanation.
 fn should_detect_color_mode(opt: &cli::Opt) -> bool {
     match opt.detect_dark_light {
@@ -126,8 +141,39 @@ fn detect_color_mode() -> Option<ColorMode> {
 mod tests {
     use super::*;
     use crate::color;
+    use crate::env::DeltaEnv;
     use crate::tests::integration_test_utils;
 
+    #[test]
+    fn test_color_mode_from_colorfgbg() {
+        for (colorfgbg, args, expected_mode) in [
+            (Some("15;0"), &[][..], Dark),
+            (Some("0;15"), &[], Light),
+            (Some("0;default;7"), &[], Light),
+            (Some("0;15"), &["--dark"], Dark),
+            (Some("0;15"), &["--detect-dark-light=never"], Dark),
+            (Some("0;15"), &["--syntax-theme=Nord"], Light),
+            (Some("0;default"), &[], Dark),
+            (Some("0;default"), &["--syntax-theme=GitHub"], Light),
+            (None, &[], Dark),
+        ] {
+            let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
+                DeltaEnv {
+                    colorfgbg: colorfgbg.map(str::to_string),
+                    ..DeltaEnv::default()
+                },
+                args,
+                None,
+                None,
+            );
+            assert_eq!(
+                opt.computed.color_mode, expected_mode,
+                "COLORFGBG={:?} {:?}",
+                colorfgbg, args
+            );
+        }
+    }
+
     // TODO: Test influence of BAT_THEME env var. E.g. see utils::process::tests::FakeParentArgs.
     #[test]
     fn test_syntax_theme_selection() {
@@ -1,32 +1,41 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::Write;
+use std::ops::Range;
 
 use ansi_term::ANSIString;
 use itertools::Itertools;
-use syntect::easy::HighlightLines;
+use regex::Regex;
 use syntect::highlighting::Style as SyntectStyle;
-use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxReference, SyntaxSet};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::config::{self, delta_unreachable, Config};
+use crate::cli;
+use crate::config::{
+    self, delta_unreachable, CollapsibleChanges, CommentEmphasis, Config, EmphasisAlgorithm,
+};
 use crate::delta::{DiffType, InMergeConflict, MergeParents, State};
+use crate::emitter::{AnsiEmitter, Emitter, EmitterWriter};
 use crate::features::hyperlinks;
 use crate::features::line_numbers::{self, LineNumbersData};
 use crate::features::side_by_side::ansifill;
 use crate::features::side_by_side::{self, PanelSide};
 use crate::handlers::merge_conflict;
 use crate::minusplus::*;
-use crate::paint::superimpose_style_sections::superimpose_style_sections;
+use crate::paint::superimpose_style_sections::{split_long_sections, superimpose_style_sections};
 use crate::style::Style;
-use crate::{ansi, style};
+use crate::utils::syntect::HighlightLines;
+use crate::{ansi, color, style};
 use crate::{edits, utils, utils::tabs};
 
 pub type LineSections<'a, S> = Vec<(S, &'a str)>;
 
-pub struct Painter<'p> {
-    pub minus_lines: Vec<(String, State)>,
-    pub plus_lines: Vec<(String, State)>,
-    pub writer: &'p mut dyn Write,
+pub struct Painter<'p, E: Emitter = AnsiEmitter> {
+    pub minus_lines: Vec<(String, State, LineInfo)>,
+    pub plus_lines: Vec<(String, State, LineInfo)>,
+    pub writer: EmitterWriter<'p, E>,
+    // The backend formatting what is painted (see --output-format).
+    pub emitter: E,
     pub syntax: &'p SyntaxReference,
     pub highlighter: Option<HighlightLines<'p>>,
     pub config: &'p config::Config,
@@ -37,6 +46,98 @@ pub struct Painter<'p> {
     pub line_numbers_data: Option<line_numbers::LineNumbersData<'p>>,
     pub merge_conflict_lines: merge_conflict::MergeConflictLines,
     pub merge_conflict_commit_names: merge_conflict::MergeConflictCommitNames,
+    // Numbers of (removed, added) lines painted so far in the current hunk.
+    pub hunk_size: Option<(usize, usize)>,
+    // Line numbers, in the minus and plus files, of the next line to be painted in the current
+    // hunk.
+    pub hunk_line_numbers: MinusPlus<usize>,
+    // Unchanged lines at the start of the first hunk of the current file, held back until the
+    // first change so that they can be collapsed (see --collapse-leading-context).
+    // Each line is held with the byte ranges of its tab symbols.
+    #[allow(clippy::type_complexity)]
+    pub leading_context: Option<Vec<(String, Vec<Range<usize>>, State)>>,
+    // If set (see --output-buffer-limit), the output buffer is emitted as soon as a painted line
+    // or block of changed lines takes its length to this many bytes, rather than when the handler
+    // of the current input line emits it, and its capacity is shrunk back to this size after each
+    // emit. Since the check is made after painting, a single block larger than this still grows
+    // the buffer beyond it.
+    pub output_buffer_limit: Option<usize>,
+    // An error writing the output buffer early (see `output_buffer_limit`), to be returned by the
+    // next call to emit().
+    deferred_write_error: Option<std::io::Error>,
+    // Whether each of the last --density-column-window painted hunk lines is a change.
+    density_window: VecDeque<bool>,
+    // Whether the next unchanged line of the current hunk is painted with zebra-style (see
+    // --zebra).
+    pub zebra_stripe: bool,
+    // Whether a file header, and a hunk header of the current file, have been written, so that
+    // the next ones are preceded by a separator (see --file-separator and --hunk-separator).
+    pub wrote_file_header: bool,
+    pub wrote_hunk_header: bool,
+    // If Some (see --emphasis-records-file), the emphasized text of each painted changed line is
+    // recorded here.
+    pub emphasis_records: Option<Vec<EmphasisRecord>>,
+    // Paths, in the minus and plus versions, of the file of the current hunk. These are only kept
+    // up to date while emphasis_records is Some.
+    pub hunk_file_paths: MinusPlus<String>,
+}
+
+/// What is known of a line buffered for painting besides its text and state, kept next to these
+/// in the line buffers of a [`Painter`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineInfo {
+    /// The indentation of the line before tab expansion.
+    pub raw_indentation: String,
+    /// The byte ranges of the tab symbols in the line (see `tabs::expand_marking_symbols`).
+    pub tab_symbols: Vec<Range<usize>>,
+    /// Whether the line is moved (see --color-moved).
+    pub is_moved: bool,
+    /// The painted "\ No newline at end of file" marker that follows the line, if any. It is
+    /// written after the block of lines of the side of the line.
+    pub no_newline_marker: Option<String>,
+}
+
+/// The text that delta emphasized in a painted removed or added line, as recorded in
+/// [`Painter::emphasis_records`]. Lines without emphasized text are not recorded, and neither
+/// are lines painted with --inline-edits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmphasisRecord {
+    /// The path of the file in the version (minus or plus) containing the line.
+    pub file: String,
+    /// The (1-based) number of the line in that version of the file.
+    pub line_number: usize,
+    pub side: MinusPlusIndex,
+    /// The character ranges of the painted line that are emphasized, in order and not adjacent.
+    /// Offsets exclude the +/- prefix, and count tabs after their expansion (see --tabs).
+    pub ranges: Vec<Range<usize>>,
+}
+
+impl EmphasisRecord {
+    /// Return the record as a JSON object, as written to the --emphasis-records-file.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "file": self.file,
+            "line_number": self.line_number,
+            "side": match self.side {
+                Minus => "minus",
+                Plus => "plus",
+            },
+            "ranges": self
+                .ranges
+                .iter()
+                .map(|range| [range.start, range.end])
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// The kind of a painted hunk line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineKind {
+    Minus,
+    Zero,
+    Plus,
+    NoNewlineMarker,
 }
 
 // How the background of a line is filled up to the end
@@ -70,56 +171,38 @@ pub enum StyleSectionSpecifier<'l> {
 
 impl<'p> Painter<'p> {
     pub fn new(writer: &'p mut dyn Write, config: &'p config::Config) -> Self {
-        let default_syntax = Self::get_syntax(&config.syntax_set, None, &config.default_language);
-        let panel_width_fix = ansifill::UseFullPanelWidth::new(config);
-
-        let line_numbers_data = if config.line_numbers {
-            Some(line_numbers::LineNumbersData::from_format_strings(
-                &config.line_numbers_format,
-                panel_width_fix,
-            ))
-        } else if config.side_by_side {
-            // If line numbers are disabled in side-by-side then the data is still used
-            // for width calculation and to pad odd width to even, see `UseFullPanelWidth`
-            // for details.
-            Some(line_numbers::LineNumbersData::empty_for_sbs(
-                panel_width_fix,
-            ))
-        } else {
-            None
-        };
-        Self {
-            minus_lines: Vec::new(),
-            plus_lines: Vec::new(),
-            output_buffer: String::new(),
-            syntax: default_syntax,
-            highlighter: None,
-            writer,
-            config,
-            line_numbers_data,
-            merge_conflict_lines: merge_conflict::MergeConflictLines::new(),
-            merge_conflict_commit_names: merge_conflict::MergeConflictCommitNames::new(),
-        }
+        Self::with_emitter(writer, config, AnsiEmitter)
     }
 
-    pub fn set_syntax(&mut self, filename: Option<&str>) {
-        self.syntax = Painter::get_syntax(
-            &self.config.syntax_set,
-            filename,
-            &self.config.default_language,
-        );
+    /// A painter whose syntax, and highlighter, are those of the file `filename`.
+    pub fn with_syntax(
+        writer: &'p mut dyn Write,
+        config: &'p config::Config,
+        filename: Option<&str>,
+    ) -> Self {
+        let mut painter = Self::new(writer, config);
+        painter.set_syntax(filename);
+        painter.set_highlighter();
+        painter
     }
 
-    fn get_syntax<'a>(
-        syntax_set: &'a SyntaxSet,
-        filename: Option<&str>,
-        fallback: &str,
-    ) -> &'a SyntaxReference {
+    fn get_syntax<'a>(config: &'a config::Config, filename: Option<&str>) -> &'a SyntaxReference {
+        let syntax_set = &config.syntax_set;
+        let fallback = &config.default_language;
         if let Some(filename) = filename {
             let path = std::path::Path::new(filename);
             let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
             let extension = path.extension().and_then(|x| x.to_str()).unwrap_or("");
 
+            // Mappings of --map-syntax take precedence.
+            if let Some(syntax) = [file_name, extension]
+                .iter()
+                .filter_map(|name| config.syntax_map.get(*name))
+                .find_map(|syntax| syntax_set.find_syntax_by_token(syntax))
+            {
+                return syntax;
+            }
+
             // Like syntect's `find_syntax_for_file`, without inspecting the file content, plus:
             // If the file has NO extension then look up the whole filename as a
             // syntax definition (if it is longer than 4 bytes).
@@ -148,188 +231,34 @@ impl<'p> Painter<'p> {
         }
     }
 
-    pub fn set_highlighter(&mut self) {
-        if let Some(ref syntax_theme) = self.config.syntax_theme {
-            self.highlighter = Some(HighlightLines::new(self.syntax, syntax_theme))
-        };
-    }
-
-    pub fn paint_buffered_minus_and_plus_lines(&mut self) {
-        if self.minus_lines.is_empty() && self.plus_lines.is_empty() {
-            return;
-        }
-        paint_minus_and_plus_lines(
-            MinusPlus::new(&self.minus_lines, &self.plus_lines),
-            &mut self.line_numbers_data,
-            &mut self.highlighter,
-            &mut self.output_buffer,
-            self.config,
-        );
-        self.minus_lines.clear();
-        self.plus_lines.clear();
-    }
-
-    pub fn paint_zero_line(&mut self, line: &str, state: State) {
-        let lines = &[(line.to_string(), state.clone())];
-        let syntax_style_sections =
-            get_syntax_style_sections_for_lines(lines, self.highlighter.as_mut(), self.config);
-        let mut diff_style_sections = vec![vec![(self.config.zero_style, lines[0].0.as_str())]]; // TODO: compute style from state
-        Painter::update_diff_style_sections(
-            lines,
-            &mut diff_style_sections,
-            None,
-            None,
-            &[false],
-            self.config,
-        );
-        if self.config.side_by_side {
-            // `lines[0].0` so the line has the '\n' already added (as in the +- case)
-            side_by_side::paint_zero_lines_side_by_side(
-                &lines[0].0,
-                syntax_style_sections,
-                diff_style_sections,
-                &mut self.output_buffer,
-                self.config,
-                &mut self.line_numbers_data.as_mut(),
-                painted_prefix(state, self.config),
-                BgShouldFill::With(BgFillMethod::Spaces),
-            );
-        } else {
-            Painter::paint_lines(
-                lines,
-                &syntax_style_sections,
-                diff_style_sections.as_slice(),
-                &[false],
-                &mut self.output_buffer,
-                self.config,
-                &mut self.line_numbers_data.as_mut(),
-                None,
-                BgShouldFill::With(BgFillMethod::Spaces),
-            );
-        }
-    }
-
-    /// Superimpose background styles and foreground syntax
-    /// highlighting styles, and write colored lines to output buffer.
-    #[allow(clippy::too_many_arguments)]
-    pub fn paint_lines<'a>(
-        lines: &'a [(String, State)],
-        syntax_style_sections: &[LineSections<'a, SyntectStyle>],
-        diff_style_sections: &[LineSections<'a, Style>],
-        lines_have_homolog: &[bool],
-        output_buffer: &mut String,
-        config: &config::Config,
-        line_numbers_data: &mut Option<&mut line_numbers::LineNumbersData>,
-        empty_line_style: Option<Style>, // a style with background color to highlight an empty line
-        background_color_extends_to_terminal_width: BgShouldFill,
-    ) {
-        // There's some unfortunate hackery going on here for two reasons:
-        //
-        // 1. The prefix needs to be injected into the output stream.
-        //
-        // 2. We must ensure that we fill rightwards with the appropriate
-        //    non-emph background color. In that case we don't use the last
-        //    style of the line, because this might be emph.
-        for ((((_, state), syntax_sections), diff_sections), &line_has_homolog) in lines
-            .iter()
-            .zip_eq(syntax_style_sections)
-            .zip_eq(diff_style_sections)
-            .zip_eq(lines_have_homolog)
-        {
-            let (mut line, line_is_empty) = Painter::paint_line(
-                syntax_sections,
-                diff_sections,
-                state,
-                line_numbers_data,
-                None,
-                painted_prefix(state.clone(), config),
-                config,
-            );
-            let (bg_fill_mode, fill_style) =
-                Painter::get_should_right_fill_background_color_and_fill_style(
-                    diff_sections,
-                    Some(line_has_homolog),
-                    state,
-                    background_color_extends_to_terminal_width,
-                    config,
-                );
-
-            if let Some(BgFillMethod::TryAnsiSequence) = bg_fill_mode {
-                Painter::right_fill_background_color(&mut line, fill_style);
-            } else if let Some(BgFillMethod::Spaces) = bg_fill_mode {
-                let text_width = ansi::measure_text_width(&line);
-                line.push_str(
-                    #[allow(clippy::unnecessary_to_owned)]
-                    &fill_style
-                        .paint(" ".repeat(config.available_terminal_width - text_width))
-                        .to_string(),
-                );
-            } else if line_is_empty {
-                if let Some(empty_line_style) = empty_line_style {
-                    Painter::mark_empty_line(
-                        &empty_line_style,
-                        &mut line,
-                        if config.line_numbers { Some(" ") } else { None },
-                    );
-                }
-            };
-
-            output_buffer.push_str(&line);
-            output_buffer.push('\n');
-        }
-    }
-
-    /// Write painted line to the output buffer, with syntax-highlighting and `style` superimposed.
-    // Note that, if passing `style_sections` as
-    // `StyleSectionSpecifier::StyleSections`, then tabs must already have been
-    // expanded in the text.
-    pub fn syntax_highlight_and_paint_line(
-        &mut self,
-        line: &str,
-        style_sections: StyleSectionSpecifier,
-        state: State,
-        background_color_extends_to_terminal_width: BgShouldFill,
-    ) {
-        let lines = vec![(tabs::expand(line, &self.config.tab_cfg), state)];
-        let syntax_style_sections =
-            get_syntax_style_sections_for_lines(&lines, self.highlighter.as_mut(), self.config);
-        let diff_style_sections = match style_sections {
-            StyleSectionSpecifier::Style(style) => vec![vec![(style, lines[0].0.as_str())]],
-            StyleSectionSpecifier::StyleSections(style_sections) => vec![style_sections],
-        };
-        Painter::paint_lines(
-            &lines,
-            &syntax_style_sections,
-            &diff_style_sections,
-            &[false],
-            &mut self.output_buffer,
-            self.config,
-            &mut None,
-            None,
-            background_color_extends_to_terminal_width,
-        );
-    }
-
     /// Determine whether the terminal should fill the line rightwards with a background color, and
-    /// the style for doing so.
+    /// the style for doing so. `line_style`, if any, is the style of the whole line, e.g. that of a
+    /// moved line (see --color-moved) or of a zebra stripe (see --zebra), and is used for filling.
     pub fn get_should_right_fill_background_color_and_fill_style(
         diff_sections: &[(Style, &str)],
         line_has_homolog: Option<bool>,
+        line_style: Option<Style>,
         state: &State,
         background_color_extends_to_terminal_width: BgShouldFill,
         config: &config::Config,
     ) -> (Option<BgFillMethod>, Style) {
         let fill_style = match state {
             State::HunkMinus(_, None) | State::HunkMinusWrapped => {
-                if let Some(true) = line_has_homolog {
+                if let Some(line_style) = line_style {
+                    line_style
+                } else if let Some(true) = line_has_homolog {
                     config.minus_non_emph_style
                 } else {
                     config.minus_style
                 }
             }
-            State::HunkZero(_, None) | State::HunkZeroWrapped => config.zero_style,
+            State::HunkZero(_, None) | State::HunkZeroWrapped => {
+                line_style.unwrap_or(config.zero_style)
+            }
             State::HunkPlus(_, None) | State::HunkPlusWrapped => {
-                if let Some(true) = line_has_homolog {
+                if let Some(line_style) = line_style {
+                    line_style
+                } else if let Some(true) = line_has_homolog {
                     config.plus_non_emph_style
                 } else {
                     config.plus_style
@@ -383,87 +312,6 @@ impl<'p> Painter<'p> {
         line.push_str(ansi::ANSI_SGR_RESET);
     }
 
-    /// Use ANSI sequences to visually mark the current line as empty. If `marker` is None then the
-    /// line is marked using terminal emulator colors only, i.e. without appending any marker text
-    /// to the line. This is typically appropriate only when the `line` buffer is empty, since
-    /// otherwise the ANSI_CSI_CLEAR_TO_BOL instruction would overwrite the text to the left of the
-    /// current buffer position.
-    pub fn mark_empty_line(empty_line_style: &Style, line: &mut String, marker: Option<&str>) {
-        line.push_str(
-            #[allow(clippy::unnecessary_to_owned)]
-            &empty_line_style
-                .paint(marker.unwrap_or(ansi::ANSI_CSI_CLEAR_TO_BOL))
-                .to_string(),
-        );
-    }
-
-    /// Return painted line (maybe prefixed with line numbers field) and an is_empty? boolean.
-    pub fn paint_line(
-        syntax_sections: &[(SyntectStyle, &str)],
-        diff_sections: &[(Style, &str)],
-        state: &State,
-        line_numbers_data: &mut Option<&mut line_numbers::LineNumbersData>,
-        side_by_side_panel: Option<PanelSide>,
-        mut painted_prefix: Option<ansi_term::ANSIString>,
-        config: &config::Config,
-    ) -> (String, bool) {
-        let mut ansi_strings = Vec::new();
-
-        let output_line_numbers = line_numbers_data.is_some();
-        if output_line_numbers {
-            // Unified diff lines are printed in one go, but side-by-side lines
-            // are printed in two parts, so do not increment line numbers when the
-            // first (left) part is printed.
-            let increment = !matches!(side_by_side_panel, Some(side_by_side::Left));
-            if let Some((line_numbers, styles)) = line_numbers::linenumbers_and_styles(
-                line_numbers_data.as_mut().unwrap(),
-                state,
-                config,
-                increment,
-            ) {
-                ansi_strings.extend(line_numbers::format_and_paint_line_numbers(
-                    line_numbers_data.as_ref().unwrap(),
-                    side_by_side_panel,
-                    styles,
-                    line_numbers,
-                    config,
-                ))
-            }
-        }
-        let superimposed = superimpose_style_sections(
-            syntax_sections,
-            diff_sections,
-            config.true_color,
-            config.null_syntect_style,
-        );
-
-        let mut handled_prefix = false;
-        for (section_style, text) in &superimposed {
-            // If requested re-insert the +/- prefix with proper styling.
-            if !handled_prefix {
-                if let Some(painted_prefix) = painted_prefix.take() {
-                    ansi_strings.push(painted_prefix)
-                }
-            }
-
-            if !text.is_empty() {
-                ansi_strings.push(section_style.paint(text.as_str()));
-            }
-            handled_prefix = true;
-        }
-
-        // Only if syntax is empty (implies diff empty) can a line actually be empty.
-        let is_empty = syntax_sections.is_empty();
-        (ansi_term::ANSIStrings(&ansi_strings).to_string(), is_empty)
-    }
-
-    /// Write output buffer to output stream, and clear the buffer.
-    pub fn emit(&mut self) -> std::io::Result<()> {
-        write!(self.writer, "{}", self.output_buffer)?;
-        self.output_buffer.clear();
-        Ok(())
-    }
-
     pub fn should_compute_syntax_highlighting(state: &State, config: &config::Config) -> bool {
         if config.syntax_theme.is_none() {
             return false;
@@ -522,14 +370,14 @@ impl<'p> Painter<'p> {
     ///    computed diff styles with these styles from the raw line. (This is
     ///    how support for git's --color-moved is implemented.)
     fn update_diff_style_sections<'a>(
-        lines: &'a [(String, State)],
+        lines: &'a [(String, State, LineInfo)],
         diff_style_sections: &mut Vec<LineSections<'a, Style>>,
         whitespace_error_style: Option<Style>,
         non_emph_style: Option<Style>,
         lines_have_homolog: &[bool],
         config: &config::Config,
     ) {
-        for (((_, state), style_sections), line_has_homolog) in lines
+        for (((_, state, _), style_sections), line_has_homolog) in lines
             .iter()
             .zip_eq(diff_style_sections)
             .zip_eq(lines_have_homolog)
@@ -578,206 +426,2508 @@ impl<'p> Painter<'p> {
     }
 }
 
-/// Remove initial -/+ character, expand tabs as spaces, and terminate with newline.
-// Terminating with newline character is necessary for many of the sublime syntax definitions to
-// highlight correctly.
-// See https://docs.rs/syntect/3.2.0/syntect/parsing/struct.SyntaxSetBuilder.html#method.add_from_folder
-pub fn prepare(line: &str, prefix_length: usize, config: &config::Config) -> String {
-    if !line.is_empty() {
+impl<'p, E: Emitter> Painter<'p, E> {
+    /// A painter whose output is formatted by `emitter`.
+    pub fn with_emitter(writer: &'p mut dyn Write, config: &'p config::Config, emitter: E) -> Self {
+        let default_syntax = Painter::get_syntax(config, None);
+        let panel_width_fix = ansifill::UseFullPanelWidth::new(config);
+
+        let line_numbers_data = if config.line_numbers {
+            Some(line_numbers::LineNumbersData::from_format_strings(
+                &config.line_numbers_format,
+                panel_width_fix,
+            ))
+        } else if config.side_by_side {
+            // If line numbers are disabled in side-by-side then the data is still used
+            // for width calculation and to pad odd width to even, see `UseFullPanelWidth`
+            // for details.
+            Some(line_numbers::LineNumbersData::empty_for_sbs(
+                panel_width_fix,
+            ))
+        } else {
+            None
+        };
+        Self {
+            minus_lines: Vec::new(),
+            plus_lines: Vec::new(),
+            output_buffer: config
+                .output_buffer_limit
+                .map_or_else(String::new, String::with_capacity),
+            syntax: default_syntax,
+            highlighter: None,
+            writer: EmitterWriter::new(writer),
+            emitter,
+            config,
+            line_numbers_data,
+            merge_conflict_lines: merge_conflict::MergeConflictLines::new(),
+            merge_conflict_commit_names: merge_conflict::MergeConflictCommitNames::new(),
+            hunk_size: None,
+            leading_context: None,
+            hunk_line_numbers: MinusPlus::new(0, 0),
+            output_buffer_limit: config.output_buffer_limit,
+            deferred_write_error: None,
+            density_window: VecDeque::new(),
+            zebra_stripe: false,
+            wrote_file_header: false,
+            wrote_hunk_header: false,
+            emphasis_records: config.emphasis_records_file.as_ref().map(|_| Vec::new()),
+            hunk_file_paths: MinusPlus::new(String::new(), String::new()),
+        }
+    }
+
+    pub fn set_syntax(&mut self, filename: Option<&str>) {
+        self.syntax = Painter::get_syntax(self.config, filename);
+    }
+
+    pub fn set_highlighter(&mut self) {
+        if let Some(ref syntax_theme) = self.config.syntax_theme {
+            self.highlighter = Some(HighlightLines::new(self.syntax, syntax_theme))
+        };
+    }
+
+    pub fn paint_buffered_minus_and_plus_lines(&mut self) {
+        if self.minus_lines.is_empty() && self.plus_lines.is_empty() {
+            return;
+        }
+        if let Some((n_removed, n_added)) = self.hunk_size.as_mut() {
+            *n_removed += self.minus_lines.len();
+            *n_added += self.plus_lines.len();
+        }
+        let output_buffer_len = self.output_buffer.len();
+        if self.config.line_ending_changes != CollapsibleChanges::Show
+            && is_line_ending_only_change(MinusPlus::new(&self.minus_lines, &self.plus_lines))
+        {
+            self.paint_collapsed_change("line-ending change", &self.config.line_ending_changes);
+        } else if self.config.whitespace_only_changes != CollapsibleChanges::Show
+            && is_whitespace_only_change(&self.minus_lines, &self.plus_lines)
+        {
+            self.paint_collapsed_change(
+                "whitespace-only change",
+                &self.config.whitespace_only_changes,
+            );
+        } else {
+            let mut emphasis_ranges = MinusPlus::new(Vec::new(), Vec::new());
+            paint_minus_and_plus_lines(
+                MinusPlus::new(&self.minus_lines, &self.plus_lines),
+                &mut self.line_numbers_data,
+                &mut self.highlighter,
+                self.syntax,
+                self.emphasis_records
+                    .is_some()
+                    .then_some(&mut emphasis_ranges),
+                &mut self.output_buffer,
+                &self.emitter,
+                self.config,
+            );
+            self.record_emphasis_ranges(emphasis_ranges);
+        }
+        let line_kinds = painted_order(self.config)
+            .iter()
+            .flat_map(|&minus_or_plus| {
+                let (lines, kind) = match minus_or_plus {
+                    Minus => (&self.minus_lines, LineKind::Minus),
+                    Plus => (&self.plus_lines, LineKind::Plus),
+                };
+                lines
+                    .iter()
+                    .flat_map(move |(_, _, info)| match info.no_newline_marker {
+                        Some(_) => vec![kind, LineKind::NoNewlineMarker],
+                        None => vec![kind],
+                    })
+            })
+            .collect::<Vec<_>>();
+        self.decorate_painted_lines(output_buffer_len, &line_kinds);
+        self.minus_lines.clear();
+        self.plus_lines.clear();
+        self.emit_if_output_buffer_limit_reached();
+    }
+
+    /// Append to emphasis_records the emphasized character ranges of each buffered minus and plus
+    /// line, given as returned by paint_minus_and_plus_lines.
+    fn record_emphasis_ranges(&mut self, emphasis_ranges: MinusPlus<Vec<Vec<Range<usize>>>>) {
+        let Some(records) = self.emphasis_records.as_mut() else {
+            return;
+        };
+        for minus_or_plus in [Minus, Plus] {
+            for (i, ranges) in emphasis_ranges[minus_or_plus].iter().enumerate() {
+                if !ranges.is_empty() {
+                    records.push(EmphasisRecord {
+                        file: self.hunk_file_paths[minus_or_plus].clone(),
+                        line_number: self.hunk_line_numbers[minus_or_plus] + i,
+                        side: minus_or_plus,
+                        ranges: ranges.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Write emphasis_records to the --emphasis-records-file, if there is one, as JSON lines.
+    pub fn write_emphasis_records(&self) -> std::io::Result<()> {
+        let (Some(path), Some(records)) =
+            (&self.config.emphasis_records_file, &self.emphasis_records)
+        else {
+            return Ok(());
+        };
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        for record in records {
+            writeln!(file, "{}", record.to_json())?;
+        }
+        file.flush()
+    }
+
+    /// Paint the buffered lines, whose change is of the kind described by `description`, as a
+    /// notice, followed by the added lines if `display` is 'compact'.
+    fn paint_collapsed_change(&mut self, description: &str, display: &CollapsibleChanges) {
+        let notice = format!(
+            "{description} (-{}/+{})",
+            self.minus_lines.len(),
+            self.plus_lines.len()
+        );
+        self.output_buffer.push_str(
+            &self
+                .emitter
+                .paint(self.config.inline_hint_style.ansi_term_style, &notice),
+        );
+        self.output_buffer.push('\n');
+        // Keep line numbers in step with the lines that are not displayed.
+        let painted_plus_lines = match display {
+            CollapsibleChanges::Compact => &self.plus_lines[..],
+            _ => &[],
+        };
+        if let Some(line_numbers_data) = self.line_numbers_data.as_mut() {
+            line_numbers_data.line_number[Minus] += self.minus_lines.len();
+            line_numbers_data.line_number[Plus] += self.plus_lines.len() - painted_plus_lines.len();
+        }
+        if !painted_plus_lines.is_empty() {
+            paint_minus_and_plus_lines(
+                MinusPlus::new(&Vec::new(), &painted_plus_lines.to_vec()),
+                &mut self.line_numbers_data,
+                &mut self.highlighter,
+                self.syntax,
+                None,
+                &mut self.output_buffer,
+                &self.emitter,
+                self.config,
+            );
+        }
+    }
+
+    /// Apply the --density-column to the lines painted to the output buffer since `start`, which
+    /// are of kinds `line_kinds`, and advance the hunk line numbers past them.
+    fn decorate_painted_lines(&mut self, start: usize, line_kinds: &[LineKind]) {
+        for kind in line_kinds {
+            let numbers = &mut self.hunk_line_numbers;
+            match kind {
+                LineKind::Minus => numbers[Minus] += 1,
+                LineKind::Zero => {
+                    numbers[Minus] += 1;
+                    numbers[Plus] += 1;
+                }
+                LineKind::Plus => numbers[Plus] += 1,
+                LineKind::NoNewlineMarker => {}
+            }
+        }
+        let density_shades = line_kinds
+            .iter()
+            .map(|kind| match kind {
+                LineKind::NoNewlineMarker => None,
+                _ => self.next_density_shade(*kind != LineKind::Zero),
+            })
+            .collect::<Vec<_>>();
+        if !self.config.density_column {
+            return;
+        }
+        let painted = &self.output_buffer[start..];
+        // Lines cannot be matched to their kinds if they have been wrapped, or combined as in
+        // side-by-side mode or with --inline-edits.
+        if self.config.side_by_side || painted.lines().count() != line_kinds.len() {
+            return;
+        }
+        let mut decorated = String::with_capacity(painted.len());
+        for (line, density_shade) in painted.lines().zip(density_shades) {
+            let mut line = line.to_string();
+            if let Some(shade) = density_shade {
+                append_density_shade(&mut line, shade, &self.emitter, self.config);
+            }
+            decorated.push_str(&line);
+            decorated.push('\n');
+        }
+        self.output_buffer.truncate(start);
+        self.output_buffer.push_str(&decorated);
+    }
+
+    /// Record whether the next painted hunk line is a change, and return the --density-column
+    /// shade for the window of lines ending with it, if the column is displayed.
+    fn next_density_shade(&mut self, is_change: bool) -> Option<char> {
+        if !self.config.density_column {
+            return None;
+        }
+        let window = self.config.density_column_window;
+        if self.density_window.len() == window {
+            self.density_window.pop_front();
+        }
+        self.density_window.push_back(is_change);
+        let n_changes = self
+            .density_window
+            .iter()
+            .filter(|is_change| **is_change)
+            .count();
+        let shades = &self.config.density_column_shades;
+        // Round to the nearest shade.
+        Some(shades[(n_changes * (shades.len() - 1) + window / 2) / window])
+    }
+
+    /// Paint the held back leading context of the current file, replacing it with a one-line
+    /// summary if it exceeds the --collapse-leading-context threshold, and stop holding back lines.
+    pub fn paint_leading_context(&mut self) {
+        let lines = match self.leading_context.take() {
+            Some(lines) => lines,
+            None => return,
+        };
+        if lines.len() <= self.config.collapse_leading_context {
+            for (line, tab_symbols, state) in lines {
+                self.paint_zero_line(&line, &tab_symbols, state);
+            }
+            return;
+        }
+        let notice = format!("(… {} lines …)", lines.len());
+        self.output_buffer.push_str(
+            &self
+                .emitter
+                .paint(self.config.inline_hint_style.ansi_term_style, &notice),
+        );
+        self.output_buffer.push('\n');
+        // Keep line numbers in step with the lines that are not displayed.
+        if let Some(line_numbers_data) = self.line_numbers_data.as_mut() {
+            line_numbers_data.line_number[Minus] += lines.len();
+            line_numbers_data.line_number[Plus] += lines.len();
+        }
+        self.hunk_line_numbers[Minus] += lines.len();
+        self.hunk_line_numbers[Plus] += lines.len();
+    }
+
+    /// Paint what is pending of the hunk that has just ended: its buffered minus and plus lines, any
+    /// held back leading context, and its --hunk-size-format annotation.
+    pub fn paint_end_of_hunk(&mut self) {
+        self.paint_buffered_minus_and_plus_lines();
+        // Leading context is held back from the header of a file, so the end of a hunk only ends
+        // it once it holds lines.
+        if self
+            .leading_context
+            .as_ref()
+            .is_some_and(|lines| !lines.is_empty())
+        {
+            self.paint_leading_context();
+        }
+        self.paint_hunk_size_annotation();
+    }
+
+    /// Paint the --hunk-size-format annotation for the hunk that has just ended, if any.
+    fn paint_hunk_size_annotation(&mut self) {
+        if let Some((n_removed, n_added)) = self.hunk_size.take() {
+            if self.config.hunk_size_format.is_empty() {
+                return;
+            }
+            let annotation = self
+                .config
+                .hunk_size_format
+                .replace("{added}", &n_added.to_string())
+                .replace("{removed}", &n_removed.to_string());
+            self.output_buffer.push_str(
+                &self
+                    .emitter
+                    .paint(self.config.hunk_header_style.ansi_term_style, &annotation),
+            );
+            self.output_buffer.push('\n');
+        }
+    }
+
+    /// Write the file-separator, unless no file header has been written yet.
+    pub fn write_file_separator(&mut self) -> std::io::Result<()> {
+        let config = self.config;
+        self.wrote_hunk_header = false;
+        if std::mem::replace(&mut self.wrote_file_header, true) {
+            self.write_separator(&config.file_separator, config.file_separator_style)?;
+        }
+        Ok(())
+    }
+
+    /// Write the hunk-separator, unless no hunk header of the current file has been written yet.
+    pub fn write_hunk_separator(&mut self) -> std::io::Result<()> {
+        let config = self.config;
+        if std::mem::replace(&mut self.wrote_hunk_header, true) {
+            self.write_separator(&config.hunk_separator, config.hunk_separator_style)?;
+        }
+        Ok(())
+    }
+
+    /// Write `separator`, repeated to fill the decorations width. Nothing is written if it is
+    /// empty.
+    fn write_separator(&mut self, separator: &str, style: Style) -> std::io::Result<()> {
+        if separator.is_empty() {
+            return Ok(());
+        }
+        let repetitions = match self.config.decorations_width {
+            cli::Width::Fixed(width) => (width / separator.width().max(1)).max(1),
+            cli::Width::Variable => 1,
+        };
+        writeln!(
+            self.writer,
+            "{}",
+            self.emitter
+                .paint(style.ansi_term_style, &separator.repeat(repetitions))
+        )
+    }
+
+    /// `tab_symbols` are the byte ranges of the tab symbols in `line` (see
+    /// `prepare_marking_tab_symbols()`).
+    pub fn paint_zero_line(&mut self, line: &str, tab_symbols: &[Range<usize>], state: State) {
+        let output_buffer_len = self.output_buffer.len();
+        let lines = &[(line.to_string(), state.clone(), LineInfo::default())];
+        let syntax_style_sections =
+            get_syntax_style_sections_for_lines(lines, self.highlighter.as_mut(), self.config);
+        let zebra_stripe = self.zebra_stripe;
+        let zero_style = if zebra_stripe {
+            self.config.zebra_style
+        } else {
+            self.config.zero_style
+        };
+        self.zebra_stripe = self.config.zebra && !zebra_stripe;
+        let mut diff_style_sections = vec![vec![(zero_style, lines[0].0.as_str())]]; // TODO: compute style from state
+        Painter::update_diff_style_sections(
+            lines,
+            &mut diff_style_sections,
+            None,
+            None,
+            &[false],
+            self.config,
+        );
+        mark_tab_symbols(&lines[0].0, &mut diff_style_sections[0], tab_symbols);
+        if self.config.side_by_side {
+            // `lines[0].0` so the line has the '\n' already added (as in the +- case)
+            side_by_side::paint_zero_lines_side_by_side(
+                &lines[0].0,
+                syntax_style_sections,
+                diff_style_sections,
+                &mut self.output_buffer,
+                self.config,
+                &mut self.line_numbers_data.as_mut(),
+                painted_prefix(state, self.config),
+                zebra_stripe.then_some(zero_style),
+                BgShouldFill::With(BgFillMethod::Spaces),
+            );
+        } else {
+            Painter::paint_lines(
+                lines,
+                &syntax_style_sections,
+                diff_style_sections.as_slice(),
+                &[false],
+                zebra_stripe.then_some(zero_style),
+                &mut self.output_buffer,
+                &self.emitter,
+                self.config,
+                &mut self.line_numbers_data.as_mut(),
+                None,
+                BgShouldFill::With(BgFillMethod::Spaces),
+            );
+        }
+        self.decorate_painted_lines(output_buffer_len, &[LineKind::Zero]);
+        self.emit_if_output_buffer_limit_reached();
+    }
+
+    /// Emit the output buffer if it has reached `output_buffer_limit`. An error is deferred to the
+    /// next call to emit(), which the caller is responsible for making.
+    fn emit_if_output_buffer_limit_reached(&mut self) {
+        match self.output_buffer_limit {
+            Some(limit)
+                if self.output_buffer.len() >= limit && self.deferred_write_error.is_none() =>
+            {
+                if let Err(error) = self.emit() {
+                    self.deferred_write_error = Some(error);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Superimpose background styles and foreground syntax
+    /// highlighting styles, and write colored lines to output buffer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn paint_lines<'a>(
+        lines: &'a [(String, State, LineInfo)],
+        syntax_style_sections: &[LineSections<'a, SyntectStyle>],
+        diff_style_sections: &[LineSections<'a, Style>],
+        lines_have_homolog: &[bool],
+        line_style: Option<Style>,
+        output_buffer: &mut String,
+        emitter: &E,
+        config: &config::Config,
+        line_numbers_data: &mut Option<&mut line_numbers::LineNumbersData>,
+        empty_line_style: Option<Style>, // a style with background color to highlight an empty line
+        background_color_extends_to_terminal_width: BgShouldFill,
+    ) {
+        // There's some unfortunate hackery going on here for two reasons:
+        //
+        // 1. The prefix needs to be injected into the output stream.
+        //
+        // 2. We must ensure that we fill rightwards with the appropriate
+        //    non-emph background color. In that case we don't use the last
+        //    style of the line, because this might be emph.
+        for ((((_, state, _), syntax_sections), diff_sections), &line_has_homolog) in lines
+            .iter()
+            .zip_eq(syntax_style_sections)
+            .zip_eq(diff_style_sections)
+            .zip_eq(lines_have_homolog)
+        {
+            let (mut line, line_is_empty) = Painter::paint_line(
+                syntax_sections,
+                diff_sections,
+                state,
+                line_numbers_data,
+                None,
+                painted_gutter_or_prefix(state, line_has_homolog, config),
+                emitter,
+                config,
+            );
+            if let Some(label) = line_kind_label(state, line_has_homolog, config) {
+                line.insert_str(
+                    0,
+                    &emitter.paint(ansi_term::Style::new(), &format!("{label} ")),
+                );
+            }
+            let (bg_fill_mode, fill_style) =
+                Painter::get_should_right_fill_background_color_and_fill_style(
+                    diff_sections,
+                    Some(line_has_homolog),
+                    line_style,
+                    state,
+                    background_color_extends_to_terminal_width,
+                    config,
+                );
+
+            let is_code_line = matches!(
+                state,
+                State::HunkMinus(..) | State::HunkZero(..) | State::HunkPlus(..)
+            );
+            let (rows, fit_to_terminal) = if config.wrap_lines && is_code_line {
+                (wrap_painted_line(&line, diff_sections, config), true)
+            } else if config.truncate_lines && is_code_line {
+                (vec![truncate_painted_line(line, fill_style, config)], true)
+            } else {
+                (vec![line], false)
+            };
+            for mut row in rows {
+                if fit_to_terminal && emitter.text_width(&row) >= config.available_terminal_width {
+                    // The row already fills the terminal, and clearing to the end of the line from
+                    // its last column would erase the character there in some terminals.
+                } else if let Some(BgFillMethod::TryAnsiSequence) = bg_fill_mode {
+                    Painter::right_fill_background_color(&mut row, fill_style);
+                } else if let Some(BgFillMethod::Spaces) = bg_fill_mode {
+                    // A line wider than the terminal is not padded.
+                    let text_width = emitter.text_width(&row);
+                    row.push_str(&emitter.paint(
+                        fill_style.ansi_term_style,
+                        &" ".repeat(config.available_terminal_width.saturating_sub(text_width)),
+                    ));
+                } else if line_is_empty {
+                    if let Some(empty_line_style) = empty_line_style {
+                        Painter::mark_empty_line(
+                            &empty_line_style,
+                            &mut row,
+                            if config.line_numbers { Some(" ") } else { None },
+                            emitter,
+                        );
+                    }
+                };
+
+                output_buffer.push_str(&row);
+                output_buffer.push('\n');
+            }
+        }
+    }
+
+    /// Write painted line to the output buffer, with syntax-highlighting and `style` superimposed.
+    // Note that, if passing `style_sections` as
+    // `StyleSectionSpecifier::StyleSections`, then tabs must already have been
+    // expanded in the text.
+    pub fn syntax_highlight_and_paint_line(
+        &mut self,
+        line: &str,
+        style_sections: StyleSectionSpecifier,
+        state: State,
+        background_color_extends_to_terminal_width: BgShouldFill,
+    ) {
+        let (line, tab_symbols) = tabs::expand_marking_symbols(line, &self.config.tab_cfg);
+        let lines = vec![(line, state, LineInfo::default())];
+        let syntax_style_sections =
+            get_syntax_style_sections_for_lines(&lines, self.highlighter.as_mut(), self.config);
+        let diff_style_sections = match style_sections {
+            StyleSectionSpecifier::Style(style) => {
+                let mut style_sections = vec![(style, lines[0].0.as_str())];
+                mark_tab_symbols(&lines[0].0, &mut style_sections, &tab_symbols);
+                vec![style_sections]
+            }
+            StyleSectionSpecifier::StyleSections(style_sections) => vec![style_sections],
+        };
+        Painter::paint_lines(
+            &lines,
+            &syntax_style_sections,
+            &diff_style_sections,
+            &[false],
+            None,
+            &mut self.output_buffer,
+            &self.emitter,
+            self.config,
+            &mut None,
+            None,
+            background_color_extends_to_terminal_width,
+        );
+    }
+
+    /// Use ANSI sequences to visually mark the current line as empty. If `marker` is None then the
+    /// line is marked using terminal emulator colors only, i.e. without appending any marker text
+    /// to the line. This is typically appropriate only when the `line` buffer is empty, since
+    /// otherwise the ANSI_CSI_CLEAR_TO_BOL instruction would overwrite the text to the left of the
+    /// current buffer position.
+    pub fn mark_empty_line(
+        empty_line_style: &Style,
+        line: &mut String,
+        marker: Option<&str>,
+        emitter: &E,
+    ) {
+        line.push_str(&emitter.paint(
+            empty_line_style.ansi_term_style,
+            marker.unwrap_or(ansi::ANSI_CSI_CLEAR_TO_BOL),
+        ));
+    }
+
+    /// Return painted line (maybe prefixed with line numbers field), formatted by `emitter`, and an
+    /// is_empty? boolean.
+    #[allow(clippy::too_many_arguments)]
+    pub fn paint_line(
+        syntax_sections: &[(SyntectStyle, &str)],
+        diff_sections: &[(Style, &str)],
+        state: &State,
+        line_numbers_data: &mut Option<&mut line_numbers::LineNumbersData>,
+        side_by_side_panel: Option<PanelSide>,
+        mut painted_prefix: Option<ansi_term::ANSIString>,
+        emitter: &E,
+        config: &config::Config,
+    ) -> (String, bool) {
+        let mut ansi_strings = Vec::new();
+
+        let output_line_numbers = line_numbers_data.is_some();
+        if output_line_numbers {
+            // Unified diff lines are printed in one go, but side-by-side lines
+            // are printed in two parts, so do not increment line numbers when the
+            // first (left) part is printed.
+            let increment = !matches!(side_by_side_panel, Some(side_by_side::Left));
+            if let Some((line_numbers, styles)) = line_numbers::linenumbers_and_styles(
+                line_numbers_data.as_mut().unwrap(),
+                state,
+                config,
+                increment,
+            ) {
+                ansi_strings.extend(line_numbers::format_and_paint_line_numbers(
+                    line_numbers_data.as_ref().unwrap(),
+                    side_by_side_panel,
+                    styles,
+                    line_numbers,
+                    config,
+                ))
+            }
+        }
+        let mut superimposed = superimpose_style_sections(
+            syntax_sections,
+            diff_sections,
+            config.true_color,
+            config.null_syntect_style,
+            config.syntax_font_styles,
+        );
+        if let Some(indent_guide_style) = config.indent_guide_style {
+            if config.tab_cfg.width() > 0
+                && matches!(
+                    state,
+                    State::HunkMinus(..) | State::HunkZero(..) | State::HunkPlus(..)
+                )
+            {
+                superimposed =
+                    draw_indent_guides(superimposed, config.tab_cfg.width(), indent_guide_style);
+            }
+        }
+        if config.show_whitespace
+            && matches!(
+                state,
+                State::HunkMinus(..) | State::HunkZero(..) | State::HunkPlus(..)
+            )
+        {
+            superimposed = mark_whitespace(
+                superimposed,
+                config.tab_cfg.symbol().filter(|_| config.tab_cfg.replace()),
+                config.tab_cfg.width(),
+            );
+        }
+        if config.max_section_length > 0 {
+            superimposed = split_long_sections(superimposed, config.max_section_length);
+        }
+
+        let mut handled_prefix = false;
+        for (section_style, text) in &superimposed {
+            // If requested re-insert the +/- prefix with proper styling.
+            if !handled_prefix {
+                if let Some(painted_prefix) = painted_prefix.take() {
+                    ansi_strings.push(painted_prefix)
+                }
+            }
+
+            if section_style.is_tab_symbol {
+                let mut symbol_style = *section_style;
+                symbol_style.ansi_term_style.foreground =
+                    config.inline_hint_style.ansi_term_style.foreground;
+                ansi_strings.push(symbol_style.paint(text.as_str()));
+            } else if !text.is_empty() {
+                ansi_strings.push(section_style.paint(text.as_str()));
+            }
+            handled_prefix = true;
+        }
+
+        let sections: Vec<_> = ansi_strings
+            .iter()
+            .map(|ansi_string| (*ansi_string.style_ref(), &**ansi_string))
+            .collect();
+        let mut line = String::new();
+        emitter.emit_sections(&sections, &mut line);
+        // Only if syntax is empty (implies diff empty) can a line actually be empty.
+        let is_empty = syntax_sections.is_empty();
+        (line, is_empty)
+    }
+
+    /// Write output buffer to output stream, and clear the buffer.
+    pub fn emit(&mut self) -> std::io::Result<()> {
+        if let Some(error) = self.deferred_write_error.take() {
+            return Err(error);
+        }
+        write!(self.writer, "{}", self.output_buffer)?;
+        self.output_buffer.clear();
+        if let Some(limit) = self.output_buffer_limit {
+            self.output_buffer.shrink_to(limit);
+        }
+        Ok(())
+    }
+}
+
+/// Remove initial -/+ character, expand tabs as spaces, and terminate with newline.
+// Terminating with newline character is necessary for many of the sublime syntax definitions to
+// highlight correctly.
+// See https://docs.rs/syntect/3.2.0/syntect/parsing/struct.SyntaxSetBuilder.html#method.add_from_folder
+pub fn prepare(line: &str, prefix_length: usize, config: &config::Config) -> String {
+    prepare_marking_tab_symbols(line, prefix_length, config).0
+}
+
+/// As `prepare()`, also returning the byte ranges of the tab symbols in the prepared line (see
+/// `tabs::expand_marking_symbols()`).
+pub fn prepare_marking_tab_symbols(
+    line: &str,
+    prefix_length: usize,
+    config: &config::Config,
+) -> (String, Vec<Range<usize>>) {
+    if !line.is_empty() {
         // The prefix contains -/+/space characters, added by git. We removes them now so they
         // are not present during syntax highlighting or wrapping. If --keep-plus-minus-markers
         // is in effect the prefix is re-inserted in Painter::paint_line.
-        let mut line = tabs::remove_prefix_and_expand(prefix_length, line, &config.tab_cfg);
+        let (mut line, tab_symbols) =
+            tabs::remove_prefix_and_expand(prefix_length, line, &config.tab_cfg);
         line.push('\n');
-        line
+        (line, tab_symbols)
+    } else {
+        ("\n".to_string(), Vec::new())
+    }
+}
+
+// Remove initial -/+ characters, expand tabs as spaces, retaining ANSI sequences. Terminate with
+// newline character.
+pub fn prepare_raw_line(raw_line: &str, prefix_length: usize, config: &config::Config) -> String {
+    let mut line = tabs::expand(raw_line, &config.tab_cfg);
+    line.push('\n');
+    ansi::ansi_preserving_slice(&line, prefix_length)
+}
+
+/// The lines of a side that are all moved are painted with --moved-style (see --color-moved). If
+/// `emphasis_ranges` is given, the emphasized character ranges of each painted line are stored in
+/// it (see [`EmphasisRecord`]); they are left empty for lines painted with --inline-edits.
+#[allow(clippy::too_many_arguments)]
+pub fn paint_minus_and_plus_lines<E: Emitter>(
+    lines: MinusPlus<&Vec<(String, State, LineInfo)>>,
+    line_numbers_data: &mut Option<LineNumbersData>,
+    highlighter: &mut Option<HighlightLines>,
+    syntax: &SyntaxReference,
+    emphasis_ranges: Option<&mut MinusPlus<Vec<Vec<Range<usize>>>>>,
+    output_buffer: &mut String,
+    emitter: &E,
+    config: &config::Config,
+) {
+    let mut syntax_style_sections = MinusPlus::new(
+        get_syntax_style_sections_for_lines(lines[Minus], highlighter.as_mut(), config),
+        get_syntax_style_sections_for_lines(lines[Plus], highlighter.as_mut(), config),
+    );
+    let are_moved = |lines: &[(String, State, LineInfo)]| {
+        !lines.is_empty() && lines.iter().all(|(_, _, info)| info.is_moved)
+    };
+    let moved = MinusPlus::new(are_moved(lines[Minus]), are_moved(lines[Plus]));
+    // The style of the lines of each side, if they are moved (see --color-moved).
+    let lines_style = MinusPlus::new(
+        moved[Minus].then_some(config.moved_style),
+        moved[Plus].then_some(config.moved_style),
+    );
+    let (mut diff_style_sections, line_alignment) = match config.reordered_lines_style {
+        _ if moved[Minus] || moved[Plus] => get_unpaired_style_sections(
+            &lines,
+            MinusPlus::new(
+                lines_style[Minus].unwrap_or(config.minus_style),
+                lines_style[Plus].unwrap_or(config.plus_style),
+            ),
+        ),
+        Some(style) if is_reordering(&lines) => {
+            get_unpaired_style_sections(&lines, MinusPlus::new(style, style))
+        }
+        _ => get_diff_style_sections(&lines, config),
+    };
+    if config.emphasis_algorithm == EmphasisAlgorithm::Lcs {
+        apply_lcs_emphasis(&lines, &mut diff_style_sections, &line_alignment, config);
+    }
+    if let Some(emph_token_regex) = &config.emph_token_regex {
+        for minus_or_plus in [Minus, Plus] {
+            snap_emphasis_to_tokens(
+                lines[minus_or_plus],
+                &mut diff_style_sections[minus_or_plus],
+                emph_token_regex,
+            );
+        }
+    }
+    if config.comment_emphasis != CommentEmphasis::Normal {
+        for minus_or_plus in [Minus, Plus] {
+            apply_comment_emphasis(
+                lines[minus_or_plus],
+                &mut diff_style_sections[minus_or_plus],
+                syntax,
+                config,
+            );
+        }
+    }
+    if let Some(trailing_comment_emph_style) = config.trailing_comment_emph_style {
+        apply_trailing_comment_emphasis(
+            &lines,
+            &mut diff_style_sections,
+            &line_alignment,
+            trailing_comment_emph_style,
+            syntax,
+            config,
+        );
+    }
+    if let Some(formatting_emph_style) = config.formatting_emph_style {
+        apply_formatting_emphasis(
+            &lines,
+            &mut diff_style_sections,
+            &line_alignment,
+            formatting_emph_style,
+            config,
+        );
+    }
+    if let Some(rename_emph_style) = config.rename_emph_style {
+        apply_rename_emphasis(
+            &lines,
+            &mut diff_style_sections,
+            &line_alignment,
+            &config.rename_map,
+            rename_emph_style,
+            config,
+        );
+    }
+    if let Some(pair_swap_emph_style) = config.pair_swap_emph_style {
+        apply_rename_emphasis(
+            &lines,
+            &mut diff_style_sections,
+            &line_alignment,
+            &config.paired_tokens,
+            pair_swap_emph_style,
+            config,
+        );
+    }
+    if let Some(scope_change_emph_style) = config.scope_change_emph_style {
+        apply_scope_change_emphasis(
+            &lines,
+            &mut diff_style_sections,
+            &line_alignment,
+            scope_change_emph_style,
+            syntax,
+            config,
+        );
+    }
+    if let Some(punctuation_emph_style) = config.punctuation_emph_style {
+        for minus_or_plus in [Minus, Plus] {
+            restyle_emph_sections(
+                lines[minus_or_plus],
+                &mut diff_style_sections[minus_or_plus],
+                punctuation_emph_style,
+                |line, start, end| is_punctuation_change(&line[start..end]),
+            );
+        }
+    }
+    if let Some(operator_emph_style) = config.operator_emph_style {
+        for minus_or_plus in [Minus, Plus] {
+            restyle_emph_sections(
+                lines[minus_or_plus],
+                &mut diff_style_sections[minus_or_plus],
+                operator_emph_style,
+                |line, start, end| is_operator_change(line, start, end, &config.operator_tokens),
+            );
+        }
+    }
+    if config.inline_edits && !config.side_by_side {
+        if let Some(pieces) =
+            get_inline_edit_pieces(&lines, &diff_style_sections, &line_alignment, config)
+        {
+            paint_inline_edit(
+                &lines,
+                &syntax_style_sections,
+                &pieces,
+                line_numbers_data,
+                output_buffer,
+                emitter,
+                config,
+            );
+            for minus_or_plus in painted_order(config) {
+                paint_no_newline_markers(lines[minus_or_plus], output_buffer);
+            }
+            return;
+        }
+    }
+    if config.emphasize_additions_only {
+        for style_sections in diff_style_sections[Minus].iter_mut() {
+            for (style, _) in style_sections.iter_mut().filter(|(style, _)| style.is_emph) {
+                *style = config.minus_style;
+            }
+        }
+    }
+    let lines_have_homolog = edits::make_lines_have_homolog(&line_alignment);
+    Painter::update_diff_style_sections(
+        lines[Minus],
+        &mut diff_style_sections[Minus],
+        None,
+        if config.minus_non_emph_style != config.minus_emph_style
+            && !config.emphasize_additions_only
+        {
+            Some(config.minus_non_emph_style)
+        } else {
+            None
+        },
+        &lines_have_homolog[Minus],
+        config,
+    );
+    Painter::update_diff_style_sections(
+        lines[Plus],
+        &mut diff_style_sections[Plus],
+        Some(config.whitespace_error_style),
+        if config.plus_non_emph_style != config.plus_emph_style {
+            Some(config.plus_non_emph_style)
+        } else {
+            None
+        },
+        &lines_have_homolog[Plus],
+        config,
+    );
+    if let Some(indent_normalization_style) = config.indent_normalization_style {
+        apply_indent_normalization_style(
+            &lines,
+            &mut diff_style_sections,
+            &line_alignment,
+            indent_normalization_style,
+            config,
+        );
+    }
+    if let Some((from, to)) = config.emph_gradient {
+        for minus_or_plus in [Minus, Plus] {
+            for style_sections in diff_style_sections[minus_or_plus].iter_mut() {
+                *style_sections =
+                    apply_emph_gradient(std::mem::take(style_sections), from, to, config);
+            }
+        }
+    }
+    if let Some(matching_bracket_style) = config.matching_bracket_style {
+        for minus_or_plus in [Minus, Plus] {
+            apply_matching_bracket_style(
+                lines[minus_or_plus],
+                &mut diff_style_sections[minus_or_plus],
+                matching_bracket_style,
+            );
+        }
+    }
+    if config.max_common_prefix_width > 0 && !config.side_by_side {
+        elide_common_prefixes(
+            &lines,
+            &mut syntax_style_sections,
+            &mut diff_style_sections,
+            &line_alignment,
+            config.max_common_prefix_width,
+        );
+    }
+    if let Some(insertion_point_style) = config.insertion_point_style {
+        mark_insertion_points(
+            &lines,
+            &mut syntax_style_sections,
+            &mut diff_style_sections,
+            &line_alignment,
+            insertion_point_style,
+        );
+    }
+    if let Some(emphasis_ranges) = emphasis_ranges {
+        for minus_or_plus in [Minus, Plus] {
+            emphasis_ranges[minus_or_plus] = diff_style_sections[minus_or_plus]
+                .iter()
+                .map(|sections| get_emphasized_char_ranges(sections))
+                .collect();
+        }
+    }
+    for minus_or_plus in [Minus, Plus] {
+        for ((line, _, info), style_sections) in lines[minus_or_plus]
+            .iter()
+            .zip_eq(diff_style_sections[minus_or_plus].iter_mut())
+        {
+            mark_tab_symbols(line, style_sections, &info.tab_symbols);
+        }
+    }
+    if config.side_by_side {
+        side_by_side::paint_minus_and_plus_lines_side_by_side(
+            lines.clone(),
+            syntax_style_sections,
+            diff_style_sections,
+            lines_have_homolog,
+            lines_style,
+            line_alignment,
+            line_numbers_data,
+            output_buffer,
+            config,
+        );
+        // The markers cannot follow their side, as the lines of both sides are painted together.
+        for minus_or_plus in [Minus, Plus] {
+            paint_no_newline_markers(lines[minus_or_plus], output_buffer);
+        }
+    } else {
+        // Unified diff mode:
+        for minus_or_plus in painted_order(config) {
+            if lines[minus_or_plus].is_empty() {
+                continue;
+            }
+            let empty_line_marker_style = match minus_or_plus {
+                Minus => config.minus_empty_line_marker_style,
+                Plus => config.plus_empty_line_marker_style,
+            };
+            Painter::paint_lines(
+                lines[minus_or_plus],
+                &syntax_style_sections[minus_or_plus],
+                &diff_style_sections[minus_or_plus],
+                &lines_have_homolog[minus_or_plus],
+                lines_style[minus_or_plus],
+                output_buffer,
+                emitter,
+                config,
+                &mut line_numbers_data.as_mut(),
+                Some(empty_line_marker_style),
+                BgShouldFill::default(),
+            );
+            paint_no_newline_markers(lines[minus_or_plus], output_buffer);
+        }
+    }
+}
+
+/// Write the "\ No newline at end of file" markers that follow `lines`.
+fn paint_no_newline_markers(lines: &[(String, State, LineInfo)], output_buffer: &mut String) {
+    for marker in lines
+        .iter()
+        .filter_map(|(_, _, info)| info.no_newline_marker.as_ref())
+    {
+        output_buffer.push_str(marker);
+        output_buffer.push('\n');
+    }
+}
+
+/// The order in which removed and added lines are painted in unified mode (see
+/// --plus-lines-first).
+fn painted_order(config: &config::Config) -> [MinusPlusIndex; 2] {
+    if config.plus_lines_first {
+        [Plus, Minus]
     } else {
-        "\n".to_string()
+        [Minus, Plus]
+    }
+}
+
+pub fn get_syntax_style_sections_for_lines<'a>(
+    lines: &'a [(String, State, LineInfo)],
+    highlighter: Option<&mut HighlightLines>,
+    config: &config::Config,
+) -> Vec<LineSections<'a, SyntectStyle>> {
+    let mut line_sections = Vec::new();
+    match (
+        highlighter,
+        lines
+            .iter()
+            .any(|(_, state, _)| Painter::should_compute_syntax_highlighting(state, config)),
+    ) {
+        (Some(highlighter), true) => {
+            let max_scope_depth = match config.max_syntax_scope_depth {
+                0 => usize::MAX,
+                depth => depth,
+            };
+            for (line, _, _) in lines.iter() {
+                // Fast but simple length comparison. Overcounts non-printable ansi
+                // characters or wider UTF-8, but `truncate_str_short` in the
+                // else branch corrects that.
+                if line.len() < config.max_syntax_length || config.max_syntax_length == 0 {
+                    line_sections.push(
+                        highlighter
+                            .highlight_line(line, &config.syntax_set, max_scope_depth)
+                            .unwrap()
+                            .unwrap_or_else(|| vec![(config.null_syntect_style, line.as_str())]),
+                    );
+                } else {
+                    let line_syntax = ansi::truncate_str_short(line, config.max_syntax_length);
+                    // Re-split to get references into `line` with correct lifetimes.
+                    // SAFETY: slicing the string is safe because `truncate_str_short` always
+                    // returns a prefix of the input and only cuts at grapheme borders.
+                    let (with_syntax, plain) = line.split_at(line_syntax.len());
+                    // Note: splitting a line and only feeding one half to the highlighter may
+                    // result in wrong highlighting until it is reset the next hunk.
+                    //
+                    // Also, as lines are no longer newline terminated they might not be
+                    // highlighted correctly, and because of lifetimes inserting '\n' here is not
+                    // possible, also see `prepare()`.
+                    line_sections.push(
+                        highlighter
+                            .highlight_line(with_syntax, &config.syntax_set, max_scope_depth)
+                            .unwrap()
+                            .unwrap_or_else(|| vec![(config.null_syntect_style, with_syntax)]),
+                    );
+
+                    if !plain.is_empty() {
+                        line_sections
+                            .last_mut()
+                            .unwrap()
+                            .push((config.null_syntect_style, plain));
+                    }
+                }
+            }
+        }
+        _ => {
+            for (line, _, _) in lines.iter() {
+                line_sections.push(vec![(config.null_syntect_style, line.as_str())])
+            }
+        }
+    }
+    line_sections
+}
+
+/// Get background styles to represent diff for minus and plus lines in buffer.
+#[allow(clippy::type_complexity)]
+fn get_diff_style_sections<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State, LineInfo)>>,
+    config: &config::Config,
+) -> (
+    MinusPlus<Vec<LineSections<'a, Style>>>,
+    Vec<(Option<usize>, Option<usize>)>,
+) {
+    let (minus_lines, minus_styles): (Vec<&str>, Vec<Style>) = lines[Minus]
+        .iter()
+        .map(|(s, state, _)| (s.as_str(), *config.get_style(state)))
+        .unzip();
+    let (plus_lines, plus_styles): (Vec<&str>, Vec<Style>) = lines[Plus]
+        .iter()
+        .map(|(s, state, _)| (s.as_str(), *config.get_style(state)))
+        .unzip();
+    let (minus_line_diff_style_sections, plus_line_diff_style_sections, line_alignment) =
+        edits::infer_edits(
+            minus_lines,
+            plus_lines,
+            minus_styles,
+            config.minus_emph_style, // FIXME
+            plus_styles,
+            config.plus_emph_style, // FIXME
+            &config.tokenization_regex,
+            config.max_line_distance,
+            config.max_line_distance_for_naively_paired_lines,
+        );
+    let mut diff_sections = MinusPlus::new(
+        minus_line_diff_style_sections,
+        plus_line_diff_style_sections,
+    );
+    if config.emph_merge_gap > 0 {
+        for (minus_or_plus, emph_style) in [
+            (Minus, config.minus_emph_style),
+            (Plus, config.plus_emph_style),
+        ] {
+            for style_sections in diff_sections[minus_or_plus].iter_mut() {
+                edits::merge_emph_gaps(style_sections, emph_style, config.emph_merge_gap);
+            }
+        }
+    }
+    (diff_sections, line_alignment)
+}
+
+/// Return whether the plus lines are the minus lines in a different order.
+fn is_reordering(lines: &MinusPlus<&Vec<(String, State, LineInfo)>>) -> bool {
+    fn sorted(lines: &[(String, State, LineInfo)]) -> Vec<&str> {
+        let mut lines: Vec<&str> = lines.iter().map(|(line, _, _)| line.as_str()).collect();
+        lines.sort_unstable();
+        lines
+    }
+    lines[Minus].len() == lines[Plus].len()
+        && lines[Minus].len() > 1
+        && !lines[Minus]
+            .iter()
+            .zip(lines[Plus].iter())
+            .all(|((minus, _, _), (plus, _, _))| minus == plus)
+        && sorted(lines[Minus]) == sorted(lines[Plus])
+}
+
+/// Return style sections painting each line with `style` as a whole, and an alignment in which no
+/// lines are paired.
+#[allow(clippy::type_complexity)]
+fn get_unpaired_style_sections<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State, LineInfo)>>,
+    styles: MinusPlus<Style>,
+) -> (
+    MinusPlus<Vec<LineSections<'a, Style>>>,
+    Vec<(Option<usize>, Option<usize>)>,
+) {
+    let sections = |lines: &'a [(String, State, LineInfo)], style: Style| {
+        lines
+            .iter()
+            .map(|(line, _, _)| vec![(style, line.as_str())])
+            .collect()
+    };
+    let line_alignment = (0..lines[Minus].len())
+        .map(|i| (Some(i), None))
+        .chain((0..lines[Plus].len()).map(|i| (None, Some(i))))
+        .collect();
+    (
+        MinusPlus::new(
+            sections(lines[Minus], styles[Minus]),
+            sections(lines[Plus], styles[Plus]),
+        ),
+        line_alignment,
+    )
+}
+
+/// Return true if the removed and added lines are pairwise identical, and so are their
+/// indentations before tab expansion. Since delta strips carriage returns from the line endings of
+/// its input, such lines differed only in their line endings.
+fn is_line_ending_only_change(lines: MinusPlus<&[(String, State, LineInfo)]>) -> bool {
+    lines[Minus].len() == lines[Plus].len()
+        && lines[Minus].iter().zip(lines[Plus]).all(
+            |((minus, _, minus_info), (plus, _, plus_info))| {
+                minus == plus && minus_info.raw_indentation == plus_info.raw_indentation
+            },
+        )
+}
+
+/// Return whether the minus and plus lines are identical when whitespace is ignored.
+fn is_whitespace_only_change(
+    minus_lines: &[(String, State, LineInfo)],
+    plus_lines: &[(String, State, LineInfo)],
+) -> bool {
+    let non_whitespace = |lines: &[(String, State, LineInfo)]| {
+        lines
+            .iter()
+            .flat_map(|(line, _, _)| line.chars())
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+    };
+    non_whitespace(minus_lines) == non_whitespace(plus_lines)
+}
+
+/// Re-emphasize each pair of homologous lines with the characters that are not on a longest common
+/// subsequence of the two lines (see --emphasis-algorithm).
+fn apply_lcs_emphasis<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State, LineInfo)>>,
+    diff_style_sections: &mut MinusPlus<Vec<LineSections<'a, Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    config: &config::Config,
+) {
+    for &(minus_index, plus_index) in line_alignment {
+        let (Some(minus_index), Some(plus_index)) = (minus_index, plus_index) else {
+            continue;
+        };
+        if let (State::HunkMinus(_, Some(_)), _) | (_, State::HunkPlus(_, Some(_))) =
+            (&lines[Minus][minus_index].1, &lines[Plus][plus_index].1)
+        {
+            // The style sections were parsed from the raw line.
+            continue;
+        }
+        let indices = MinusPlus::new(minus_index, plus_index);
+        let Some(emph_ranges) =
+            edits::lcs_emphasis_ranges(&lines[Minus][minus_index].0, &lines[Plus][plus_index].0)
+        else {
+            continue;
+        };
+        for (minus_or_plus, emph_style) in [
+            (Minus, config.minus_emph_style),
+            (Plus, config.plus_emph_style),
+        ] {
+            let (line, state, _) = &lines[minus_or_plus][indices[minus_or_plus]];
+            diff_style_sections[minus_or_plus][indices[minus_or_plus]] = style_sections_from_ranges(
+                line,
+                &emph_ranges[minus_or_plus],
+                *config.get_style(state),
+                emph_style,
+            );
+        }
+    }
+}
+
+/// Split `line` into sections styled with `emph_style` within `ranges`, and with `style` elsewhere.
+/// Ranges that are out of bounds or do not fall on character boundaries are ignored.
+fn style_sections_from_ranges<'a>(
+    line: &'a str,
+    ranges: &[Range<usize>],
+    style: Style,
+    emph_style: Style,
+) -> LineSections<'a, Style> {
+    let mut ranges: Vec<_> = ranges
+        .iter()
+        .filter(|range| !range.is_empty() && line.get(range.start..range.end).is_some())
+        .cloned()
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    let mut sections = Vec::new();
+    let mut offset = 0;
+    for Range { start, end } in ranges {
+        let start = start.max(offset);
+        if start >= end {
+            continue;
+        }
+        if offset < start {
+            sections.push((style, &line[offset..start]));
+        }
+        sections.push((emph_style, &line[start..end]));
+        offset = end;
+    }
+    if offset < line.len() || sections.is_empty() {
+        sections.push((style, &line[offset..]));
+    }
+    sections
+}
+
+/// For each pair of homologous lines whose shared leading unemphasized text is longer than
+/// `max_width` characters, replace the middle of that text with an ellipsis in both lines.
+fn elide_common_prefixes<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State, LineInfo)>>,
+    syntax_style_sections: &mut MinusPlus<Vec<LineSections<'a, SyntectStyle>>>,
+    diff_style_sections: &mut MinusPlus<Vec<LineSections<'a, Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    max_width: usize,
+) {
+    let unemphasized_prefix_len = |sections: &LineSections<Style>| {
+        sections
+            .iter()
+            .take_while(|(style, _)| !style.is_emph)
+            .map(|(_, s)| s.len())
+            .sum::<usize>()
+    };
+    for &(minus_index, plus_index) in line_alignment {
+        let (Some(i), Some(j)) = (minus_index, plus_index) else {
+            continue;
+        };
+        let ((minus_line, minus_state, _), (plus_line, plus_state, _)) =
+            (&lines[Minus][i], &lines[Plus][j]);
+        if let (State::HunkMinus(_, Some(_)), _) | (_, State::HunkPlus(_, Some(_))) =
+            (minus_state, plus_state)
+        {
+            // The style sections were parsed from the raw line.
+            continue;
+        }
+        let max_len = unemphasized_prefix_len(&diff_style_sections[Minus][i])
+            .min(unemphasized_prefix_len(&diff_style_sections[Plus][j]));
+        // Byte offsets of the characters of the common prefix.
+        let prefix: Vec<usize> = minus_line
+            .char_indices()
+            .zip(plus_line.chars())
+            .take_while(|((offset, c), d)| c == d && *offset + c.len_utf8() <= max_len)
+            .map(|((offset, _), _)| offset)
+            .collect();
+        // An elided prefix must still be followed by a change.
+        if prefix.len() <= max_width || prefix.len() == minus_line.chars().count() {
+            continue;
+        }
+        let n_head = (max_width - 1) / 2;
+        let n_tail = max_width - 1 - n_head;
+        let start = prefix[n_head];
+        let end = prefix[prefix.len() - n_tail - 1]
+            + minus_line[prefix[prefix.len() - n_tail - 1]..]
+                .chars()
+                .next()
+                .unwrap()
+                .len_utf8();
+        elide_range(&mut syntax_style_sections[Minus][i], start, end);
+        elide_range(&mut syntax_style_sections[Plus][j], start, end);
+        elide_range(&mut diff_style_sections[Minus][i], start, end);
+        elide_range(&mut diff_style_sections[Plus][j], start, end);
+    }
+}
+
+/// Replace bytes `start..end` of the line covered by `sections` with an ellipsis, which takes
+/// the style of the section in which the replaced text starts.
+fn elide_range<S: Copy>(sections: &mut LineSections<S>, start: usize, end: usize) {
+    let mut elided = Vec::with_capacity(sections.len() + 1);
+    let mut offset = 0;
+    for (style, s) in sections.iter() {
+        let (section_start, section_end) = (offset, offset + s.len());
+        offset = section_end;
+        if section_start < start {
+            elided.push((*style, &s[..start.min(section_end) - section_start]));
+        }
+        if section_start <= start && start < section_end {
+            elided.push((*style, "…"));
+        }
+        if end < section_end {
+            elided.push((*style, &s[end.max(section_start) - section_start..]));
+        }
+    }
+    *sections = elided;
+}
+
+/// Return the character ranges of the line covered by `sections` whose style is an emphasis style.
+fn get_emphasized_char_ranges(sections: &LineSections<Style>) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut offset = 0;
+    for (style, s) in sections {
+        let (start, end) = (offset, offset + s.chars().count());
+        offset = end;
+        if !style.is_emph || start == end {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(range) if range.end == start => range.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+/// For each pair of homologous lines such that the plus line only inserts text into the minus
+/// line, insert a caret styled with `insertion_point_style` into the minus line at each position
+/// where text was inserted (see --insertion-point-style).
+fn mark_insertion_points<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State, LineInfo)>>,
+    syntax_style_sections: &mut MinusPlus<Vec<LineSections<'a, SyntectStyle>>>,
+    diff_style_sections: &mut MinusPlus<Vec<LineSections<'a, Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    insertion_point_style: Style,
+) {
+    let covers = |sections: &LineSections<Style>, line: &str| {
+        sections.iter().map(|(_, s)| s.len()).sum::<usize>() == line.len()
+    };
+    for &(minus_index, plus_index) in line_alignment {
+        let (Some(i), Some(j)) = (minus_index, plus_index) else {
+            continue;
+        };
+        let ((minus_line, minus_state, _), (plus_line, plus_state, _)) =
+            (&lines[Minus][i], &lines[Plus][j]);
+        if let (State::HunkMinus(_, Some(_)), _) | (_, State::HunkPlus(_, Some(_))) =
+            (minus_state, plus_state)
+        {
+            // The style sections were parsed from the raw line.
+            continue;
+        }
+        if diff_style_sections[Minus][i].iter().any(|(style, _)| style.is_emph)
+            // E.g. the common prefix of the lines has been elided.
+            || !covers(&diff_style_sections[Minus][i], minus_line)
+            || !covers(&diff_style_sections[Plus][j], plus_line)
+        {
+            continue;
+        }
+        // The plus line without its emphasized sections, and the corresponding positions in the
+        // minus line of its emphasized sections.
+        let mut unemphasized = String::with_capacity(minus_line.len());
+        let mut positions = Vec::new();
+        for (style, s) in &diff_style_sections[Plus][j] {
+            if !style.is_emph {
+                unemphasized.push_str(s);
+            } else if positions.last() != Some(&unemphasized.len()) {
+                positions.push(unemphasized.len());
+            }
+        }
+        if positions.is_empty() || unemphasized != *minus_line {
+            continue;
+        }
+        // Insert from the end, so that the earlier positions remain valid.
+        for &position in positions.iter().rev() {
+            insert_marker(&mut syntax_style_sections[Minus][i], position, None);
+            insert_marker(
+                &mut diff_style_sections[Minus][i],
+                position,
+                Some(insertion_point_style),
+            );
+        }
+    }
+}
+
+/// Insert a caret at byte `position` of the line covered by `sections`. It takes `style` if
+/// given, else the style of the text following it (or preceding it, at the end of the line).
+fn insert_marker<S: Copy>(sections: &mut LineSections<S>, position: usize, style: Option<S>) {
+    let mut offset = 0;
+    for k in 0..sections.len() {
+        let (section_style, s) = sections[k];
+        if position < offset + s.len() {
+            let split = position - offset;
+            let marker = (style.unwrap_or(section_style), "‸");
+            if split == 0 {
+                sections.insert(k, marker);
+            } else {
+                sections.splice(
+                    k..k + 1,
+                    [
+                        (section_style, &s[..split]),
+                        marker,
+                        (section_style, &s[split..]),
+                    ],
+                );
+            }
+            return;
+        }
+        offset += s.len();
+    }
+    if let Some(&(section_style, _)) = sections.last() {
+        sections.push((style.unwrap_or(section_style), "‸"));
+    }
+}
+
+/// A section of an --inline-edits line: a byte range of the minus or plus line, and its style.
+type InlineEditPiece = (MinusPlusIndex, usize, usize, Style);
+
+/// If the buffered lines are a single minus line paired with a single plus line, return the
+/// sections of the combined line: text removed from the minus line, text inserted in the plus
+/// line, and the text common to both.
+fn get_inline_edit_pieces(
+    lines: &MinusPlus<&Vec<(String, State, LineInfo)>>,
+    diff_style_sections: &MinusPlus<Vec<LineSections<Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    config: &config::Config,
+) -> Option<Vec<InlineEditPiece>> {
+    if line_alignment != [(Some(0), Some(0))] {
+        return None;
     }
-}
+    if let (State::HunkMinus(_, Some(_)), _) | (_, State::HunkPlus(_, Some(_))) =
+        (&lines[Minus][0].1, &lines[Plus][0].1)
+    {
+        return None;
+    }
+    // (is_emph, start, end) for the sections of each line.
+    let ranges = |sections: &LineSections<Style>| {
+        let mut offset = 0;
+        sections
+            .iter()
+            .map(|(style, s)| {
+                offset += s.len();
+                (style.is_emph, offset - s.len(), offset)
+            })
+            .collect::<Vec<_>>()
+    };
+    let (minus, plus) = (
+        ranges(&diff_style_sections[Minus][0]),
+        ranges(&diff_style_sections[Plus][0]),
+    );
+    let (minus_line, plus_line) = (&lines[Minus][0].0, &lines[Plus][0].0);
 
-// Remove initial -/+ characters, expand tabs as spaces, retaining ANSI sequences. Terminate with
-// newline character.
-pub fn prepare_raw_line(raw_line: &str, prefix_length: usize, config: &config::Config) -> String {
-    let mut line = tabs::expand(raw_line, &config.tab_cfg);
-    line.push('\n');
-    ansi::ansi_preserving_slice(&line, prefix_length)
+    let mut pieces = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let (mut minus_start, mut plus_start) = (0, 0);
+    loop {
+        while let Some(&(true, start, end)) = minus.get(i) {
+            pieces.push((Minus, start, end, config.minus_emph_style));
+            (i, minus_start) = (i + 1, end);
+        }
+        while let Some(&(true, start, end)) = plus.get(j) {
+            pieces.push((Plus, start, end, config.plus_emph_style));
+            (j, plus_start) = (j + 1, end);
+        }
+        let (Some(&(_, _, minus_end)), Some(&(_, _, plus_end))) = (minus.get(i), plus.get(j))
+        else {
+            break;
+        };
+        // Both lines continue with unchanged text, which must be the same in both.
+        let n = (minus_end - minus_start).min(plus_end - plus_start);
+        if minus_line[minus_start..minus_start + n] != plus_line[plus_start..plus_start + n] {
+            return None;
+        }
+        pieces.push((Plus, plus_start, plus_start + n, config.zero_style));
+        (minus_start, plus_start) = (minus_start + n, plus_start + n);
+        if minus_start == minus_end {
+            i += 1;
+        }
+        if plus_start == plus_end {
+            j += 1;
+        }
+    }
+    if i < minus.len() || j < plus.len() {
+        return None;
+    }
+    Some(pieces)
 }
 
-pub fn paint_minus_and_plus_lines(
-    lines: MinusPlus<&Vec<(String, State)>>,
+/// Paint a minus line and its paired plus line as a single line, as described by `pieces`.
+fn paint_inline_edit<E: Emitter>(
+    lines: &MinusPlus<&Vec<(String, State, LineInfo)>>,
+    syntax_style_sections: &MinusPlus<Vec<LineSections<SyntectStyle>>>,
+    pieces: &[InlineEditPiece],
     line_numbers_data: &mut Option<LineNumbersData>,
-    highlighter: &mut Option<HighlightLines>,
     output_buffer: &mut String,
+    emitter: &E,
     config: &config::Config,
 ) {
-    let syntax_style_sections = MinusPlus::new(
-        get_syntax_style_sections_for_lines(lines[Minus], highlighter.as_mut(), config),
-        get_syntax_style_sections_for_lines(lines[Plus], highlighter.as_mut(), config),
-    );
-    let (mut diff_style_sections, line_alignment) = get_diff_style_sections(&lines, config);
-    let lines_have_homolog = edits::make_lines_have_homolog(&line_alignment);
-    Painter::update_diff_style_sections(
-        lines[Minus],
-        &mut diff_style_sections[Minus],
+    let mut syntax_sections = Vec::new();
+    let mut diff_sections = Vec::new();
+    for &(minus_or_plus, start, end, style) in pieces {
+        let line = &lines[minus_or_plus][0].0;
+        diff_sections.push((style, &line[start..end]));
+        let mut offset = 0;
+        for (syntax_style, s) in &syntax_style_sections[minus_or_plus][0] {
+            let (section_start, section_end) = (offset, offset + s.len());
+            offset = section_end;
+            let (start, end) = (start.max(section_start), end.min(section_end));
+            if start < end {
+                syntax_sections.push((*syntax_style, &line[start..end]));
+            }
+        }
+    }
+    let plus_state = &lines[Plus][0].1;
+    // The combined line represents both the minus and the plus line, as an unchanged line does.
+    let state = match plus_state {
+        State::HunkPlus(diff_type, _) => State::HunkZero(diff_type.clone(), None),
+        _ => delta_unreachable("Unexpected state in paint_inline_edit."),
+    };
+    let (mut line, _) = Painter::paint_line(
+        &syntax_sections,
+        &diff_sections,
+        &state,
+        &mut line_numbers_data.as_mut(),
         None,
-        if config.minus_non_emph_style != config.minus_emph_style {
-            Some(config.minus_non_emph_style)
-        } else {
-            None
-        },
-        &lines_have_homolog[Minus],
+        painted_gutter_or_prefix(plus_state, true, config),
+        emitter,
         config,
     );
-    Painter::update_diff_style_sections(
-        lines[Plus],
-        &mut diff_style_sections[Plus],
-        Some(config.whitespace_error_style),
-        if config.plus_non_emph_style != config.plus_emph_style {
-            Some(config.plus_non_emph_style)
-        } else {
-            None
-        },
-        &lines_have_homolog[Plus],
+    let (bg_fill_mode, fill_style) = Painter::get_should_right_fill_background_color_and_fill_style(
+        &diff_sections,
+        None,
+        None,
+        &state,
+        BgShouldFill::default(),
         config,
     );
-    if config.side_by_side {
-        side_by_side::paint_minus_and_plus_lines_side_by_side(
-            lines,
-            syntax_style_sections,
-            diff_style_sections,
-            lines_have_homolog,
-            line_alignment,
-            line_numbers_data,
-            output_buffer,
-            config,
-        )
-    } else {
-        // Unified diff mode:
-        if !lines[Minus].is_empty() {
-            Painter::paint_lines(
-                lines[Minus],
-                &syntax_style_sections[Minus],
-                &diff_style_sections[Minus],
-                &lines_have_homolog[Minus],
-                output_buffer,
-                config,
-                &mut line_numbers_data.as_mut(),
-                Some(config.minus_empty_line_marker_style),
-                BgShouldFill::default(),
-            );
+    if let Some(BgFillMethod::TryAnsiSequence) = bg_fill_mode {
+        Painter::right_fill_background_color(&mut line, fill_style);
+    }
+    output_buffer.push_str(&line);
+    output_buffer.push('\n');
+}
+
+/// Append the --density-column `shade` to the painted `line`, in the last column of the
+/// decorations width if that is fixed, or else directly after the line.
+fn append_density_shade<E: Emitter>(
+    line: &mut String,
+    shade: char,
+    emitter: &E,
+    config: &config::Config,
+) {
+    if let cli::Width::Fixed(width) = config.decorations_width {
+        let padding = " ".repeat(width.saturating_sub(emitter.text_width(line) + 1));
+        // Pad before an ANSI background fill, so that the padding takes on the fill color.
+        match line.rfind(ansi::ANSI_CSI_CLEAR_TO_EOL) {
+            Some(i) => line.insert_str(i, &padding),
+            None => line.push_str(&padding),
+        }
+    }
+    line.push_str(&emitter.paint(ansi_term::Style::new(), &shade.to_string()));
+}
+
+/// Extend the emphasized sections of each line to the boundaries of the tokens matching
+/// `emph_token_regex` that they overlap, giving the rest of each such token the style of its first
+/// emphasized byte.
+fn snap_emphasis_to_tokens<'a>(
+    lines: &'a [(String, State, LineInfo)],
+    diff_style_sections: &mut [LineSections<'a, Style>],
+    emph_token_regex: &Regex,
+) {
+    for ((line, _, _), style_sections) in lines.iter().zip_eq(diff_style_sections) {
+        let mut byte_styles = style_sections
+            .iter()
+            .flat_map(|(style, s)| std::iter::repeat_n(*style, s.len()))
+            .collect::<Vec<_>>();
+        let mut snapped = false;
+        for token in emph_token_regex.find_iter(line) {
+            let token_styles = &mut byte_styles[token.range()];
+            let Some(emph_style) = token_styles.iter().find(|style| style.is_emph).copied() else {
+                continue;
+            };
+            for style in token_styles.iter_mut().filter(|style| !style.is_emph) {
+                *style = emph_style;
+                snapped = true;
+            }
+        }
+        if !snapped {
+            continue;
+        }
+        style_sections.clear();
+        let mut start = 0;
+        for end in 1..=line.len() {
+            if end == line.len() || byte_styles[end] != byte_styles[start] {
+                style_sections.push((byte_styles[start], &line[start..end]));
+                start = end;
+            }
+        }
+    }
+}
+
+/// Remove emphasis from sections that lie inside (--comment-emphasis=suppress) or outside
+/// (--comment-emphasis=only) code comments.
+fn apply_comment_emphasis<'a>(
+    lines: &'a [(String, State, LineInfo)],
+    diff_style_sections: &mut [LineSections<'a, Style>],
+    syntax: &SyntaxReference,
+    config: &config::Config,
+) {
+    for ((line, state, _), style_sections) in lines.iter().zip_eq(diff_style_sections) {
+        if !style_sections.iter().any(|(style, _)| style.is_emph) {
+            continue;
+        }
+        let comment_ranges = get_comment_ranges(line, syntax, &config.syntax_set);
+        let mut offset = 0;
+        for (style, s) in style_sections.iter_mut() {
+            let (start, end) = (offset, offset + s.len());
+            offset = end;
+            if !style.is_emph || s.trim().is_empty() {
+                continue;
+            }
+            let in_comment = comment_ranges
+                .iter()
+                .any(|(comment_start, comment_end)| *comment_start <= start && end <= *comment_end);
+            if in_comment == (config.comment_emphasis == CommentEmphasis::Suppress) {
+                *style = *config.get_style(state);
+            }
+        }
+    }
+}
+
+/// For each pair of lines differing only in a trailing comment, emphasize just the comment, with
+/// `trailing_comment_emph_style` (see --trailing-comment-emph-style).
+fn apply_trailing_comment_emphasis<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State, LineInfo)>>,
+    diff_style_sections: &mut MinusPlus<Vec<LineSections<'a, Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    trailing_comment_emph_style: Style,
+    syntax: &SyntaxReference,
+    config: &config::Config,
+) {
+    for &(minus_index, plus_index) in line_alignment {
+        let (Some(minus_index), Some(plus_index)) = (minus_index, plus_index) else {
+            continue;
+        };
+        let indices = MinusPlus::new(minus_index, plus_index);
+        let comment_starts = MinusPlus::new(
+            get_trailing_comment_start(&lines[Minus][minus_index].0, syntax, &config.syntax_set),
+            get_trailing_comment_start(&lines[Plus][plus_index].0, syntax, &config.syntax_set),
+        );
+        if comment_starts[Minus].is_none() && comment_starts[Plus].is_none() {
+            continue;
+        }
+        let code = |minus_or_plus: MinusPlusIndex| {
+            let line = &lines[minus_or_plus][indices[minus_or_plus]].0;
+            line[..comment_starts[minus_or_plus].unwrap_or(line.len())].trim_end()
+        };
+        if code(Minus) != code(Plus) {
+            continue;
+        }
+        for minus_or_plus in [Minus, Plus] {
+            let Some(comment_start) = comment_starts[minus_or_plus] else {
+                continue;
+            };
+            let (_, state, _) = &lines[minus_or_plus][indices[minus_or_plus]];
+            let non_emph_style = *config.get_style(state);
+            let style_sections = &mut diff_style_sections[minus_or_plus][indices[minus_or_plus]];
+            let mut restyled_sections = Vec::with_capacity(style_sections.len() + 1);
+            let mut offset = 0;
+            for (style, s) in style_sections.drain(..) {
+                let end = offset + s.len();
+                if !style.is_emph {
+                    restyled_sections.push((style, s));
+                } else if comment_start <= offset {
+                    restyled_sections.push((trailing_comment_emph_style, s));
+                } else if comment_start < end {
+                    let (code, comment) = s.split_at(comment_start - offset);
+                    restyled_sections.push((non_emph_style, code));
+                    restyled_sections.push((trailing_comment_emph_style, comment));
+                } else {
+                    // Since the code is unchanged, this can only be whitespace.
+                    restyled_sections.push((non_emph_style, s));
+                }
+                offset = end;
+            }
+            *style_sections = restyled_sections;
+        }
+    }
+}
+
+/// Return the byte offset at which a comment ending `line` starts, if `line` ends with a comment
+/// preceded by code.
+fn get_trailing_comment_start(
+    line: &str,
+    syntax: &SyntaxReference,
+    syntax_set: &SyntaxSet,
+) -> Option<usize> {
+    let (start, end) = *get_comment_ranges(line, syntax, syntax_set).last()?;
+    let is_trailing = line[end..].trim().is_empty() && !line[..start].trim().is_empty();
+    is_trailing.then_some(start)
+}
+
+/// Restyle with `formatting_emph_style` the emphasized sections of each pair of lines that do not
+/// differ after normalizing case and whitespace (see --formatting-emph-style).
+fn apply_formatting_emphasis<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State, LineInfo)>>,
+    diff_style_sections: &mut MinusPlus<Vec<LineSections<'a, Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    formatting_emph_style: Style,
+    config: &config::Config,
+) {
+    for &(minus_index, plus_index) in line_alignment {
+        let (Some(minus_index), Some(plus_index)) = (minus_index, plus_index) else {
+            continue;
+        };
+        let indices = MinusPlus::new(minus_index, plus_index);
+        let normalized = MinusPlus::new(
+            edits::normalize_case_and_whitespace(&lines[Minus][minus_index].0),
+            edits::normalize_case_and_whitespace(&lines[Plus][plus_index].0),
+        );
+        // The lines are already known to be paired, so a maximum distance of 1 forces pairing.
+        let (normalized_minus_sections, normalized_plus_sections, _) = edits::infer_edits(
+            vec![normalized[Minus].0.as_str()],
+            vec![normalized[Plus].0.as_str()],
+            vec![false],
+            true,
+            vec![false],
+            true,
+            &config.tokenization_regex,
+            1.0,
+            1.0,
+        );
+        let normalized_sections =
+            MinusPlus::new(&normalized_minus_sections[0], &normalized_plus_sections[0]);
+        for minus_or_plus in [Minus, Plus] {
+            let line = &lines[minus_or_plus][indices[minus_or_plus]].0;
+            let offsets = &normalized[minus_or_plus].1;
+            // Whether each byte of the line begins a character that differs after normalization.
+            let mut is_meaningful = vec![false; line.len()];
+            let mut offset = 0;
+            for (is_emph, s) in normalized_sections[minus_or_plus] {
+                if *is_emph {
+                    for &i in &offsets[offset..offset + s.len()] {
+                        is_meaningful[i] = true;
+                    }
+                }
+                offset += s.len();
+            }
+            let mut offset = 0;
+            for (style, s) in diff_style_sections[minus_or_plus][indices[minus_or_plus]].iter_mut()
+            {
+                let (start, end) = (offset, offset + s.len());
+                offset = end;
+                if style.is_emph && !is_meaningful[start..end].contains(&true) {
+                    *style = formatting_emph_style;
+                }
+            }
+        }
+    }
+}
+
+/// Paint with `indent_normalization_style` the paired lines that differ only in the composition of
+/// their indentation, which has the same width but mixes tabs and spaces differently.
+fn apply_indent_normalization_style<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State, LineInfo)>>,
+    diff_style_sections: &mut MinusPlus<Vec<LineSections<'a, Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    indent_normalization_style: Style,
+    config: &config::Config,
+) {
+    // Tabs that are displayed as such are most likely rendered with the usual tab stops.
+    let tab_width = match config.tab_cfg.width() {
+        0 => 8,
+        width => width,
+    };
+    for &(minus_index, plus_index) in line_alignment {
+        let (Some(minus_index), Some(plus_index)) = (minus_index, plus_index) else {
+            continue;
+        };
+        let indices = MinusPlus::new(minus_index, plus_index);
+        let indentations = MinusPlus::new(
+            &lines[Minus][minus_index].2.raw_indentation,
+            &lines[Plus][plus_index].2.raw_indentation,
+        );
+        if indentations[Minus] == indentations[Plus]
+            || tabs::indentation_width(indentations[Minus], tab_width)
+                != tabs::indentation_width(indentations[Plus], tab_width)
+            || lines[Minus][minus_index].0.trim_start() != lines[Plus][plus_index].0.trim_start()
+        {
+            continue;
+        }
+        for minus_or_plus in [Minus, Plus] {
+            let line = &lines[minus_or_plus][indices[minus_or_plus]].0;
+            diff_style_sections[minus_or_plus][indices[minus_or_plus]] =
+                vec![(indent_normalization_style, line.as_str())];
+        }
+    }
+}
+
+/// Restyle with `rename_emph_style` the emphasized sections of paired lines that lie within a token
+/// renamed according to `renames` (--rename-map, or both directions of --paired-tokens), provided
+/// that the token is no longer emphasized once the renames are applied to the minus line, i.e. that
+/// it is aligned with its new name.
+fn apply_rename_emphasis<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State, LineInfo)>>,
+    diff_style_sections: &mut MinusPlus<Vec<LineSections<'a, Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    renames: &[(String, String)],
+    rename_emph_style: Style,
+    config: &config::Config,
+) {
+    let has_emph_in = |sections: &[(bool, &str)], range: &Range<usize>| {
+        let mut offset = 0;
+        sections.iter().any(|(is_emph, s)| {
+            let (start, end) = (offset, offset + s.len());
+            offset = end;
+            *is_emph && start < range.end && range.start < end
+        })
+    };
+    for &(minus_index, plus_index) in line_alignment {
+        let (Some(minus_index), Some(plus_index)) = (minus_index, plus_index) else {
+            continue;
+        };
+        let indices = MinusPlus::new(minus_index, plus_index);
+        let (minus_line, plus_line) = (&lines[Minus][minus_index].0, &lines[Plus][plus_index].0);
+        let (renamed_minus_line, renamed_tokens) =
+            edits::apply_renames(minus_line, &config.tokenization_regex, renames);
+        if renamed_tokens.is_empty() {
+            continue;
+        }
+        // The lines are already known to be paired, so a maximum distance of 1 forces pairing.
+        let (renamed_minus_sections, plus_sections, _) = edits::infer_edits(
+            vec![renamed_minus_line.as_str()],
+            vec![plus_line.as_str()],
+            vec![false],
+            true,
+            vec![false],
+            true,
+            &config.tokenization_regex,
+            1.0,
+            1.0,
+        );
+        let minus_ranges = renamed_tokens
+            .into_iter()
+            .filter(|(_, renamed)| !has_emph_in(&renamed_minus_sections[0], renamed))
+            .map(|(original, _)| original)
+            .collect::<Vec<_>>();
+        let plus_ranges = config
+            .tokenization_regex
+            .find_iter(plus_line)
+            .filter(|token| renames.iter().any(|(_, new)| new == token.as_str()))
+            .map(|token| token.range())
+            .filter(|range| !has_emph_in(&plus_sections[0], range))
+            .collect::<Vec<_>>();
+        for (minus_or_plus, ranges) in [(Minus, minus_ranges), (Plus, plus_ranges)] {
+            let mut offset = 0;
+            for (style, s) in diff_style_sections[minus_or_plus][indices[minus_or_plus]].iter_mut()
+            {
+                let start = offset + (s.len() - s.trim_start().len());
+                let end = offset + s.trim_end().len();
+                offset += s.len();
+                if style.is_emph
+                    && start < end
+                    && ranges
+                        .iter()
+                        .any(|range| range.start <= start && end <= range.end)
+                {
+                    *style = rename_emph_style;
+                }
+            }
+        }
+    }
+}
+
+/// Restyle with `emph_style` the emphasized sections `line[start..end]` for which
+/// `is_match(line, start, end)` is true.
+fn restyle_emph_sections<'a>(
+    lines: &'a [(String, State, LineInfo)],
+    diff_style_sections: &mut [LineSections<'a, Style>],
+    emph_style: Style,
+    is_match: impl Fn(&str, usize, usize) -> bool,
+) {
+    for ((line, _, _), style_sections) in lines.iter().zip_eq(diff_style_sections) {
+        let mut offset = 0;
+        for (style, s) in style_sections.iter_mut() {
+            let (start, end) = (offset, offset + s.len());
+            offset = end;
+            if style.is_emph && is_match(line, start, end) {
+                *style = emph_style;
+            }
+        }
+    }
+}
+
+/// Return true if the non-whitespace content of `section` consists of punctuation only.
+fn is_punctuation_change(section: &str) -> bool {
+    let trimmed = section.trim();
+    !trimmed.is_empty() && trimmed.chars().all(|c| c.is_ascii_punctuation())
+}
+
+/// Return true if the non-whitespace content of `line[start..end]` consists of operator
+/// characters only, and the operator token it belongs to is one of `operator_tokens`.
+fn is_operator_change(line: &str, start: usize, end: usize, operator_tokens: &[String]) -> bool {
+    let is_operator_char =
+        |c: char| c.is_ascii_punctuation() && !"()[]{},;:.'\"`_#@$\\".contains(c);
+    let section = &line[start..end];
+    let trimmed = section.trim();
+    if trimmed.is_empty() || !trimmed.chars().all(is_operator_char) {
+        return false;
+    }
+    let trimmed_start = start + (section.len() - section.trim_start().len());
+    let trimmed_end = trimmed_start + trimmed.len();
+    let token_start = line[..trimmed_start]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_operator_char(*c))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let token_end = line[trimmed_end..]
+        .find(|c| !is_operator_char(c))
+        .map_or(line.len(), |i| trimmed_end + i);
+    let token = &line[token_start..token_end];
+    operator_tokens.iter().any(|operator| operator == token)
+}
+
+/// Split each run of emphasized sections into sections of one character, with background colors
+/// going from `from` for the first character of the run to `to` for its last (see
+/// --emph-gradient).
+fn apply_emph_gradient<'a>(
+    style_sections: LineSections<'a, Style>,
+    from: ansi_term::Color,
+    to: ansi_term::Color,
+    config: &config::Config,
+) -> LineSections<'a, Style> {
+    let mut gradient_sections = Vec::with_capacity(style_sections.len());
+    for (is_emph, run) in &style_sections
+        .into_iter()
+        .group_by(|(style, _)| style.is_emph)
+    {
+        if !is_emph {
+            gradient_sections.extend(run);
+            continue;
+        }
+        let run: Vec<_> = run.collect();
+        let n_chars: usize = run.iter().map(|(_, s)| s.chars().count()).sum();
+        let mut i = 0;
+        for (style, s) in run {
+            for (offset, c) in s.char_indices() {
+                let t = if n_chars > 1 {
+                    i as f64 / (n_chars - 1) as f64
+                } else {
+                    0.0
+                };
+                let mut style = style;
+                style.ansi_term_style.background = Some(color::mix(from, to, t, config.true_color));
+                gradient_sections.push((style, &s[offset..offset + c.len_utf8()]));
+                i += 1;
+            }
+        }
+    }
+    gradient_sections
+}
+
+/// Restyle the brackets enclosing the emphasized sections of each line with
+/// `matching_bracket_style`, keeping the colors of the original sections where the style does not
+/// specify any.
+fn apply_matching_bracket_style<'a>(
+    lines: &'a [(String, State, LineInfo)],
+    diff_style_sections: &mut [LineSections<'a, Style>],
+    matching_bracket_style: Style,
+) {
+    for ((line, _, _), style_sections) in lines.iter().zip_eq(diff_style_sections) {
+        let mut emph_range = None;
+        let mut offset = 0;
+        for (style, s) in style_sections.iter() {
+            if style.is_emph && !s.trim().is_empty() {
+                let start = emph_range.map_or(offset, |(start, _)| start);
+                emph_range = Some((start, offset + s.len()));
+            }
+            offset += s.len();
+        }
+        let Some((open, close)) =
+            emph_range.and_then(|(start, end)| find_enclosing_brackets(line, start, end))
+        else {
+            continue;
+        };
+        let mut restyled_sections = Vec::with_capacity(style_sections.len() + 4);
+        let mut offset = 0;
+        for (style, mut s) in style_sections.drain(..) {
+            let end = offset + s.len();
+            for bracket in [open, close] {
+                if offset <= bracket && bracket < end {
+                    let (before, rest) = s.split_at(bracket - offset);
+                    let (bracket_str, after) = rest.split_at(1);
+                    if !before.is_empty() {
+                        restyled_sections.push((style, before));
+                    }
+                    let mut bracket_style = matching_bracket_style;
+                    let ansi_term_style = &mut bracket_style.ansi_term_style;
+                    ansi_term_style.foreground = ansi_term_style
+                        .foreground
+                        .or(style.ansi_term_style.foreground);
+                    ansi_term_style.background = ansi_term_style
+                        .background
+                        .or(style.ansi_term_style.background);
+                    restyled_sections.push((bracket_style, bracket_str));
+                    s = after;
+                    offset = bracket + 1;
+                }
+            }
+            if !s.is_empty() {
+                restyled_sections.push((style, s));
+            }
+            offset = end;
+        }
+        *style_sections = restyled_sections;
+    }
+}
+
+/// Return the byte offsets of the innermost pair of matching brackets enclosing `line[start..end]`,
+/// or None if there is no such pair, e.g. because the brackets around it are unbalanced.
+fn find_enclosing_brackets(line: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+    const PAIRS: [(u8, u8); 3] = [(b'(', b')'), (b'[', b']'), (b'{', b'}')];
+    let bytes = line.as_bytes();
+    // Brackets opened (scanning backwards: closed) but not yet matched.
+    let mut pending = Vec::new();
+    let mut open = None;
+    for i in (0..end).rev() {
+        if let Some(&(opening, _)) = PAIRS.iter().find(|(_, closing)| *closing == bytes[i]) {
+            pending.push(opening);
+        } else if PAIRS.iter().any(|(opening, _)| *opening == bytes[i]) {
+            match pending.pop() {
+                Some(opening) if opening == bytes[i] => {}
+                Some(_) => return None,
+                None if i < start => {
+                    open = Some(i);
+                    break;
+                }
+                None => return None,
+            }
+        }
+    }
+    let open = open?;
+    let expected_closing = PAIRS.iter().find(|(opening, _)| *opening == bytes[open])?.1;
+    for (i, &byte) in bytes.iter().enumerate().skip(open + 1) {
+        if let Some(&(_, closing)) = PAIRS.iter().find(|(opening, _)| *opening == byte) {
+            pending.push(closing);
+        } else if PAIRS.iter().any(|(_, closing)| *closing == byte) {
+            match pending.pop() {
+                Some(closing) if closing == byte => {}
+                Some(_) => return None,
+                None if i >= end && byte == expected_closing => return Some((open, i)),
+                None => return None,
+            }
+        }
+    }
+    None
+}
+
+/// Return the byte ranges of `line` that lie within a comment scope.
+fn get_comment_ranges(
+    line: &str,
+    syntax: &SyntaxReference,
+    syntax_set: &SyntaxSet,
+) -> Vec<(usize, usize)> {
+    let comment_scope = Scope::new("comment").unwrap();
+    let ops = match ParseState::new(syntax).parse_line(line, syntax_set) {
+        Ok(ops) => ops,
+        Err(_) => return Vec::new(),
+    };
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut scope_stack = ScopeStack::new();
+    let mut segment_start = 0;
+    for (position, op) in ops
+        .iter()
+        .map(|(position, op)| (*position, Some(op)))
+        .chain([(line.len(), None)])
+    {
+        // The scope stack applies to the segment of the line preceding this op.
+        let in_comment = scope_stack
+            .as_slice()
+            .iter()
+            .any(|scope| comment_scope.is_prefix_of(*scope));
+        if in_comment && position > segment_start {
+            match ranges.last_mut() {
+                Some((_, end)) if *end == segment_start => *end = position,
+                _ => ranges.push((segment_start, position)),
+            }
+        }
+        segment_start = position;
+        if let Some(op) = op {
+            if scope_stack.apply(op).is_err() {
+                return Vec::new();
+            }
+        }
+    }
+    ranges
+}
+
+/// Restyle with `scope_change_emph_style` the emphasized text of each pair of lines that replaces
+/// text of another kind of syntax (see --scope-change-emph-style).
+fn apply_scope_change_emphasis<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State, LineInfo)>>,
+    diff_style_sections: &mut MinusPlus<Vec<LineSections<'a, Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    scope_change_emph_style: Style,
+    syntax: &SyntaxReference,
+    config: &config::Config,
+) {
+    // The runs of consecutive emphasized sections of a line, as (offset of the run in the text the
+    // lines have in common, byte range of the run in the line).
+    let emph_runs = |sections: &LineSections<Style>| {
+        let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+        let (mut common_offset, mut offset) = (0, 0);
+        for (style, s) in sections {
+            if !style.is_emph {
+                common_offset += s.len();
+            } else if let Some((_, run)) = runs
+                .last_mut()
+                .filter(|(run_offset, run)| *run_offset == common_offset && run.end == offset)
+            {
+                run.end += s.len();
+            } else {
+                runs.push((common_offset, offset..offset + s.len()));
+            }
+            offset += s.len();
+        }
+        runs
+    };
+    for &(minus_index, plus_index) in line_alignment {
+        let (Some(minus_index), Some(plus_index)) = (minus_index, plus_index) else {
+            continue;
+        };
+        let indices = MinusPlus::new(minus_index, plus_index);
+        let runs = MinusPlus::new(
+            emph_runs(&diff_style_sections[Minus][minus_index]),
+            emph_runs(&diff_style_sections[Plus][plus_index]),
+        );
+        if runs[Minus].is_empty() || runs[Plus].is_empty() {
+            continue;
+        }
+        let scope_kinds = MinusPlus::new(
+            get_scope_kinds(&lines[Minus][minus_index].0, syntax, &config.syntax_set),
+            get_scope_kinds(&lines[Plus][plus_index].0, syntax, &config.syntax_set),
+        );
+        let mut restyled = MinusPlus::new(Vec::new(), Vec::new());
+        for (minus_offset, minus_run) in &runs[Minus] {
+            // Text replaced by other text, rather than only deleted.
+            let Some((_, plus_run)) = runs[Plus]
+                .iter()
+                .find(|(plus_offset, _)| plus_offset == minus_offset)
+            else {
+                continue;
+            };
+            let run_ranges = MinusPlus::new(minus_run, plus_run);
+            let kinds = [Minus, Plus].map(|minus_or_plus| {
+                let line = &lines[minus_or_plus][indices[minus_or_plus]].0;
+                let run = run_ranges[minus_or_plus];
+                let start =
+                    run.start + line[run.clone()].len() - line[run.clone()].trim_start().len();
+                scope_kinds[minus_or_plus]
+                    .iter()
+                    .find(|(range, _)| range.contains(&start))
+                    .map(|(_, kind)| kind)
+            });
+            if let [Some(minus_kind), Some(plus_kind)] = &kinds {
+                if minus_kind != plus_kind {
+                    restyled[Minus].push(minus_run.clone());
+                    restyled[Plus].push(plus_run.clone());
+                }
+            }
+        }
+        for minus_or_plus in [Minus, Plus] {
+            let mut offset = 0;
+            for (style, s) in diff_style_sections[minus_or_plus][indices[minus_or_plus]].iter_mut()
+            {
+                let start = offset;
+                offset += s.len();
+                if style.is_emph
+                    && restyled[minus_or_plus]
+                        .iter()
+                        .any(|run| run.contains(&start))
+                {
+                    *style = scope_change_emph_style;
+                }
+            }
+        }
+    }
+}
+
+/// Return the byte ranges of `line` and the kind of syntax of each, i.e. the first component of
+/// the innermost scope applying to it (e.g. "keyword" for "keyword.control.rust").
+fn get_scope_kinds(
+    line: &str,
+    syntax: &SyntaxReference,
+    syntax_set: &SyntaxSet,
+) -> Vec<(Range<usize>, String)> {
+    let ops = match ParseState::new(syntax).parse_line(line, syntax_set) {
+        Ok(ops) => ops,
+        Err(_) => return Vec::new(),
+    };
+    let mut kinds = Vec::new();
+    let mut scope_stack = ScopeStack::new();
+    let mut segment_start = 0;
+    for (position, op) in ops
+        .iter()
+        .map(|(position, op)| (*position, Some(op)))
+        .chain([(line.len(), None)])
+    {
+        // The scope stack applies to the segment of the line preceding this op.
+        if let Some(scope) = scope_stack.as_slice().last() {
+            if position > segment_start {
+                let scope = scope.build_string();
+                let kind = scope.split('.').next().unwrap_or_default().to_string();
+                kinds.push((segment_start..position, kind));
+            }
+        }
+        segment_start = position;
+        if let Some(op) = op {
+            if scope_stack.apply(op).is_err() {
+                return Vec::new();
+            }
+        }
+    }
+    kinds
+}
+
+/// Set `is_tab_symbol` on the styles of the text of the `sections` of `line` which is within the
+/// byte ranges `tab_symbols`, splitting sections as needed. The text of a section is located by its
+/// address, so that sections not taken from the line, such as the ellipsis of an elided prefix,
+/// are left as they are.
+fn mark_tab_symbols<'a>(
+    line: &str,
+    sections: &mut LineSections<'a, Style>,
+    tab_symbols: &[Range<usize>],
+) {
+    if tab_symbols.is_empty() {
+        return;
+    }
+    let mut marked = Vec::with_capacity(sections.len() + 2 * tab_symbols.len());
+    for (style, text) in sections.drain(..) {
+        let start = (text.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+        if start >= line.len() {
+            marked.push((style, text));
+            continue;
+        }
+        let end = start + text.len();
+        let mut unmarked_start = start;
+        for range in tab_symbols
+            .iter()
+            .filter(|range| range.start < end && range.end > start)
+        {
+            let (symbol_start, symbol_end) = (range.start.max(start), range.end.min(end));
+            if symbol_start > unmarked_start {
+                marked.push((style, &text[unmarked_start - start..symbol_start - start]));
+            }
+            let symbol_style = Style {
+                is_tab_symbol: true,
+                ..style
+            };
+            marked.push((
+                symbol_style,
+                &text[symbol_start - start..symbol_end - start],
+            ));
+            unmarked_start = symbol_end;
         }
-        if !lines[Plus].is_empty() {
-            Painter::paint_lines(
-                lines[Plus],
-                &syntax_style_sections[Plus],
-                &diff_style_sections[Plus],
-                &lines_have_homolog[Plus],
-                output_buffer,
-                config,
-                &mut line_numbers_data.as_mut(),
-                Some(config.plus_empty_line_marker_style),
-                BgShouldFill::default(),
-            );
+        if unmarked_start < end {
+            marked.push((style, &text[unmarked_start - start..]));
         }
     }
+    *sections = marked;
 }
 
-pub fn get_syntax_style_sections_for_lines<'a>(
-    lines: &'a [(String, State)],
-    highlighter: Option<&mut HighlightLines>,
-    config: &config::Config,
-) -> Vec<LineSections<'a, SyntectStyle>> {
-    let mut line_sections = Vec::new();
-    match (
-        highlighter,
-        lines
-            .iter()
-            .any(|(_, state)| Painter::should_compute_syntax_highlighting(state, config)),
-    ) {
-        (Some(highlighter), true) => {
-            for (line, _) in lines.iter() {
-                // Fast but simple length comparison. Overcounts non-printable ansi
-                // characters or wider UTF-8, but `truncate_str_short` in the
-                // else branch corrects that.
-                if line.len() < config.max_syntax_length || config.max_syntax_length == 0 {
-                    line_sections.push(
-                        highlighter
-                            .highlight_line(line, &config.syntax_set)
-                            .unwrap(),
-                    );
-                } else {
-                    let line_syntax = ansi::truncate_str_short(line, config.max_syntax_length);
-                    // Re-split to get references into `line` with correct lifetimes.
-                    // SAFETY: slicing the string is safe because `truncate_str_short` always
-                    // returns a prefix of the input and only cuts at grapheme borders.
-                    let (with_syntax, plain) = line.split_at(line_syntax.len());
-                    // Note: splitting a line and only feeding one half to the highlighter may
-                    // result in wrong highlighting until it is reset the next hunk.
-                    //
-                    // Also, as lines are no longer newline terminated they might not be
-                    // highlighted correctly, and because of lifetimes inserting '\n' here is not
-                    // possible, also see `prepare()`.
-                    line_sections.push(
-                        highlighter
-                            .highlight_line(with_syntax, &config.syntax_set)
-                            .unwrap(),
-                    );
-
-                    if !plain.is_empty() {
-                        line_sections
-                            .last_mut()
-                            .unwrap()
-                            .push((config.null_syntect_style, plain));
-                    }
+/// Replace with a guide each leading space of the line covered by `sections` at a column that is
+/// a multiple of `level_width` (see --indent-guide-style).
+fn draw_indent_guides(
+    sections: Vec<(Style, String)>,
+    level_width: usize,
+    indent_guide_style: Style,
+) -> Vec<(Style, String)> {
+    let mut drawn = Vec::with_capacity(sections.len());
+    let mut column = 0;
+    let mut in_indentation = true;
+    for (style, text) in sections {
+        if !in_indentation {
+            drawn.push((style, text));
+            continue;
+        }
+        let mut guide_style = style;
+        guide_style.ansi_term_style.foreground = indent_guide_style.ansi_term_style.foreground;
+        let mut text_without_guides = String::new();
+        for c in text.chars() {
+            in_indentation &= c == ' ';
+            if in_indentation && column % level_width == 0 {
+                if !text_without_guides.is_empty() {
+                    drawn.push((style, std::mem::take(&mut text_without_guides)));
                 }
+                drawn.push((guide_style, "│".to_string()));
+            } else {
+                text_without_guides.push(c);
             }
+            column += 1;
         }
-        _ => {
-            for (line, _) in lines.iter() {
-                line_sections.push(vec![(config.null_syntect_style, line.as_str())])
+        if !text_without_guides.is_empty() {
+            drawn.push((style, text_without_guides));
+        }
+    }
+    drawn
+}
+
+/// Replace with '·' the spaces of the (tab-expanded) code of a line, except those following a
+/// `tab_symbol` up to the next tab stop, and dim these and the tab symbols (see
+/// --show-whitespace). Tab symbols are those of the sections marked `is_tab_symbol`, so that the
+/// same text in the code of the line is left as it is. Each space is replaced with a glyph of the
+/// same width, so the columns of the line are unchanged.
+fn mark_whitespace(
+    sections: Vec<(Style, String)>,
+    tab_symbol: Option<&str>,
+    tab_width: usize,
+) -> Vec<(Style, String)> {
+    fn push(marked: &mut Vec<(Style, String)>, style: Style, text: &str) {
+        match marked.last_mut() {
+            Some((last_style, last_text)) if *last_style == style => last_text.push_str(text),
+            _ => marked.push((style, text.to_string())),
+        }
+    }
+    let mut marked = Vec::with_capacity(sections.len());
+    let mut column = 0;
+    // The column of the tab stop ending the padding of the last tab.
+    let mut tab_padding_end = 0;
+    for (style, text) in sections {
+        let mut whitespace_style = style;
+        whitespace_style.ansi_term_style.is_dimmed = true;
+        if let Some(symbol) = tab_symbol.filter(|_| style.is_tab_symbol) {
+            // Adjacent tab symbols are in the same section.
+            for _ in text.matches(symbol) {
+                tab_padding_end = (column / tab_width + 1) * tab_width;
+                column += symbol.width();
             }
+            push(&mut marked, whitespace_style, &text);
+            continue;
+        }
+        let mut rest = text.as_str();
+        while let Some(c) = rest.chars().next() {
+            if c == ' ' && column >= tab_padding_end {
+                push(&mut marked, whitespace_style, "·");
+            } else {
+                push(&mut marked, style, &rest[..c.len_utf8()]);
+            }
+            column += c.width().unwrap_or(0);
+            rest = &rest[c.len_utf8()..];
         }
     }
-    line_sections
+    marked
 }
 
-/// Get background styles to represent diff for minus and plus lines in buffer.
-#[allow(clippy::type_complexity)]
-fn get_diff_style_sections<'a>(
-    lines: &MinusPlus<&'a Vec<(String, State)>>,
+/// Split the painted diff `line`, whose code is made of `diff_sections`, into rows as wide as the
+/// terminal (see --wrap-lines). Continuation rows start with a blank gutter as wide as that of the
+/// line, ending with the wrap symbol.
+fn wrap_painted_line(
+    line: &str,
+    diff_sections: &[(Style, &str)],
     config: &config::Config,
-) -> (
-    MinusPlus<Vec<LineSections<'a, Style>>>,
-    Vec<(Option<usize>, Option<usize>)>,
-) {
-    let (minus_lines, minus_styles): (Vec<&str>, Vec<Style>) = lines[Minus]
+) -> Vec<String> {
+    let code_width: usize = diff_sections
         .iter()
-        .map(|(s, state)| (s.as_str(), *config.get_style(state)))
-        .unzip();
-    let (plus_lines, plus_styles): (Vec<&str>, Vec<Style>) = lines[Plus]
-        .iter()
-        .map(|(s, state)| (s.as_str(), *config.get_style(state)))
-        .unzip();
-    let (minus_line_diff_style_sections, plus_line_diff_style_sections, line_alignment) =
-        edits::infer_edits(
-            minus_lines,
-            plus_lines,
-            minus_styles,
-            config.minus_emph_style, // FIXME
-            plus_styles,
-            config.plus_emph_style, // FIXME
-            &config.tokenization_regex,
-            config.max_line_distance,
-            config.max_line_distance_for_naively_paired_lines,
-        );
-    let diff_sections = MinusPlus::new(
-        minus_line_diff_style_sections,
-        plus_line_diff_style_sections,
+        .map(|(_, text)| ansi::measure_text_width(text))
+        .sum();
+    let symbol = &config.wrap_config.right_prefix_symbol;
+    let gutter_width = ansi::measure_text_width(line)
+        .saturating_sub(code_width)
+        .max(symbol.width());
+    if gutter_width >= config.available_terminal_width {
+        return vec![line.to_string()];
+    }
+    let continuation_gutter = format!(
+        "{}{}",
+        " ".repeat(gutter_width - symbol.width()),
+        config.inline_hint_style.paint(symbol)
     );
-    (diff_sections, line_alignment)
+    ansi::wrap(
+        line,
+        config.available_terminal_width,
+        gutter_width,
+        &continuation_gutter,
+    )
+}
+
+/// Cut the painted diff `line`, if it is wider than the terminal, so that it ends with an ellipsis
+/// in the last column, on the background of `fill_style` (see --truncate-lines).
+fn truncate_painted_line(line: String, fill_style: Style, config: &config::Config) -> String {
+    if ansi::measure_text_width(&line) <= config.available_terminal_width {
+        return line;
+    }
+    let mut ellipsis_style = fill_style;
+    ellipsis_style.ansi_term_style.foreground = config.inline_hint_style.ansi_term_style.foreground;
+    let ellipsis = format!("{}{}", ansi::ANSI_SGR_RESET, ellipsis_style.paint("…"));
+    ansi::truncate_str(&line, config.available_terminal_width, &ellipsis).into_owned()
+}
+
+/// Return the painted gutter symbol if --gutter is in effect, and the painted +/- prefix otherwise.
+fn painted_gutter_or_prefix<'a>(
+    state: &State,
+    line_has_homolog: bool,
+    config: &'a config::Config,
+) -> Option<ANSIString<'a>> {
+    if !config.gutter {
+        return painted_prefix(state.clone(), config);
+    }
+    let style = match (state, line_has_homolog) {
+        (State::HunkMinus(_, _) | State::HunkPlus(_, _), true) => config.gutter_changed_style,
+        (State::HunkMinus(_, _), false) => config.gutter_minus_style,
+        (State::HunkPlus(_, _), false) => config.gutter_plus_style,
+        (State::HunkZero(_, _), _) => config.gutter_zero_style,
+        _ => return painted_prefix(state.clone(), config),
+    };
+    if style.is_omitted {
+        Some(ANSIString::from(" "))
+    } else {
+        Some(style.paint(config.gutter_symbol.as_str()))
+    }
+}
+
+/// Return the label of the kind of line, if --line-kind-labels is in effect.
+fn line_kind_label<'a>(
+    state: &State,
+    line_has_homolog: bool,
+    config: &'a config::Config,
+) -> Option<&'a str> {
+    if !config.line_kind_labels {
+        return None;
+    }
+    let label = match (state, line_has_homolog) {
+        (State::HunkMinus(_, _) | State::HunkPlus(_, _), true) => &config.changed_line_label,
+        (State::HunkMinus(_, _), false) => &config.removed_line_label,
+        (State::HunkPlus(_, _), false) => &config.added_line_label,
+        (State::HunkZero(_, _), _) => &config.context_line_label,
+        _ => return None,
+    };
+    (!label.is_empty()).then_some(label.as_str())
 }
 
-fn painted_prefix(state: State, config: &config::Config) -> Option<ANSIString> {
+fn painted_prefix(state: State, config: &config::Config) -> Option<ANSIString<'_>> {
     use DiffType::*;
     use State::*;
     match (state, config.keep_plus_minus_markers) {
@@ -796,9 +2946,9 @@ fn painted_prefix(state: State, config: &config::Config) -> Option<ANSIString> {
             Some(config.plus_style.paint(prefix))
         }
         // But otherwise we honor keep_plus_minus_markers
-        (HunkMinus(_, _), true) => Some(config.minus_style.paint("-".to_string())),
-        (HunkZero(_, _), true) => Some(config.zero_style.paint(" ".to_string())),
-        (HunkPlus(_, _), true) => Some(config.plus_style.paint("+".to_string())),
+        (HunkMinus(_, _), true) => Some(config.minus_style.paint(config.minus_marker.as_str())),
+        (HunkZero(_, _), true) => Some(config.zero_style.paint(config.zero_marker.as_str())),
+        (HunkPlus(_, _), true) => Some(config.plus_style.paint(config.plus_marker.as_str())),
         _ => None,
     }
 }
@@ -828,7 +2978,7 @@ pub fn parse_style_sections<'a>(
 }
 
 #[allow(clippy::too_many_arguments)]
-pub fn paint_file_path_with_line_number(
+pub fn paint_file_path_with_line_number<E: Emitter>(
     line_number: Option<usize>,
     file_path: &str,
     pad_line_number: bool,
@@ -836,6 +2986,7 @@ pub fn paint_file_path_with_line_number(
     terminate_with_separator: bool,
     file_style: Option<Style>,        // None means do not include file path
     line_number_style: Option<Style>, // None means do not include line number
+    emitter: &E,
     config: &Config,
 ) -> String {
     let mut file_with_line_number = Vec::new();
@@ -874,19 +3025,21 @@ pub fn paint_file_path_with_line_number(
             _ => {}
         }
     }
-    let file_with_line_number = ansi_term::ANSIStrings(&file_with_line_number).to_string();
+    let sections: Vec<_> = file_with_line_number
+        .iter()
+        .map(|ansi_string| (*ansi_string.style_ref(), &**ansi_string))
+        .collect();
+    let mut file_with_line_number = String::new();
+    emitter.emit_sections(&sections, &mut file_with_line_number);
     match if config.hyperlinks && !file_with_line_number.is_empty() {
         utils::path::absolute_path(file_path, config)
     } else {
         None
     } {
-        Some(absolute_path) => hyperlinks::format_osc8_file_hyperlink(
-            absolute_path,
-            line_number,
+        Some(absolute_path) => emitter.hyperlink(
+            &hyperlinks::file_hyperlink_url(absolute_path, line_number, config),
             &file_with_line_number,
-            config,
-        )
-        .into(),
+        ),
         _ => file_with_line_number,
     }
 }
@@ -903,7 +3056,7 @@ fn style_sections_contain_more_than_one_style(sections: &[(Style, &str)]) -> boo
 }
 
 mod superimpose_style_sections {
-    use syntect::highlighting::Style as SyntectStyle;
+    use syntect::highlighting::{FontStyle as SyntectFontStyle, Style as SyntectStyle};
 
     use crate::style::Style;
     use crate::utils::bat::terminal::to_ansi_color;
@@ -915,25 +3068,53 @@ mod superimpose_style_sections {
     // they may indicate that the foreground color comes from syntax
     // highlighting (the is_syntax_highlighting attribute on style::Style). This
     // function takes in the two input streams and outputs one stream with a
-    // single style assigned to each character.
+    // single style assigned to each character. If `font_styles` is true,
+    // syntax-highlighted characters also take the bold, italic and underline
+    // attributes of their syntax style. Should the text of the two streams
+    // differ, the line is styled without syntax highlighting, so that none of
+    // its text is lost. They may only differ by a terminating newline, which
+    // is not part of the output.
     pub fn superimpose_style_sections(
         syntax_style_sections: &[(SyntectStyle, &str)],
         diff_style_sections: &[(Style, &str)],
         true_color: bool,
         null_syntect_style: SyntectStyle,
+        font_styles: bool,
     ) -> Vec<(Style, String)> {
-        coalesce(
+        let exploded_diff_style_sections = explode(diff_style_sections);
+        // The terminating newline of the line may be present on one side only.
+        let superimposed = if text_len(syntax_style_sections) == text_len(diff_style_sections) {
             superimpose(
                 explode(syntax_style_sections)
                     .iter()
-                    .zip(explode(diff_style_sections))
+                    .zip(exploded_diff_style_sections.iter().copied())
                     .collect::<Vec<(&(SyntectStyle, char), (Style, char))>>(),
-            ),
+            )
+        } else {
+            None
+        };
+        coalesce(
+            superimposed.unwrap_or_else(|| {
+                exploded_diff_style_sections
+                    .into_iter()
+                    .map(|(style, c)| ((null_syntect_style, style), c))
+                    .collect()
+            }),
             true_color,
             null_syntect_style,
+            font_styles,
         )
     }
 
+    // Return the length in bytes of the text of the sections, without its terminating newline.
+    fn text_len<T>(style_sections: &[(T, &str)]) -> usize {
+        let len = style_sections.iter().map(|(_, s)| s.len()).sum();
+        match style_sections.iter().rev().find(|(_, s)| !s.is_empty()) {
+            Some((_, s)) if s.ends_with('\n') => len - 1,
+            _ => len,
+        }
+    }
+
     fn explode<T>(style_sections: &[(T, &str)]) -> Vec<(T, char)>
     where
         T: Copy,
@@ -947,33 +3128,42 @@ mod superimpose_style_sections {
         exploded
     }
 
+    // Return None if the characters of a pair differ, i.e. if the two annotations are not of the
+    // same text.
     #[allow(clippy::type_complexity)]
     fn superimpose(
         style_section_pairs: Vec<(&(SyntectStyle, char), (Style, char))>,
-    ) -> Vec<((SyntectStyle, Style), char)> {
-        let mut superimposed: Vec<((SyntectStyle, Style), char)> = Vec::new();
-        for ((syntax_style, char_1), (style, char_2)) in style_section_pairs {
-            if *char_1 != char_2 {
-                panic!(
-                    "String mismatch encountered while superimposing style sections: '{}' vs '{}'",
-                    *char_1, char_2
-                )
-            }
-            superimposed.push(((*syntax_style, style), *char_1));
-        }
-        superimposed
+    ) -> Option<Vec<((SyntectStyle, Style), char)>> {
+        style_section_pairs
+            .into_iter()
+            .map(|((syntax_style, char_1), (style, char_2))| {
+                (*char_1 == char_2).then_some(((*syntax_style, style), *char_1))
+            })
+            .collect()
     }
 
     fn coalesce(
         style_sections: Vec<((SyntectStyle, Style), char)>,
         true_color: bool,
         null_syntect_style: SyntectStyle,
+        font_styles: bool,
     ) -> Vec<(Style, String)> {
         let make_superimposed_style = |(syntect_style, style): (SyntectStyle, Style)| {
             if style.is_syntax_highlighted && syntect_style != null_syntect_style {
+                let font_style = if font_styles {
+                    syntect_style.font_style
+                } else {
+                    SyntectFontStyle::empty()
+                };
                 Style {
                     ansi_term_style: ansi_term::Style {
                         foreground: to_ansi_color(syntect_style.foreground, true_color),
+                        is_bold: style.ansi_term_style.is_bold
+                            || font_style.contains(SyntectFontStyle::BOLD),
+                        is_italic: style.ansi_term_style.is_italic
+                            || font_style.contains(SyntectFontStyle::ITALIC),
+                        is_underline: style.ansi_term_style.is_underline
+                            || font_style.contains(SyntectFontStyle::UNDERLINE),
                         ..style.ansi_term_style
                     },
                     ..style
@@ -983,18 +3173,21 @@ mod superimpose_style_sections {
             }
         };
         let mut coalesced: Vec<(Style, String)> = Vec::new();
-        let mut style_sections = style_sections.iter();
-        if let Some((style_pair, c)) = style_sections.next() {
+        // Distinct style pairs may superimpose to the same style, e.g. when the diff style is not
+        // syntax highlighted, so the superimposed styles are compared.
+        let mut style_sections = style_sections
+            .iter()
+            .map(|(style_pair, c)| (make_superimposed_style(*style_pair), *c));
+        if let Some((style, c)) = style_sections.next() {
             let mut current_string = c.to_string();
-            let mut current_style_pair = style_pair;
-            for (style_pair, c) in style_sections {
-                if style_pair != current_style_pair {
-                    let style = make_superimposed_style(*current_style_pair);
-                    coalesced.push((style, current_string));
+            let mut current_style = style;
+            for (style, c) in style_sections {
+                if style != current_style {
+                    coalesced.push((current_style, current_string));
                     current_string = String::new();
-                    current_style_pair = style_pair;
+                    current_style = style;
                 }
-                current_string.push(*c);
+                current_string.push(c);
             }
 
             // TODO: This is not the ideal location for the following code.
@@ -1003,12 +3196,32 @@ mod superimpose_style_sections {
                 // highlighter to work correctly.
                 current_string.truncate(current_string.len() - 1);
             }
-            let style = make_superimposed_style(*current_style_pair);
-            coalesced.push((style, current_string));
+            coalesced.push((current_style, current_string));
         }
         coalesced
     }
 
+    // Split each section longer than `max_len` characters into sections of the same style, all
+    // but the last of which are `max_len` characters long. Sections are split between characters,
+    // never within the bytes of a multibyte character.
+    pub fn split_long_sections(
+        sections: Vec<(Style, String)>,
+        max_len: usize,
+    ) -> Vec<(Style, String)> {
+        let mut split = Vec::with_capacity(sections.len());
+        for (style, s) in sections {
+            if s.chars().nth(max_len).is_none() {
+                split.push((style, s));
+                continue;
+            }
+            let chars: Vec<char> = s.chars().collect();
+            for chunk in chars.chunks(max_len) {
+                split.push((style, chunk.iter().collect()));
+            }
+        }
+        split
+    }
+
     #[cfg(test)]
     mod tests {
         use lazy_static::lazy_static;
@@ -1040,6 +3253,7 @@ mod superimpose_style_sections {
                 is_omitted: false,
                 is_raw: false,
                 is_syntax_highlighted: true,
+                is_tab_symbol: false,
                 decoration_style: DecorationStyle::NoDecoration,
             };
         }
@@ -1055,6 +3269,7 @@ mod superimpose_style_sections {
                 is_omitted: false,
                 is_raw: false,
                 is_syntax_highlighted: false,
+                is_tab_symbol: false,
                 decoration_style: DecorationStyle::NoDecoration,
             };
         }
@@ -1070,6 +3285,7 @@ mod superimpose_style_sections {
                 is_omitted: false,
                 is_raw: false,
                 is_syntax_highlighted: true,
+                is_tab_symbol: false,
                 decoration_style: DecorationStyle::NoDecoration,
             };
         }
@@ -1080,7 +3296,13 @@ mod superimpose_style_sections {
             let sections_2 = vec![(*SYNTAX_HIGHLIGHTED_STYLE, "ab")];
             let superimposed = vec![(*SUPERIMPOSED_STYLE, "ab".to_string())];
             assert_eq!(
-                superimpose_style_sections(&sections_1, &sections_2, true, SyntectStyle::default()),
+                superimpose_style_sections(
+                    &sections_1,
+                    &sections_2,
+                    true,
+                    SyntectStyle::default(),
+                    false,
+                ),
                 superimposed
             );
         }
@@ -1094,7 +3316,13 @@ mod superimpose_style_sections {
             ];
             let superimposed = vec![(*SUPERIMPOSED_STYLE, String::from("ab"))];
             assert_eq!(
-                superimpose_style_sections(&sections_1, &sections_2, true, SyntectStyle::default()),
+                superimpose_style_sections(
+                    &sections_1,
+                    &sections_2,
+                    true,
+                    SyntectStyle::default(),
+                    false,
+                ),
                 superimposed
             );
         }
@@ -1105,11 +3333,144 @@ mod superimpose_style_sections {
             let sections_2 = vec![(*NON_SYNTAX_HIGHLIGHTED_STYLE, "ab")];
             let superimposed = vec![(*NON_SYNTAX_HIGHLIGHTED_STYLE, "ab".to_string())];
             assert_eq!(
-                superimpose_style_sections(&sections_1, &sections_2, true, SyntectStyle::default()),
+                superimpose_style_sections(
+                    &sections_1,
+                    &sections_2,
+                    true,
+                    SyntectStyle::default(),
+                    false,
+                ),
                 superimposed
             );
         }
 
+        #[test]
+        fn test_superimpose_style_sections_coalesces_equal_styles() {
+            let other_syntax_style = SyntectStyle {
+                foreground: SyntectColor::WHITE,
+                ..*SYNTAX_STYLE
+            };
+            // The syntax styles differ, but are not used by the diff style.
+            let sections_1 = vec![
+                (*SYNTAX_STYLE, "a"),
+                (other_syntax_style, "b"),
+                (*SYNTAX_STYLE, "c"),
+            ];
+            let sections_2 = vec![(*NON_SYNTAX_HIGHLIGHTED_STYLE, "abc")];
+            let superimposed = superimpose_style_sections(
+                &sections_1,
+                &sections_2,
+                true,
+                SyntectStyle::default(),
+                false,
+            );
+            assert_eq!(
+                superimposed,
+                vec![(*NON_SYNTAX_HIGHLIGHTED_STYLE, "abc".to_string())]
+            );
+            let (style, s) = &superimposed[0];
+            assert_eq!(style.paint(s).to_string(), "\x1b[4;47;37mabc\x1b[0m");
+        }
+
+        #[test]
+        fn test_superimpose_style_sections_text_mismatch() {
+            let sections_2 = vec![
+                (*SYNTAX_HIGHLIGHTED_STYLE, "ab"),
+                (*NON_SYNTAX_HIGHLIGHTED_STYLE, "c"),
+            ];
+            // The syntax sections are shorter, longer, or different.
+            for sections_1 in [
+                vec![(*SYNTAX_STYLE, "ab")],
+                vec![(*SYNTAX_STYLE, "abcd")],
+                vec![(*SYNTAX_STYLE, "a"), (*SYNTAX_STYLE, "xc")],
+            ] {
+                assert_eq!(
+                    superimpose_style_sections(
+                        &sections_1,
+                        &sections_2,
+                        true,
+                        SyntectStyle::default(),
+                        false,
+                    ),
+                    vec![
+                        (*SYNTAX_HIGHLIGHTED_STYLE, "ab".to_string()),
+                        (*NON_SYNTAX_HIGHLIGHTED_STYLE, "c".to_string()),
+                    ]
+                );
+            }
+        }
+
+        #[test]
+        fn test_superimpose_style_sections_newline_on_one_side() {
+            // The terminating newline is dropped, whichever of the streams contains it.
+            for (syntax_text, diff_text) in [("ab\n", "ab"), ("ab", "ab\n")] {
+                assert_eq!(
+                    superimpose_style_sections(
+                        &[(*SYNTAX_STYLE, syntax_text)],
+                        &[(*SYNTAX_HIGHLIGHTED_STYLE, diff_text)],
+                        true,
+                        SyntectStyle::default(),
+                        false,
+                    ),
+                    vec![(*SUPERIMPOSED_STYLE, "ab".to_string())]
+                );
+            }
+        }
+
+        #[test]
+        fn test_superimpose_style_sections_font_styles() {
+            let syntax_style = SyntectStyle {
+                font_style: SyntectFontStyle::BOLD | SyntectFontStyle::UNDERLINE,
+                ..*SYNTAX_STYLE
+            };
+            let diff_style = Style {
+                ansi_term_style: ansi_term::Style::new().on(Color::White),
+                is_syntax_highlighted: true,
+                ..*SYNTAX_HIGHLIGHTED_STYLE
+            };
+            let sections_1 = vec![(syntax_style, "ab")];
+            let sections_2 = vec![(diff_style, "ab")];
+            let painted = |font_styles| {
+                let superimposed = superimpose_style_sections(
+                    &sections_1,
+                    &sections_2,
+                    true,
+                    SyntectStyle::default(),
+                    font_styles,
+                );
+                let (style, s) = &superimposed[0];
+                style.paint(s).to_string()
+            };
+            assert_eq!(painted(true), "\x1b[1;4;47;38;2;0;0;0mab\x1b[0m");
+            assert_eq!(painted(false), "\x1b[47;38;2;0;0;0mab\x1b[0m");
+        }
+
+        #[test]
+        fn test_split_long_sections() {
+            let sections = vec![
+                (*SYNTAX_HIGHLIGHTED_STYLE, "a".repeat(100)),
+                (*NON_SYNTAX_HIGHLIGHTED_STYLE, "b".repeat(30)),
+            ];
+            let split = split_long_sections(sections, 30);
+            assert_eq!(split.len(), 100_usize.div_ceil(30) + 1);
+            assert!(split.iter().all(|(_, s)| s.chars().count() <= 30));
+            assert_eq!(split[3], (*SYNTAX_HIGHLIGHTED_STYLE, "a".repeat(10)));
+            assert_eq!(split[4], (*NON_SYNTAX_HIGHLIGHTED_STYLE, "b".repeat(30)));
+        }
+
+        #[test]
+        fn test_split_long_sections_multibyte() {
+            let sections = vec![(*SYNTAX_HIGHLIGHTED_STYLE, "aéb€".repeat(3))];
+            assert_eq!(
+                split_long_sections(sections, 5),
+                vec![
+                    (*SYNTAX_HIGHLIGHTED_STYLE, "aéb€a".to_string()),
+                    (*SYNTAX_HIGHLIGHTED_STYLE, "éb€aé".to_string()),
+                    (*SYNTAX_HIGHLIGHTED_STYLE, "b€".to_string()),
+                ]
+            );
+        }
+
         #[test]
         fn test_explode() {
             let arbitrary = 0;
@@ -1119,14 +3480,433 @@ mod superimpose_style_sections {
             )
         }
 
+        #[test]
+        fn test_explode_reproduces_text() {
+            let sections = [(0, "fn "), (1, ""), (2, "été()\t"), (3, "日本\n")];
+            let exploded: String = explode(&sections).iter().map(|(_, c)| c).collect();
+            assert_eq!(exploded, "fn été()\t日本\n");
+        }
+
         #[test]
         fn test_superimpose() {
             let x = (*SYNTAX_STYLE, 'a');
             let pairs = vec![(&x, (*SYNTAX_HIGHLIGHTED_STYLE, 'a'))];
             assert_eq!(
                 superimpose(pairs),
-                vec![((*SYNTAX_STYLE, *SYNTAX_HIGHLIGHTED_STYLE), 'a')]
+                Some(vec![((*SYNTAX_STYLE, *SYNTAX_HIGHLIGHTED_STYLE), 'a')])
+            );
+            let y = (*SYNTAX_STYLE, 'b');
+            assert_eq!(
+                superimpose(vec![(&y, (*SYNTAX_HIGHLIGHTED_STYLE, 'a'))]),
+                None
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delta::DiffType;
+    use crate::tests::integration_test_utils::{make_config_from_args, MINUS_PLUS_TEST_STYLE_ARGS};
+
+    #[test]
+    fn test_painted_lines_end_with_style_reset() {
+        use ansi_term::Color;
+
+        let config = make_config_from_args(&["--syntax-theme=none"]);
+        let lines = vec![(
+            "ab".to_string(),
+            State::HunkMinus(DiffType::Unified, None),
+            LineInfo::default(),
+        )];
+        let syntax_style_sections = vec![vec![
+            (SyntectStyle::default(), "a"),
+            (SyntectStyle::default(), "b"),
+        ]];
+        let diff_style_sections = vec![vec![
+            (Style::from_colors(Some(Color::Red), None), "a"),
+            (Style::from_colors(None, Some(Color::Blue)), "b"),
+        ]];
+        for bg_should_fill in [
+            BgShouldFill::No,
+            BgShouldFill::With(BgFillMethod::TryAnsiSequence),
+            BgShouldFill::With(BgFillMethod::Spaces),
+        ] {
+            let mut output_buffer = String::new();
+            Painter::paint_lines(
+                &lines,
+                &syntax_style_sections,
+                &diff_style_sections,
+                &[true],
+                None,
+                &mut output_buffer,
+                &AnsiEmitter,
+                &config,
+                &mut None,
+                None,
+                bg_should_fill,
             );
+            assert!(
+                output_buffer.ends_with("\x1b[0m\n"),
+                "{:?} does not end with a style reset",
+                output_buffer
+            );
+        }
+    }
+
+    #[test]
+    fn test_paint_lines_fill_with_spaces() {
+        let config = make_config_from_args(&["--zero-style=normal blue"]);
+        let width = config.available_terminal_width;
+        // The unchanged line is painted with its " " prefix.
+        for (text, n_fill_spaces) in [("ab".to_string(), width - 3), ("x".repeat(width), 0)] {
+            let mut writer = Vec::new();
+            let mut painter = Painter::new(&mut writer, &config);
+            let state = State::HunkZero(DiffType::Unified, None);
+            painter.paint_zero_line(&format!(" {}\n", text), &[], state);
+            assert_eq!(
+                ansi::strip_ansi_codes(&painter.output_buffer),
+                format!(" {}{}\n", text, " ".repeat(n_fill_spaces))
+            );
+        }
+    }
+
+    #[test]
+    fn test_equal_styles_of_adjacent_sections_are_not_repeated() {
+        let painted = |args: &[&str]| {
+            let config = make_config_from_args(args);
+            let mut writer = Vec::new();
+            let mut painter = Painter::new(&mut writer, &config);
+            let state = State::HunkZero(DiffType::Unified, None);
+            painter.paint_zero_line(&format!(" {}\n", "x".repeat(100)), &[], state);
+            painter.output_buffer.clone()
+        };
+        let args = ["--zero-style=syntax #aabbcc", "--syntax-theme=none"];
+        // Splitting the line into sections of the same style does not add escape sequences.
+        let unsplit = painted(&args);
+        let split = painted(&[&args[..], &["--max-section-length=3"]].concat());
+        assert_eq!(split, unsplit);
+        assert!(split.starts_with(&format!("\x1b[48;5;146m {}\x1b[0m", "x".repeat(100))));
+    }
+
+    #[test]
+    fn test_output_buffer_limit() {
+        // A writer recording the size of each write.
+        struct CountingWriter(Vec<usize>);
+        impl Write for CountingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.push(buf.len());
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let config = make_config_from_args(&["--keep-plus-minus-markers"]);
+        let mut writer = CountingWriter(Vec::new());
+        let mut painter = Painter::new(&mut writer, &config);
+        painter.paint_zero_line("zero\n", &[], State::HunkZero(DiffType::Unified, None));
+        let line_len = painter.output_buffer.len();
+        drop(painter);
+
+        let limit = 3 * line_len;
+        let mut writer = CountingWriter(Vec::new());
+        let mut painter = Painter::new(&mut writer, &config);
+        painter.output_buffer_limit = Some(limit);
+        for _ in 0..10 {
+            painter.paint_zero_line("zero\n", &[], State::HunkZero(DiffType::Unified, None));
+            assert!(painter.output_buffer.len() < limit);
+        }
+        painter.emit().unwrap();
+        assert!(painter.output_buffer.capacity() <= limit);
+        drop(painter);
+
+        // Every third line takes the buffer to the limit, so that it is written early.
+        assert_eq!(writer.0, [limit, limit, limit, line_len]);
+    }
+
+    #[test]
+    fn test_fill_style_of_line_style() {
+        let config = make_config_from_args(&[
+            "--color-moved",
+            "--moved-style=normal blue",
+            "--minus-style=normal red",
+        ]);
+        let state = State::HunkMinus(DiffType::Unified, None);
+        let fill_style = |diff_sections: &[(Style, &str)], line_style| {
+            Painter::get_should_right_fill_background_color_and_fill_style(
+                diff_sections,
+                Some(false),
+                line_style,
+                &state,
+                BgShouldFill::default(),
+                &config,
+            )
+            .1
+        };
+        // A line is filled with moved-style because it is moved, not because of its first style.
+        assert_eq!(
+            fill_style(&[(config.moved_style, "x")], None),
+            config.minus_style
+        );
+        assert_eq!(
+            fill_style(&[(config.minus_style, "x")], Some(config.moved_style)),
+            config.moved_style
+        );
+    }
+
+    #[test]
+    fn test_output_buffer_limit_option() {
+        let config = make_config_from_args(&["--output-buffer-limit=100"]);
+        let mut writer = Vec::new();
+        let painter = Painter::new(&mut writer, &config);
+        assert_eq!(painter.output_buffer_limit, Some(100));
+        assert!(painter.output_buffer.capacity() >= 100);
+
+        let config = make_config_from_args(&[]);
+        let mut writer = Vec::new();
+        let painter = Painter::new(&mut writer, &config);
+        assert_eq!(painter.output_buffer_limit, None);
+    }
+
+    #[test]
+    fn test_output_buffer_limit_write_error() {
+        struct FailingWriter;
+        impl Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let config = make_config_from_args(&[]);
+        let mut writer = FailingWriter;
+        let mut painter = Painter::new(&mut writer, &config);
+        painter.output_buffer_limit = Some(1);
+        painter.paint_zero_line("zero\n", &[], State::HunkZero(DiffType::Unified, None));
+        let error = painter.emit().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn test_paint_buffered_lines_clears_buffers() {
+        let config = make_config_from_args(&[]);
+        let mut writer = Vec::new();
+        let mut painter = Painter::new(&mut writer, &config);
+        for _ in 0..10 {
+            let state = State::HunkMinus(DiffType::Unified, None);
+            painter
+                .minus_lines
+                .push(("minus\n".to_string(), state, LineInfo::default()));
+            let state = State::HunkPlus(DiffType::Unified, None);
+            painter
+                .plus_lines
+                .push(("plus\n".to_string(), state, LineInfo::default()));
+        }
+        painter.paint_buffered_minus_and_plus_lines();
+        assert!(painter.minus_lines.is_empty());
+        assert!(painter.plus_lines.is_empty());
+        // The buffers keep their allocations for the next hunk.
+        assert!(painter.minus_lines.capacity() >= 10);
+        assert!(painter.plus_lines.capacity() >= 10);
+        assert_eq!(
+            ansi::strip_ansi_codes(&painter.output_buffer)
+                .lines()
+                .count(),
+            20
+        );
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_emphasis_records() {
+        let config = make_config_from_args(&MINUS_PLUS_TEST_STYLE_ARGS);
+        let mut writer = Vec::new();
+        let mut painter = Painter::new(&mut writer, &config);
+        painter.emphasis_records = Some(Vec::new());
+        painter.hunk_file_paths = MinusPlus::new("old.rs".to_string(), "new.rs".to_string());
+        painter.hunk_line_numbers = MinusPlus::new(10, 20);
+        for line in ["let x = 1;\n", "naïve(b, a);\n", "removed();\n"] {
+            let state = State::HunkMinus(DiffType::Unified, None);
+            painter
+                .minus_lines
+                .push((line.to_string(), state, LineInfo::default()));
         }
+        for line in ["let y = 2;\n", "naïve(a, b, c);\n"] {
+            let state = State::HunkPlus(DiffType::Unified, None);
+            painter
+                .plus_lines
+                .push((line.to_string(), state, LineInfo::default()));
+        }
+        painter.paint_buffered_minus_and_plus_lines();
+
+        assert_eq!(
+            ansi::explain_ansi(&painter.output_buffer, false),
+            "\
+(red)let (ul normal)x(red) = (ul normal)1(red);(normal)
+(red)naïve(b, (ul normal)a(red));(normal)
+(red)removed();(normal)
+(green)let (ul normal)y(green) = (ul normal)2(green);(normal)
+(green)naïve((ul normal)a, (green)b, (ul normal)c(green));(normal)
+"
+        );
+        let record = |file: &str, line_number, side, ranges| EmphasisRecord {
+            file: file.to_string(),
+            line_number,
+            side,
+            ranges,
+        };
+        assert_eq!(
+            painter.emphasis_records.unwrap(),
+            [
+                record("old.rs", 10, Minus, vec![4..5, 8..9]),
+                record("old.rs", 11, Minus, vec![9..10]),
+                record("new.rs", 20, Plus, vec![4..5, 8..9]),
+                record("new.rs", 21, Plus, vec![6..9, 12..13]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_enclosing_brackets() {
+        assert_eq!(find_enclosing_brackets("f(a, b)", 2, 3), Some((1, 6)));
+        assert_eq!(find_enclosing_brackets("f(g(a), b)", 5, 9), Some((1, 9)));
+        assert_eq!(find_enclosing_brackets("x[f(a)]", 4, 5), Some((3, 5)));
+        assert_eq!(find_enclosing_brackets("{ [a] }", 2, 5), Some((0, 6)));
+        assert_eq!(find_enclosing_brackets("a + b", 4, 5), None);
+        assert_eq!(find_enclosing_brackets("f(a]", 2, 3), None);
+        assert_eq!(find_enclosing_brackets("f(a", 2, 3), None);
+        assert_eq!(find_enclosing_brackets("a)", 0, 1), None);
+    }
+
+    #[test]
+    fn test_is_punctuation_change() {
+        assert!(is_punctuation_change(","));
+        assert!(is_punctuation_change(" ; "));
+        assert!(is_punctuation_change("});"));
+        assert!(!is_punctuation_change(" "));
+        assert!(!is_punctuation_change(", b"));
+    }
+
+    #[test]
+    fn test_is_operator_change() {
+        let operators = ["==", "!=", "=>"].map(str::to_string);
+        assert!(is_operator_change("a == b", 2, 3, &operators));
+        assert!(is_operator_change("a => b", 3, 4, &operators));
+        assert!(is_operator_change("a != b", 1, 4, &operators));
+        assert!(!is_operator_change("a =~ b", 2, 3, &operators));
+        assert!(!is_operator_change("a == b", 0, 1, &operators));
+        assert!(!is_operator_change("f(a)", 1, 2, &operators));
+    }
+
+    #[test]
+    fn test_paint_line_with_mismatched_syntax_sections() {
+        let config = make_config_from_args(&["--zero-style=syntax"]);
+        let zero_style = config.zero_style;
+        // The syntax sections are those of a line longer than the one being painted.
+        let (painted, _) = Painter::paint_line(
+            &[(config.null_syntect_style, "fn main() {}\n")],
+            &[(zero_style, "fn f() {}")],
+            &State::HunkZero(DiffType::Unified, None),
+            &mut None,
+            None,
+            None,
+            &AnsiEmitter,
+            &config,
+        );
+        assert_eq!(ansi::strip_ansi_codes(&painted), "fn f() {}");
+    }
+
+    #[test]
+    fn test_painter_with_syntax() {
+        let config = make_config_from_args(&[]);
+        let mut writer = Vec::new();
+        let painter = Painter::with_syntax(&mut writer, &config, Some("a.rs"));
+        assert_eq!(painter.syntax.name, "Rust");
+        assert!(painter.highlighter.is_some());
+        assert!(painter.minus_lines.is_empty());
+        assert!(painter.plus_lines.is_empty());
+        assert!(painter.output_buffer.is_empty());
+    }
+
+    #[test]
+    fn test_max_syntax_length_skips_highlighting_of_long_line() {
+        let line = "x".repeat(100_000);
+        let lines = vec![(
+            line,
+            State::HunkZero(DiffType::Unified, None),
+            LineInfo::default(),
+        )];
+        let config = make_config_from_args(&["--syntax-theme=GitHub"]);
+        let mut writer = Vec::new();
+        let mut painter = Painter::with_syntax(&mut writer, &config, Some("a.js"));
+        let syntax_sections =
+            get_syntax_style_sections_for_lines(&lines, painter.highlighter.as_mut(), &config);
+        let (last_style, last_text) = syntax_sections[0].last().unwrap();
+        assert_eq!(*last_style, config.null_syntect_style);
+        assert_eq!(last_text.len(), 100_000 - config.max_syntax_length);
+    }
+
+    #[test]
+    fn test_max_syntax_scope_depth() {
+        let line = "[[[[[[[[[[1]]]]]]]]]]\n";
+        let lines = vec![(
+            line.to_string(),
+            State::HunkZero(DiffType::Unified, None),
+            LineInfo::default(),
+        )];
+        let syntax_sections = |max_depth: usize| {
+            let config = make_config_from_args(&[
+                "--syntax-theme=GitHub",
+                &format!("--max-syntax-scope-depth={max_depth}"),
+            ]);
+            let mut writer = Vec::new();
+            let mut painter = Painter::with_syntax(&mut writer, &config, Some("a.json"));
+            get_syntax_style_sections_for_lines(&lines, painter.highlighter.as_mut(), &config)
+                .into_iter()
+                .map(|sections| sections.len())
+                .collect::<Vec<_>>()
+        };
+        // The scope stack is 12 deep at the number in the innermost of the 10 nested arrays.
+        assert!(syntax_sections(0)[0] > 1);
+        assert!(syntax_sections(12)[0] > 1);
+        assert_eq!(syntax_sections(11), vec![1]);
+    }
+
+    #[test]
+    fn test_max_syntax_scope_depth_keeps_highlighter_state() {
+        let lines = ["{\"a\": [[[[[[[[[[1]]]]]]]]]],\n", "\"b\": 2}\n"].map(|line| {
+            (
+                line.to_string(),
+                State::HunkZero(DiffType::Unified, None),
+                LineInfo::default(),
+            )
+        });
+        let syntax_sections = |lines: &[(String, State, LineInfo)], max_depth: usize| {
+            let config = make_config_from_args(&[
+                "--syntax-theme=GitHub",
+                &format!("--max-syntax-scope-depth={max_depth}"),
+            ]);
+            let mut writer = Vec::new();
+            let mut painter = Painter::with_syntax(&mut writer, &config, Some("a.json"));
+            get_syntax_style_sections_for_lines(lines, painter.highlighter.as_mut(), &config)
+                .into_iter()
+                .map(|sections| {
+                    sections
+                        .into_iter()
+                        .map(|(style, s)| (style, s.to_string()))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        let limited = syntax_sections(&lines, 11);
+        assert_eq!(limited[0].len(), 1);
+        // The line following the unhighlighted one is highlighted as part of the object.
+        assert_eq!(limited[1], syntax_sections(&lines, 0)[1]);
+        assert_ne!(limited[1], syntax_sections(&lines[1..], 0)[0]);
     }
 }
@@ -31,6 +31,7 @@ impl Style {
             is_omitted,
             is_raw,
             is_syntax_highlighted,
+            is_tab_symbol: false,
             decoration_style,
         }
     }
@@ -323,6 +324,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_ansi_term_style_with_hex_and_auto_colors() {
+        assert_eq!(
+            parse_ansi_term_style("bold #aabbcc '#440000'", None, true, None),
+            (
+                ansi_term::Style {
+                    foreground: Some(ansi_term::Color::RGB(0xaa, 0xbb, 0xcc)),
+                    background: Some(ansi_term::Color::RGB(0x44, 0x00, 0x00)),
+                    is_bold: true,
+                    ..ansi_term::Style::new()
+                },
+                false,
+                false,
+                false
+            )
+        );
+        let default = Style {
+            ansi_term_style: ansi_term::Style::new()
+                .fg(ansi_term::Color::Red)
+                .on(ansi_term::Color::Blue),
+            ..Style::new()
+        };
+        // "auto" takes the color of the default style.
+        assert_eq!(
+            parse_ansi_term_style("auto green", Some(default), false, None),
+            (
+                ansi_term::Style {
+                    foreground: Some(ansi_term::Color::Red),
+                    background: Some(ansi_term::Color::Green),
+                    ..ansi_term::Style::new()
+                },
+                false,
+                false,
+                false
+            )
+        );
+        assert_eq!(
+            parse_ansi_term_style("italic normal auto", Some(default), false, None),
+            (
+                ansi_term::Style {
+                    background: Some(ansi_term::Color::Blue),
+                    is_italic: true,
+                    ..ansi_term::Style::new()
+                },
+                false,
+                false,
+                false
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid style string: red green blue.")]
+    fn test_parse_ansi_term_style_with_too_many_colors() {
+        parse_ansi_term_style("red green blue", None, false, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid color or style attribute: bolder")]
+    fn test_parse_ansi_term_style_with_invalid_word() {
+        parse_ansi_term_style("bolder red", None, false, None);
+    }
+
     #[test]
     fn test_parse_ansi_term_style_with_special_syntax_color() {
         assert_eq!(
@@ -37,6 +37,19 @@ pub fn parse_styles(opt: &cli::Opt) -> HashMap<String, Style> {
         .get_mut("plus-emph-style")
         .unwrap_or_else(|| panic!("plus-emph-style not found in resolved styles"))
         .is_emph = true;
+    for name in [
+        "formatting-emph-style",
+        "operator-emph-style",
+        "pair-swap-emph-style",
+        "punctuation-emph-style",
+        "rename-emph-style",
+        "scope-change-emph-style",
+        "trailing-comment-emph-style",
+    ] {
+        if let Some(style) = resolved_styles.get_mut(name) {
+            style.is_emph = true;
+        }
+    }
     resolved_styles
 }
 
@@ -137,12 +150,25 @@ fn make_hunk_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>)
         opt.git_config(),
     );
 
+    // With --auto-emph-colors, the default background of an emph style is derived from that of
+    // the corresponding base style, if it has one.
+    let emph_background_color_default =
+        |base_style: &StyleReference, default: ansi_term::Color| match base_style {
+            StyleReference::Style(style) if opt.auto_emph_colors => style
+                .ansi_term_style
+                .background
+                .map(|color| color::emph_background_color(color, true_color))
+                .unwrap_or(default),
+            _ => default,
+        };
+
     let minus_emph_style = style_from_str(
         &opt.minus_emph_style,
         Some(Style::from_colors(
             None,
-            Some(color::get_minus_emph_background_color_default(
-                color_mode, true_color,
+            Some(emph_background_color_default(
+                &minus_style,
+                color::get_minus_emph_background_color_default(color_mode, true_color),
             )),
         )),
         None,
@@ -173,7 +199,37 @@ fn make_hunk_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>)
         opt.git_config(),
     );
 
-    let zero_style = style_from_str(&opt.zero_style, None, None, true_color, opt.git_config());
+    let syntax_theme_background_fallback = opt
+        .syntax_theme_background_fallback
+        .as_deref()
+        .and_then(|s| color::parse_color(s, true_color, opt.git_config()));
+    let zero_style = style_from_str(
+        &opt.zero_style,
+        Some(Style::from_colors(
+            None,
+            color::get_syntax_theme_background_color(
+                opt.computed.syntax_theme.as_ref(),
+                syntax_theme_background_fallback,
+                true_color,
+            ),
+        )),
+        None,
+        true_color,
+        opt.git_config(),
+    );
+
+    let zebra_style = style_from_str(
+        &opt.zebra_style,
+        Some(Style::from_colors(
+            None,
+            Some(color::get_zebra_background_color_default(
+                color_mode, true_color,
+            )),
+        )),
+        None,
+        true_color,
+        opt.git_config(),
+    );
 
     let plus_style = style_from_str(
         &opt.plus_style,
@@ -192,8 +248,9 @@ fn make_hunk_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>)
         &opt.plus_emph_style,
         Some(Style::from_colors(
             None,
-            Some(color::get_plus_emph_background_color_default(
-                color_mode, true_color,
+            Some(emph_background_color_default(
+                &plus_style,
+                color::get_plus_emph_background_color_default(color_mode, true_color),
             )),
         )),
         None,
@@ -241,6 +298,7 @@ fn make_hunk_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>)
             minus_empty_line_marker_style,
         ),
         ("zero-style", zero_style),
+        ("zebra-style", zebra_style),
         ("plus-style", plus_style),
         ("plus-emph-style", plus_emph_style),
         ("plus-non-emph-style", plus_non_emph_style),
@@ -397,6 +455,26 @@ fn make_commit_file_hunk_header_styles(opt: &cli::Opt, styles: &mut HashMap<&str
                 opt.git_config(),
             ),
         ),
+        (
+            "file-separator-style",
+            style_from_str(
+                &opt.file_separator_style,
+                None,
+                None,
+                true_color,
+                opt.git_config(),
+            ),
+        ),
+        (
+            "hunk-separator-style",
+            style_from_str(
+                &opt.hunk_separator_style,
+                None,
+                None,
+                true_color,
+                opt.git_config(),
+            ),
+        ),
     ]);
 }
 
@@ -500,16 +578,61 @@ fn make_merge_conflict_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleRe
 }
 
 fn make_misc_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>) {
-    styles.insert(
-        "inline-hint-style",
-        style_from_str(
-            &opt.inline_hint_style,
-            None,
-            None,
-            opt.computed.true_color,
-            opt.git_config(),
+    for (name, style_string) in [
+        ("empty-file-style", &opt.empty_file_style),
+        ("gutter-changed-style", &opt.gutter_changed_style),
+        ("gutter-minus-style", &opt.gutter_minus_style),
+        ("gutter-plus-style", &opt.gutter_plus_style),
+        ("gutter-zero-style", &opt.gutter_zero_style),
+        ("inline-hint-style", &opt.inline_hint_style),
+        ("moved-style", &opt.moved_style),
+        ("no-newline-marker-style", &opt.no_newline_marker_style),
+    ] {
+        styles.insert(
+            name,
+            style_from_str(
+                style_string,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        );
+    }
+    // These styles are unset by default.
+    for (name, style_string) in [
+        ("formatting-emph-style", &opt.formatting_emph_style),
+        ("indent-guide-style", &opt.indent_guide_style),
+        (
+            "indent-normalization-style",
+            &opt.indent_normalization_style,
         ),
-    );
+        ("insertion-point-style", &opt.insertion_point_style),
+        ("matching-bracket-style", &opt.matching_bracket_style),
+        ("operator-emph-style", &opt.operator_emph_style),
+        ("pair-swap-emph-style", &opt.pair_swap_emph_style),
+        ("punctuation-emph-style", &opt.punctuation_emph_style),
+        ("rename-emph-style", &opt.rename_emph_style),
+        ("reordered-lines-style", &opt.reordered_lines_style),
+        ("scope-change-emph-style", &opt.scope_change_emph_style),
+        (
+            "trailing-comment-emph-style",
+            &opt.trailing_comment_emph_style,
+        ),
+    ] {
+        if let Some(style_string) = style_string {
+            styles.insert(
+                name,
+                style_from_str(
+                    style_string,
+                    None,
+                    None,
+                    opt.computed.true_color,
+                    opt.git_config(),
+                ),
+            );
+        }
+    }
     styles.insert(
         "git-minus-style",
         StyleReference::Style(
@@ -580,6 +703,8 @@ fn style_from_str_with_handling_of_special_decoration_attributes(
 
 #[cfg(test)]
 mod tests {
+    use syntect::highlighting::{Color as SyntectColor, Theme as SyntaxTheme};
+
     use super::*;
     use crate::tests::integration_test_utils;
 
@@ -670,4 +795,118 @@ mod tests {
             "__cycle__"
         );
     }
+
+    #[test]
+    fn test_auto_emph_colors() {
+        let emph_backgrounds = |args: &[&str]| {
+            let args = [args, &["--true-color", "always"]].concat();
+            let config = integration_test_utils::make_config_from_args(&args);
+            (
+                config.minus_emph_style.ansi_term_style.background,
+                config.plus_emph_style.ansi_term_style.background,
+            )
+        };
+        let base_styles = [
+            "--minus-style",
+            "normal #400020",
+            "--plus-style",
+            "normal #002040",
+        ];
+        assert_eq!(
+            emph_backgrounds(&[&base_styles[..], &["--auto-emph-colors"]].concat()),
+            (
+                Some(color::emph_background_color(
+                    ansi_term::Color::RGB(0x40, 0x00, 0x20),
+                    true
+                )),
+                Some(color::emph_background_color(
+                    ansi_term::Color::RGB(0x00, 0x20, 0x40),
+                    true
+                )),
+            )
+        );
+        // An explicit emph background is kept.
+        assert_eq!(
+            emph_backgrounds(
+                &[
+                    &base_styles[..],
+                    &["--auto-emph-colors", "--plus-emph-style", "normal #00ff00"],
+                ]
+                .concat()
+            )
+            .1,
+            Some(ansi_term::Color::RGB(0x00, 0xff, 0x00))
+        );
+        // Without --auto-emph-colors, the default emph backgrounds are used.
+        assert_eq!(
+            emph_backgrounds(&base_styles),
+            (
+                Some(color::get_minus_emph_background_color_default(
+                    color::ColorMode::Dark,
+                    true
+                )),
+                Some(color::get_plus_emph_background_color_default(
+                    color::ColorMode::Dark,
+                    true
+                )),
+            )
+        );
+    }
+
+    fn zero_style_background(args: &[&str], syntax_theme: SyntaxTheme) -> Option<ansi_term::Color> {
+        let args = [args, &["--true-color", "always"]].concat();
+        let mut opt = integration_test_utils::make_options_from_args(&args);
+        opt.computed.syntax_theme = Some(syntax_theme);
+        let mut styles = HashMap::new();
+        make_hunk_styles(&opt, &mut styles);
+        match styles["zero-style"] {
+            StyleReference::Style(style) => style.ansi_term_style.background,
+            _ => panic!("zero-style should not be a style reference"),
+        }
+    }
+
+    #[test]
+    fn test_zero_style_auto_background_uses_syntax_theme_background() {
+        let mut syntax_theme = SyntaxTheme::default();
+        syntax_theme.settings.background = Some(SyntectColor {
+            r: 0x10,
+            g: 0x20,
+            b: 0x30,
+            a: 0xff,
+        });
+        assert_eq!(
+            zero_style_background(
+                &[
+                    "--zero-style",
+                    "syntax auto",
+                    "--syntax-theme-background-fallback",
+                    "#abcdef",
+                ],
+                syntax_theme,
+            ),
+            Some(ansi_term::Color::RGB(0x10, 0x20, 0x30))
+        );
+    }
+
+    #[test]
+    fn test_zero_style_auto_background_falls_back_when_syntax_theme_lacks_background() {
+        let syntax_theme = SyntaxTheme::default();
+        assert!(syntax_theme.settings.background.is_none());
+        assert_eq!(
+            zero_style_background(
+                &[
+                    "--zero-style",
+                    "syntax auto",
+                    "--syntax-theme-background-fallback",
+                    "#abcdef",
+                ],
+                syntax_theme.clone(),
+            ),
+            Some(ansi_term::Color::RGB(0xab, 0xcd, 0xef))
+        );
+        assert_eq!(
+            zero_style_background(&["--zero-style", "syntax auto"], syntax_theme),
+            None
+        );
+    }
 }
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+/// A writer retaining only the last `capacity` lines written to it, so that a pager can display a
+/// scrollback window without holding on to the whole painted diff.
+///
+/// Delta paints into it when --scrollback-lines is set. Lines are stored with their ANSI escape
+/// sequences but without their trailing newline. A line becomes available once its newline has
+/// been written; when the buffer is full, each new line evicts the oldest one.
+pub struct ScrollbackBuffer {
+    lines: VecDeque<String>,
+    capacity: usize,
+    // Bytes written since the last newline.
+    partial_line: Vec<u8>,
+}
+
+impl ScrollbackBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::with_capacity(capacity),
+            capacity,
+            partial_line: Vec::new(),
+        }
+    }
+
+    /// The retained lines, oldest first.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+
+    /// Write the retained lines to `writer`, followed by the final line if it does not end with a
+    /// newline.
+    pub fn write_to(&self, writer: &mut dyn Write) -> io::Result<()> {
+        for line in self.lines() {
+            writeln!(writer, "{line}")?;
+        }
+        writer.write_all(&self.partial_line)?;
+        writer.flush()
+    }
+
+    fn push_line(&mut self, line: String) {
+        if self.lines.len() == self.capacity && self.lines.pop_front().is_none() {
+            // Zero capacity: the line is evicted immediately.
+            return;
+        }
+        self.lines.push_back(line);
+    }
+}
+
+impl Write for ScrollbackBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while let Some(i) = rest.iter().position(|&b| b == b'\n') {
+            self.partial_line.extend_from_slice(&rest[..i]);
+            let line = String::from_utf8_lossy(&self.partial_line).into_owned();
+            self.partial_line.clear();
+            self.push_line(line);
+            rest = &rest[i + 1..];
+        }
+        self.partial_line.extend_from_slice(rest);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi;
+    use crate::delta::{DiffType, State};
+    use crate::paint::Painter;
+    use crate::tests::integration_test_utils::make_config_from_args;
+
+    #[test]
+    fn test_scrollback_buffer_retains_last_lines() {
+        let config = make_config_from_args(&["--keep-plus-minus-markers"]);
+        let mut scrollback = ScrollbackBuffer::new(3);
+        let mut painter = Painter::new(&mut scrollback, &config);
+        for line in ["a\n", "b\n", "c\n", "d\n", "e\n"] {
+            painter.paint_zero_line(line, &[], State::HunkZero(DiffType::Unified, None));
+            painter.emit().unwrap();
+        }
+        drop(painter);
+
+        let lines: Vec<String> = scrollback.lines().map(ansi::strip_ansi_codes).collect();
+        assert_eq!(lines, [" c", " d", " e"]);
+    }
+
+    #[test]
+    fn test_scrollback_buffer_partial_lines() {
+        let mut scrollback = ScrollbackBuffer::new(2);
+        write!(scrollback, "one\ntw").unwrap();
+        assert_eq!(scrollback.lines().collect::<Vec<_>>(), ["one"]);
+        write!(scrollback, "o\n\nthree").unwrap();
+        assert_eq!(scrollback.lines().collect::<Vec<_>>(), ["two", ""]);
+
+        let mut output = Vec::new();
+        scrollback.write_to(&mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "two\n\nthree");
+    }
+
+    #[test]
+    fn test_scrollback_buffer_zero_capacity() {
+        let mut scrollback = ScrollbackBuffer::new(0);
+        write!(scrollback, "one\ntwo\n").unwrap();
+        assert_eq!(scrollback.lines().count(), 0);
+    }
+}
@@ -16,6 +16,9 @@ pub struct Style {
     pub is_omitted: bool,
     pub is_raw: bool,
     pub is_syntax_highlighted: bool,
+    // Whether the text is the symbol of an expanded tab (see --tab-symbol), rather than text of
+    // the line that happens to be the same.
+    pub is_tab_symbol: bool,
     pub decoration_style: DecorationStyle,
 }
 
@@ -44,18 +47,19 @@ impl fmt::Debug for Style {
 
         write!(
             f,
-            "Style {{ {}{}{}{}{}{} }}",
+            "Style {{ {}{}{}{}{}{}{} }}",
             ansi,
             is_set('e', self.is_emph),
             is_set('o', self.is_omitted),
             is_set('r', self.is_raw),
             is_set('s', self.is_syntax_highlighted),
+            is_set('t', self.is_tab_symbol),
             deco
         )
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum DecorationStyle {
     Box(ansi_term::Style),
     Underline(ansi_term::Style),
@@ -64,15 +68,10 @@ pub enum DecorationStyle {
     BoxWithUnderline(ansi_term::Style),
     BoxWithOverline(ansi_term::Style),
     BoxWithUnderOverline(ansi_term::Style),
+    #[default]
     NoDecoration,
 }
 
-impl Default for DecorationStyle {
-    fn default() -> Self {
-        Self::NoDecoration
-    }
-}
-
 impl Style {
     pub fn new() -> Self {
         Self {
@@ -81,6 +80,7 @@ impl Style {
             is_omitted: false,
             is_raw: false,
             is_syntax_highlighted: false,
+            is_tab_symbol: false,
             decoration_style: DecorationStyle::NoDecoration,
         }
     }
@@ -483,24 +483,24 @@ pub mod tests {
     #[test]
     fn test_style_compact_debug_fmt() {
         let mut s = Style::new();
-        assert_eq!(format!("{s:?}"), "Style { <aeorsd> }");
+        assert_eq!(format!("{s:?}"), "Style { <aeorstd> }");
         s.is_emph = true;
-        assert_eq!(format!("{s:?}"), "Style { <aEorsd> }");
+        assert_eq!(format!("{s:?}"), "Style { <aEorstd> }");
         s.ansi_term_style = ansi_term::Style::new().bold();
         assert_eq!(
             format!("{s:?}"),
-            "Style { ansi_term_style: Style { bold }, <Eorsd> }"
+            "Style { ansi_term_style: Style { bold }, <Eorstd> }"
         );
         s.decoration_style = DecorationStyle::Underline(s.ansi_term_style);
         assert_eq!(
             format!("{s:?}"),
-            "Style { ansi_term_style: Style { bold }, <Eors>, \
+            "Style { ansi_term_style: Style { bold }, <Eorst>, \
                   decoration_style: Underline(Style { bold }) }"
         );
         s.ansi_term_style = ansi_term::Style::default();
         assert_eq!(
             format!("{s:?}"),
-            "Style { <aEors>, decoration_style: Underline(Style { bold }) }"
+            "Style { <aEorst>, decoration_style: Underline(Style { bold }) }"
         );
     }
 }
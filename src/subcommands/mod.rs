@@ -2,6 +2,8 @@
 pub mod generate_completion;
 pub mod list_syntax_themes;
 pub mod parse_ansi;
+pub mod parse_structure;
+pub mod preview_styles;
 mod sample_diff;
 pub mod show_colors;
 pub mod show_config;
@@ -0,0 +1,52 @@
+use std::io::{self, Read, Write};
+
+use crate::diff_structure;
+
+#[cfg(not(tarpaulin_include))]
+pub fn parse_structure() -> std::io::Result<()> {
+    let mut diff = Vec::new();
+    io::stdin().lock().read_to_end(&mut diff)?;
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    write_structure(&String::from_utf8_lossy(&diff), &mut stdout)
+}
+
+/// Write a JSON line for each file of `diff`.
+fn write_structure(diff: &str, writer: &mut dyn Write) -> std::io::Result<()> {
+    for file in diff_structure::parse_structure(diff) {
+        writeln!(writer, "{}", file.to_json())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_structure() {
+        let diff = "\
+diff --git a/src/one.rs b/src/two.rs
+similarity index 90%
+rename from src/one.rs
+rename to src/two.rs
+index 1111111..2222222 100644
+--- a/src/one.rs
++++ b/src/two.rs
+@@ -1,2 +1,3 @@
+ a
++b
+ c
+@@ -10 +11 @@ fn f() {
+-d
++e
+";
+        let mut output = Vec::new();
+        write_structure(diff, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#"{"hunks":[{"minus":[1,3],"plus":[1,4]},{"minus":[10,11],"plus":[11,12]}],"paths":{"minus":"src/one.rs","plus":"src/two.rs"}}
+"#
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use std::io::BufReader;
+
+use bytelines::ByteLines;
+
+use crate::config;
+use crate::delta;
+
+const PREVIEW_DIFF: &[u8] = b"\
+commit 0123456789abcdef0123456789abcdef01234567
+Author: A U Thor <author@example.com>
+Date:   Mon Jan 1 00:00:00 2024 +0000
+
+    Greet less formally
+
+diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,4 +1,4 @@ fn main() {
+ fn main() {
+-    let greeting = \"hello\";
++    let greeting = \"howdy\";
+     println!(\"{greeting}\");
+ }
+";
+
+/// Return a small diff painted according to `config`, showing each of its commit, file, hunk
+/// header, removed, unchanged and added line styles, including the emphasis of changed words.
+/// Invalid style strings have already been rejected when `config` was created.
+pub fn preview_styles(config: &config::Config) -> std::io::Result<String> {
+    let mut output = Vec::new();
+    delta::delta(
+        ByteLines::new(BufReader::new(PREVIEW_DIFF)),
+        &mut output,
+        config,
+    )?;
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi;
+    use crate::tests::integration_test_utils;
+
+    #[test]
+    fn test_preview_styles() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--syntax-theme=none",
+            "--commit-style=yellow",
+            "--commit-decoration-style=none",
+            "--file-style=blue",
+            "--file-decoration-style=none",
+            "--hunk-header-style=magenta",
+            "--hunk-header-decoration-style=none",
+            "--minus-style=red",
+            "--minus-emph-style=white red",
+            "--zero-style=dim",
+            "--plus-style=green",
+            "--plus-emph-style=white green",
+        ]);
+        let preview = ansi::explain_ansi(&preview_styles(&config).unwrap(), false);
+        for styled_section in [
+            "(yellow)commit 0123456789abcdef0123456789abcdef01234567",
+            "(blue)src/main.rs",
+            "(purple) fn main() { ",
+            "(red)    let greeting = \"(white red)hello",
+            "(dim normal)    println!(\"{greeting}\");",
+            "(green)    let greeting = \"(white green)howdy",
+        ] {
+            assert!(
+                preview.contains(styled_section),
+                "{:?} not found in:\n{}",
+                styled_section,
+                preview
+            );
+        }
+    }
+}
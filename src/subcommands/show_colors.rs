@@ -1,3 +1,5 @@
+use std::io::Write;
+
 use crate::cli;
 use crate::color;
 use crate::colors;
@@ -29,9 +31,7 @@ pub fn show_colors() -> std::io::Result<()> {
         OutputType::from_mode(&env, PagingMode::QuitIfOneScreen, None, &pagercfg).unwrap();
     let writer = output_type.handle().unwrap();
 
-    let mut painter = paint::Painter::new(writer, &config);
-    painter.set_syntax(Some("a.ts"));
-    painter.set_highlighter();
+    let mut painter = paint::Painter::with_syntax(writer, &config, Some("a.ts"));
 
     let title_style = ansi_term::Style::new().bold();
     let mut style = style::Style {
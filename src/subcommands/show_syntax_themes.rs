@@ -62,23 +62,7 @@ fn _show_syntax_themes(
     use std::io::BufReader;
     let input = match stdin {
         Some(stdin_data) => &stdin_data[..],
-        None => {
-            b"\
-diff --git a/example.rs b/example.rs
-index f38589a..0f1bb83 100644
---- a/example.rs
-+++ b/example.rs
-@@ -1,5 +1,5 @@
--// Output the square of a number.
--fn print_square(num: f64) {
--    let result = f64::powf(num, 2.0);
--    println!(\"The square of {:.2} is {:.2}.\", num, result);
-+// Output the cube of a number.
-+fn print_cube(num: f64) {
-+    let result = f64::powf(num, 3.0);
-+    println!(\"The cube of {:.2} is {:.2}.\", num, result);
-"
-        }
+        None => SAMPLE_DIFF,
     };
 
     opt.computed.color_mode = color_mode;
@@ -108,6 +92,48 @@ index f38589a..0f1bb83 100644
     Ok(())
 }
 
+// A diff of files in several languages, each with comments, keywords, strings and numbers.
+const SAMPLE_DIFF: &[u8] = b"\
+diff --git a/example.rs b/example.rs
+index f38589a..0f1bb83 100644
+--- a/example.rs
++++ b/example.rs
+@@ -1,5 +1,5 @@
+-// Output the square of a number.
+-fn print_square(num: f64) {
+-    let result = f64::powf(num, 2.0);
+-    println!(\"The square of {:.2} is {:.2}.\", num, result);
++// Output the cube of a number.
++fn print_cube(num: f64) {
++    let result = f64::powf(num, 3.0);
++    println!(\"The cube of {:.2} is {:.2}.\", num, result);
+diff --git a/example.py b/example.py
+index 5d1b3e1..8c2f9a4 100644
+--- a/example.py
++++ b/example.py
+@@ -1,4 +1,4 @@
+-# Output the square of a number.
+-def print_square(num: float) -> None:
+-    result = num ** 2
+-    print(f\"The square of {num:.2f} is {result:.2f}.\")
++# Output the cube of a number.
++def print_cube(num: float) -> None:
++    result = num ** 3
++    print(f\"The cube of {num:.2f} is {result:.2f}.\")
+diff --git a/example.json b/example.json
+index 2e0b9d7..a7c41f3 100644
+--- a/example.json
++++ b/example.json
+@@ -1,5 +1,5 @@
+ {
+-  \"name\": \"square\",
+-  \"exponent\": 2,
++  \"name\": \"cube\",
++  \"exponent\": 3,
+   \"enabled\": true
+ }
+";
+
 #[cfg(test)]
 mod tests {
     use std::io::{Cursor, Seek};
@@ -130,5 +156,7 @@ mod tests {
         assert!(s.contains("\nSyntax theme: gruvbox-light\n"));
         println!("{s}");
         assert!(s.contains("\nfn print_cube(num: f64) {\n"));
+        assert!(s.contains("\ndef print_cube(num: float) -> None:\n"));
+        assert!(s.contains("\n  \"name\": \"cube\",\n"));
     }
 }
@@ -6,6 +6,7 @@ pub mod ansi_test_utils {
     use crate::ansi;
     use crate::config::Config;
     use crate::delta::State;
+    use crate::emitter::AnsiEmitter;
     use crate::paint;
     use crate::style::Style;
 
@@ -173,14 +174,20 @@ pub mod ansi_test_utils {
     ) -> String {
         let mut output_buffer = String::new();
         let mut unused_writer = Vec::<u8>::new();
-        let mut painter = paint::Painter::new(&mut unused_writer, config);
-        let syntax_highlighted_style = Style {
-            is_syntax_highlighted: true,
-            ..Style::new()
+        let mut painter = paint::Painter::with_syntax(
+            &mut unused_writer,
+            config,
+            Some(filename_for_highlighting),
+        );
+        // Unchanged lines are painted in zero-style, which may add attributes to the syntax colors.
+        let syntax_highlighted_style = match state {
+            State::HunkZero(_, _) => config.zero_style,
+            _ => Style {
+                is_syntax_highlighted: true,
+                ..Style::new()
+            },
         };
-        painter.set_syntax(Some(filename_for_highlighting));
-        painter.set_highlighter();
-        let lines = vec![(line.to_string(), state)];
+        let lines = vec![(line.to_string(), state, paint::LineInfo::default())];
         let syntax_style_sections = paint::get_syntax_style_sections_for_lines(
             &lines,
             painter.highlighter.as_mut(),
@@ -192,7 +199,9 @@ pub mod ansi_test_utils {
             &syntax_style_sections,
             &diff_style_sections,
             &[false],
+            None,
             &mut output_buffer,
+            &AnsiEmitter,
             config,
             &mut None,
             None,
@@ -90,6 +90,21 @@ pub fn make_config_from_args(args: &[&str]) -> config::Config {
     config::Config::from(make_options_from_args(args))
 }
 
+/// Args painting removed and added lines plainly in red and green, their emphasized text
+/// underlined, and code without syntax highlighting, so that tests can tell the styles apart.
+pub const MINUS_PLUS_TEST_STYLE_ARGS: [&str; 5] = [
+    "--syntax-theme=none",
+    "--minus-style=red",
+    "--minus-emph-style=red ul",
+    "--plus-style=green",
+    "--plus-emph-style=green ul",
+];
+
+/// Return MINUS_PLUS_TEST_STYLE_ARGS followed by `args`.
+pub fn with_minus_plus_test_styles<'a>(args: &[&'a str]) -> Vec<&'a str> {
+    [&MINUS_PLUS_TEST_STYLE_ARGS[..], args].concat()
+}
+
 pub fn make_git_config(
     env: &DeltaEnv,
     contents: &[u8],
@@ -2,12 +2,17 @@
 mod tests {
     use crate::ansi::{self, strip_ansi_codes};
     use crate::cli::InspectRawLines;
-    use crate::delta::{DiffType, State};
+    use crate::config;
+    use crate::delta::{
+        delta_from_reader, DiffType, State, OSC_133_COMMAND_FINISHED, OSC_133_COMMAND_OUTPUT_START,
+    };
     use crate::handlers::hunk_header::ParsedHunkHeader;
     use crate::style;
     use crate::tests::ansi_test_utils::ansi_test_utils;
     use crate::tests::integration_test_utils;
-    use crate::tests::integration_test_utils::DeltaTest;
+    use crate::tests::integration_test_utils::{
+        with_minus_plus_test_styles, DeltaTest, MINUS_PLUS_TEST_STYLE_ARGS,
+    };
     use insta::assert_snapshot;
 
     #[test]
@@ -169,6 +174,81 @@ index 0123456..1234567 100644
         assert_eq!(expected, ansi);
     }
 
+    #[test]
+    fn test_zero_style_dims_syntax_highlighting_by_default() {
+        let output = DeltaTest::with_args(&[])
+            .with_input(DIFF_WITH_CONTEXT_LINE_OF_RUST)
+            .raw_output;
+        let lines: Vec<&str> = output.lines().collect();
+        let n = lines.len();
+        // The unchanged line is dimmed, and keeps the colors of its syntax highlighting.
+        assert!(lines[n - 3].starts_with("\x1b[2;38;5;81mfn\x1b[38;5;231m \x1b[38;5;149mf"));
+        // The removed and added lines are not dimmed.
+        for line in &lines[n - 2..] {
+            assert!(!line.contains("\x1b[2;") && !line.contains(";2;"));
+        }
+
+        // The dimming can be turned off.
+        let output = DeltaTest::with_args(&["--zero-style", "syntax normal"])
+            .with_input(DIFF_WITH_CONTEXT_LINE_OF_RUST)
+            .raw_output;
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[lines.len() - 3].starts_with("\x1b[38;5;81mfn"));
+    }
+
+    #[test]
+    fn test_zebra() {
+        DeltaTest::with_args(&[
+            "--zebra",
+            "--zebra-style=normal blue",
+            "--zero-style=normal",
+            "--width=variable",
+        ])
+        .explain_ansi()
+        .with_input(DIFF_WITH_TWO_HUNKS_WITH_CONTEXT)
+        // Unchanged lines alternate between zero-style and zebra-style, from the start of each hunk.
+        .expect_contains(
+            "\na\n\
+             (normal blue)b(normal)\n\
+             c\n\
+             (normal 52)x(normal)\n\
+             (231 22)y(normal)\n\
+             (normal blue)d(normal)\n",
+        )
+        .expect_contains(
+            "\ne\n\
+             (normal 52)x(normal)\n\
+             (231 22)y(normal)\n\
+             (normal blue)f(normal)\n\
+             g\n",
+        );
+    }
+
+    #[test]
+    fn test_output_buffer_limit_does_not_change_output() {
+        let output = DeltaTest::with_args(&[])
+            .with_input(GIT_DIFF_SINGLE_HUNK)
+            .raw_output;
+        let limited_output = DeltaTest::with_args(&["--output-buffer-limit=1"])
+            .with_input(GIT_DIFF_SINGLE_HUNK)
+            .raw_output;
+        assert_eq!(limited_output, output);
+    }
+
+    #[test]
+    fn test_file_and_hunk_separators() {
+        let result =
+            DeltaTest::with_args(&["--file-separator==", "--hunk-separator=~", "--width=8"])
+                .with_input(TWO_FILES_THE_FIRST_WITH_TWO_HUNKS);
+        let count = |line| result.output.lines().filter(|l| *l == line).count();
+        // Each separator is displayed between, and not before, the files or hunks of a file.
+        assert_eq!(count("========"), 1);
+        assert_eq!(count("~~~~~~~~"), 1);
+        result
+            .expect_contains("def\n~~~~~~~~\n")
+            .expect_contains("jkl\n========\n\nbar\n");
+    }
+
     #[test]
     fn test_diff_unified_two_files() {
         let config =
@@ -217,6 +297,36 @@ index 0123456..1234567 100644
             .expect_contains("\nLINES.\n\n1/y 2022-03-06");
     }
 
+    #[test]
+    fn test_diff_unified_syntax_is_detected_from_plus_file() {
+        // Use color-only so that we can refer to the line numbers from the input diff.
+        let config = integration_test_utils::make_config_from_args(&["--color-only"]);
+        for input in [DIFF_UNIFIED_OF_BACKUP_FILE, QUILT_PATCH] {
+            let output = integration_test_utils::run_delta(input, &config);
+            ansi_test_utils::assert_line_has_syntax_highlighted_substring(
+                &output,
+                input.lines().position(|l| l == " fn f() {}").unwrap(),
+                1,
+                "fn f() {}",
+                "lib.rs",
+                State::HunkZero(DiffType::Unified, None),
+                &config,
+            );
+        }
+    }
+
+    #[test]
+    fn test_quilt_patch() {
+        DeltaTest::with_args(&["--file-modified-label", "comparing:"])
+            .with_input(QUILT_PATCH)
+            .expect_contains(
+                "Index: foo/src/lib.rs\n\
+                 ===================================================================\n\
+                 \n\
+                 comparing: foo.orig/src/lib.rs ⟶   foo/src/lib.rs\n",
+            );
+    }
+
     #[test]
     #[ignore] // Ideally, delta would make this test pass. See #121.
     fn test_delta_ignores_non_diff_input() {
@@ -989,6 +1099,36 @@ src/align.rs
         );
     }
 
+    #[test]
+    fn test_hunk_header_marker() {
+        let args = [
+            "--hunk-header-style",
+            "raw",
+            "--hunk-header-decoration-style",
+            "omit",
+        ];
+        let config = integration_test_utils::make_config_from_args(
+            &[&args[..], &["--hunk-header-marker", ""]].concat(),
+        );
+        let output = integration_test_utils::run_delta(GIT_DIFF_SINGLE_HUNK, &config);
+        ansi_test_utils::assert_line_has_no_color(
+            &output,
+            9,
+            "-71,11 +71,8 impl<'a> Alignment<'a> {",
+        );
+        assert!(!output.contains("@@"));
+
+        let config = integration_test_utils::make_config_from_args(
+            &[&args[..], &["--hunk-header-marker", "§"]].concat(),
+        );
+        let output = integration_test_utils::run_delta(GIT_DIFF_SINGLE_HUNK, &config);
+        ansi_test_utils::assert_line_has_no_color(
+            &output,
+            9,
+            "§ -71,11 +71,8 § impl<'a> Alignment<'a> {",
+        );
+    }
+
     #[test]
     fn test_color_only_output_is_in_one_to_one_correspondence_with_input() {
         let user_suppliable_configs: &[&[&str]] = &[
@@ -1237,6 +1377,31 @@ src/delta.rs:1: │
         assert!(output.contains("\n71: \n"));
     }
 
+    #[test]
+    fn test_hunk_header_line_number_and_code_fragment_styles() {
+        DeltaTest::with_args(&[
+            "--syntax-theme=none",
+            "--hunk-header-style=line-number magenta",
+            "--hunk-header-line-number-style=yellow",
+            "--hunk-header-decoration-style=none",
+        ])
+        .explain_ansi()
+        .with_input(GIT_DIFF_SINGLE_HUNK)
+        .expect_contains("\n(yellow)71(normal):(purple) impl<'a> Alignment<'a> { (normal)\n");
+
+        DeltaTest::with_args(&[
+            "--syntax-theme=GitHub",
+            "--hunk-header-style=line-number syntax",
+            "--hunk-header-line-number-style=yellow",
+            "--hunk-header-decoration-style=none",
+        ])
+        .explain_ansi()
+        .with_input(GIT_DIFF_SINGLE_HUNK)
+        .expect_contains(
+            "\n(yellow)71(normal):(236) (125)impl(236)<(125)'a(236)> Alignment<(125)'a(236)> { (normal)\n",
+        );
+    }
+
     #[test]
     fn test_hunk_header_style_colored_input_color_is_stripped_under_normal() {
         let config = integration_test_utils::make_config_from_args(&[
@@ -1620,134 +1785,1335 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
     }
 
     #[test]
-    fn test_whitespace_error() {
-        let whitespace_error_style = "bold yellow red ul";
-        let config = integration_test_utils::make_config_from_args(&[
-            "--whitespace-error-style",
-            whitespace_error_style,
-        ]);
-        let output = integration_test_utils::run_delta(DIFF_WITH_WHITESPACE_ERROR, &config);
-        ansi_test_utils::assert_line_has_style(&output, 8, " ", whitespace_error_style, &config);
-        let output = integration_test_utils::run_delta(DIFF_WITH_REMOVED_WHITESPACE_ERROR, &config);
-        ansi_test_utils::assert_line_does_not_have_style(
-            &output,
-            8,
-            " ",
-            whitespace_error_style,
-            &config,
-        );
+    fn test_comment_emphasis() {
+        let args = [
+            "--syntax-theme=none",
+            "--minus-style=red",
+            "--minus-emph-style=bold red",
+            "--plus-style=green",
+            "--plus-emph-style=bold green",
+        ];
+        DeltaTest::with_args(&args)
+            .explain_ansi()
+            .with_input(DIFF_WITH_CODE_AND_COMMENT_CHANGE)
+            .expect_contains(
+                "(red)let (bold normal)x(red) = 1; // (bold normal)old(red) comment(normal)",
+            )
+            .expect_contains(
+                "(green)let (bold normal)y(green) = 1; // (bold normal)new(green) comment(normal)",
+            );
+        DeltaTest::with_args(&[&args[..], &["--comment-emphasis=suppress"]].concat())
+            .explain_ansi()
+            .with_input(DIFF_WITH_CODE_AND_COMMENT_CHANGE)
+            .expect_contains("(red)let (bold normal)x(red) = 1; // old comment(normal)")
+            .expect_contains("(green)let (bold normal)y(green) = 1; // new comment(normal)");
+        DeltaTest::with_args(&[&args[..], &["--comment-emphasis=only"]].concat())
+            .explain_ansi()
+            .with_input(DIFF_WITH_CODE_AND_COMMENT_CHANGE)
+            .expect_contains("(red)let x = 1; // (bold normal)old(red) comment(normal)")
+            .expect_contains("(green)let y = 1; // (bold normal)new(green) comment(normal)");
     }
 
     #[test]
-    fn test_whitespace_unrelated_edit_text_error() {
-        let whitespace_error_style = "bold yellow red ul";
-        let config = integration_test_utils::make_config_from_args(&[
-            "--whitespace-error-style",
-            whitespace_error_style,
-        ]);
-        let output =
-            integration_test_utils::run_delta(DIFF_WITH_WHITESPACE_UNRELATED_EDIT_ERROR, &config);
-        ansi_test_utils::assert_line_contain_substring_style(
-            &output,
-            9,
-            "some new",
-            "    ",
-            whitespace_error_style,
-            &config,
+    fn test_inline_edits() {
+        DeltaTest::with_args(&[
+            "--inline-edits",
+            "--syntax-theme=none",
+            "--zero-style=normal",
+            "--minus-emph-style=red strike",
+            "--plus-emph-style=green ul",
+        ])
+        .explain_ansi()
+        .with_input(DIFF_WITH_CODE_AND_COMMENT_CHANGE)
+        .expect_contains(
+            "\nlet (strike red)x(ul green)y(normal) = 1; // (strike red)old(ul green)new(normal) comment\n",
         );
     }
 
     #[test]
-    fn test_whitespace_edit_text_error() {
-        let whitespace_error_style = "bold yellow red ul";
-        let config = integration_test_utils::make_config_from_args(&[
-            "--whitespace-error-style",
-            whitespace_error_style,
-        ]);
-        let output = integration_test_utils::run_delta(DIFF_WITH_WHITESPACE_EDIT_ERROR, &config);
-        ansi_test_utils::assert_line_contain_substring_style(
-            &output,
-            9,
-            "same ",
-            "    ",
-            whitespace_error_style,
-            &config,
-        );
+    fn test_inline_edits_requires_a_single_pair_of_lines() {
+        DeltaTest::with_args(&["--inline-edits"])
+            .with_input(DIFF_WITH_TWO_REMOVED_LINES_AND_ONE_ADDED_LINE)
+            .expect_contains("\nlet x = 1;\nlet z = 1;\nlet y = 1;\n");
     }
 
     #[test]
-    fn test_whitespace_added_empty_line_error() {
-        let whitespace_error_style = "bold yellow red ul";
-        let config = integration_test_utils::make_config_from_args(&[
-            "--whitespace-error-style",
-            whitespace_error_style,
-        ]);
-        let output =
-            integration_test_utils::run_delta(DIFF_WITH_ADDED_WHITESPACE_EMPTY_LINE_ERROR, &config);
-        // TODO is this the first style ?
-        ansi_test_utils::assert_line_has_style(&output, 9, " ", whitespace_error_style, &config);
-        ansi_test_utils::assert_line_contain_substring_style(
-            &output,
-            9,
-            "",
-            "   ",
-            whitespace_error_style,
-            &config,
+    fn test_gutter() {
+        DeltaTest::with_args(&[
+            "--gutter",
+            "--syntax-theme=none",
+            "--minus-style=normal",
+            "--plus-style=normal",
+            "--minus-emph-style=normal",
+            "--plus-emph-style=normal",
+        ])
+        .explain_ansi()
+        .with_input(DIFF_WITH_REMOVED_ADDED_AND_CHANGED_LINES)
+        .expect_after_skip(
+            8,
+            r#"
+#indent_mark
+ (dim normal)fn main() {(normal)
+(red)█(normal)    removed();
+ (dim normal)    context();(normal)
+(yellow)█(normal)    let x = 1;
+(yellow)█(normal)    let y = 1;
+ (dim normal)    context();(normal)
+(green)█(normal)    added();
+ (dim normal)}(normal)"#,
+        );
+        DeltaTest::with_args(&[
+            "--gutter",
+            "--gutter-symbol=▌",
+            "--gutter-minus-style=blue",
+            "--gutter-plus-style=cyan",
+            "--gutter-changed-style=magenta",
+            "--gutter-zero-style=white",
+            "--syntax-theme=none",
+            "--minus-style=normal",
+            "--plus-style=normal",
+            "--minus-emph-style=normal",
+            "--plus-emph-style=normal",
+        ])
+        .explain_ansi()
+        .with_input(DIFF_WITH_REMOVED_ADDED_AND_CHANGED_LINES)
+        .expect_after_skip(
+            8,
+            r#"
+(white)▌(dim normal)fn main() {(normal)
+(blue)▌(normal)    removed();
+(white)▌(dim normal)    context();(normal)
+(purple)▌(normal)    let x = 1;
+(purple)▌(normal)    let y = 1;
+(white)▌(dim normal)    context();(normal)
+(cyan)▌(normal)    added();
+(white)▌(dim normal)}(normal)"#,
         );
     }
 
     #[test]
-    fn test_whitespace_after_text_error() {
-        let whitespace_error_style = "bold yellow red ul";
-        let config = integration_test_utils::make_config_from_args(&[
-            "--whitespace-error-style",
-            whitespace_error_style,
-        ]);
-        let output =
-            integration_test_utils::run_delta(DIFF_WITH_WHITESPACE_AFTER_TEXT_ERROR, &config);
-        ansi_test_utils::assert_line_contain_substring_style(
-            &output,
-            8,
-            "foo bar",
-            "  ",
-            whitespace_error_style,
-            &config,
-        );
-        let output = integration_test_utils::run_delta(
-            DIFF_WITH_REMOVED_WHITESPACE_AFTER_TEXT_ERROR,
-            &config,
+    fn test_operator_emph_style() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--operator-emph-style=yellow reverse",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_OPERATOR_AND_IDENTIFIER_CHANGES)
+        .expect_contains("\n(red)if a (reverse yellow)=(red)= b {(normal)\n")
+        .expect_contains("\n(green)if a (reverse yellow)!(green)= b {(normal)\n")
+        .expect_contains("\n(red)let (ul normal)count(red) = 1;(normal)\n")
+        .expect_contains("\n(green)let (ul normal)total(green) = 1;(normal)\n");
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_OPERATOR_AND_IDENTIFIER_CHANGES)
+            .expect_contains("\n(red)if a (ul normal)=(red)= b {(normal)\n");
+    }
+
+    #[test]
+    fn test_punctuation_emph_style() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--punctuation-emph-style=yellow reverse",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_ADDED_TRAILING_COMMA)
+        .expect_contains("\n(green)    a(reverse yellow),(normal)\n");
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_ADDED_TRAILING_COMMA)
+            .expect_contains("\n(green)    a(ul normal),(normal)\n");
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--punctuation-emph-style=yellow reverse",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_SCATTERED_EDITS)
+        .expect_contains("\n(green)call((ul normal)x(green), (ul normal)y(green), c);(normal)\n");
+    }
+
+    #[test]
+    fn test_emph_token_regex() {
+        let args = with_minus_plus_test_styles(&["--word-diff-regex=."]);
+        DeltaTest::with_args(&args)
+            .explain_ansi()
+            .with_input(DIFF_WITH_CHANGE_ACROSS_TOKEN_BOUNDARY)
+            .expect_contains("\n(red)say(hel(ul normal)lo wo(red)rld);(normal)\n")
+            .expect_contains("\n(green)say(hel(ul normal)xy zz(green)rld);(normal)\n");
+        DeltaTest::with_args(&[&args[..], &[r"--emph-token-regex=\w+"]].concat())
+            .explain_ansi()
+            .with_input(DIFF_WITH_CHANGE_ACROSS_TOKEN_BOUNDARY)
+            .expect_contains("\n(red)say((ul normal)hello world(red));(normal)\n")
+            .expect_contains("\n(green)say((ul normal)helxy zzrld(green));(normal)\n");
+    }
+
+    #[test]
+    fn test_emphasis_records_file() {
+        let path = std::env::temp_dir().join("delta-test-emphasis-records-file.jsonl");
+        let arg = format!("--emphasis-records-file={}", path.display());
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[&arg]))
+            .with_input(DIFF_WITH_TWO_REMOVED_LINES_AND_ONE_ADDED_LINE);
+        let records = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            records,
+            r#"{"file":"a.rs","line_number":1,"ranges":[[4,5]],"side":"minus"}
+{"file":"a.rs","line_number":1,"ranges":[[4,5]],"side":"plus"}
+"#
         );
-        ansi_test_utils::assert_line_does_not_contain_substring_style(
-            &output,
-            8,
-            "foo bar",
-            whitespace_error_style,
-            &config,
+    }
+
+    #[test]
+    fn test_output_format_html() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&["--output-format=html"]))
+            .with_input(&DIFF_WITH_TWO_REMOVED_LINES_AND_ONE_ADDED_LINE.replace("1;", "a < b;"))
+            .expect_raw_contains(
+                "\n<div><span style=\"color:#cd0000\">let </span>\
+                 <span style=\"color:#cd0000;text-decoration:underline\">x</span>\
+                 <span style=\"color:#cd0000\"> = a &lt; b;</span></div>\n\
+                 <div><span style=\"color:#cd0000\">let z = a &lt; b;</span></div>\n\
+                 <div><span style=\"color:#00cd00\">let </span>\
+                 <span style=\"color:#00cd00;text-decoration:underline\">y</span>\
+                 <span style=\"color:#00cd00\"> = a &lt; b;</span></div>\n",
+            );
+    }
+
+    #[test]
+    fn test_scrollback_lines() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&["--scrollback-lines=2"]))
+            .explain_ansi()
+            .with_input(DIFF_WITH_TWO_REMOVED_LINES_AND_ONE_ADDED_LINE)
+            .expect(
+                "
+(red)let z = 1;(normal)
+(green)let (ul normal)y(green) = 1;(normal)",
+            );
+    }
+
+    #[test]
+    fn test_plus_lines_first() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&["--plus-lines-first"]))
+            .explain_ansi()
+            .with_input(DIFF_WITH_TWO_REMOVED_LINES_AND_ONE_ADDED_LINE)
+            .expect_after_skip(
+                8,
+                "
+(green)let (ul normal)y(green) = 1;(normal)
+(red)let (ul normal)x(red) = 1;(normal)
+(red)let z = 1;(normal)",
+            );
+    }
+
+    #[test]
+    fn test_rename_emph_style() {
+        let args = with_minus_plus_test_styles(&["--rename-emph-style=blue"]);
+        DeltaTest::with_args(&[&args[..], &["--rename-map=old_name=new_name"]].concat())
+            .explain_ansi()
+            .with_input(DIFF_WITH_RENAME_AND_OTHER_CHANGE)
+            .expect_contains("\n(red)let x = (blue)old_name(red)((ul normal)a(red));(normal)\n")
+            .expect_contains(
+                "\n(green)let x = (blue)new_name(green)((ul normal)b(green));(normal)\n",
+            );
+        DeltaTest::with_args(&args)
+            .explain_ansi()
+            .with_input(DIFF_WITH_RENAME_AND_OTHER_CHANGE)
+            .expect_contains(
+                "\n(green)let x = (ul normal)new_name(green)((ul normal)b(green));(normal)\n",
+            );
+    }
+
+    #[test]
+    fn test_pair_swap_emph_style() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--pair-swap-emph-style=yellow bold",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_PAIR_SWAP_AND_OTHER_CHANGE)
+        .expect_contains("\n(red)let x = f((bold yellow)true(red), (ul normal)a(red));(normal)\n")
+        .expect_contains(
+            "\n(green)let x = f((bold yellow)false(green), (ul normal)b(green));(normal)\n",
         );
     }
 
     #[test]
-    fn test_whitespace_complex_error() {
-        let whitespace_error_style = "bold yellow red ul";
-        let config = integration_test_utils::make_config_from_args(&[
-            "--whitespace-error-style",
-            whitespace_error_style,
-        ]);
-        let output = integration_test_utils::run_delta(DIFF_WITH_WHITESPACE_COMPLEX_ERROR, &config);
-        // `minus` line should not display whitespace error
-        ansi_test_utils::assert_line_does_not_have_style(
-            &output,
+    fn test_emph_gradient() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--true-color=always",
+            "--emph-gradient=#ff0000 #0000ff",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_PAIR_SWAP_AND_OTHER_CHANGE)
+        .expect_contains(concat!(
+            "\n(red)let x = f(",
+            r##"(ul normal "#ff0000")t(normal "#aa0055")r(normal "#5500aa")u(normal "#0000ff")e"##,
+            r##"(red), (ul normal "#ff0000")a(red));(normal)"##,
+            "\n"
+        ))
+        .expect_contains(concat!(
+            "\n(green)let x = f(",
+            r##"(ul normal "#ff0000")f(normal "#bf0040")a(normal "#800080")l(normal "#4000bf")"##,
+            r##"s(normal "#0000ff")e(green), (ul normal "#ff0000")b(green));(normal)"##,
+            "\n"
+        ));
+    }
+
+    #[test]
+    fn test_trailing_comment_emph_style() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--trailing-comment-emph-style=yellow",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_TRAILING_COMMENT_CHANGES)
+        .expect_contains("\n(red)let x = 1;(normal)\n")
+        .expect_contains("\n(green)let x = 1;  (yellow)// fixed(normal)\n")
+        .expect_contains("\n(red)let y = 2; // (yellow)old(normal)\n")
+        .expect_contains("\n(green)let y = 2; // (yellow)new(normal)\n")
+        .expect_contains("\n(red)let z = (ul normal)3(red); // z(normal)\n");
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_TRAILING_COMMENT_CHANGES)
+            .expect_contains("\n(green)let x = 1;(ul normal)  // fixed(normal)\n");
+    }
+
+    #[test]
+    fn test_scope_change_emph_style() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--scope-change-emph-style=yellow",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_KEYWORD_REPLACED_BY_IDENTIFIER)
+        .expect_after_skip(
             8,
-            "  ",
-            whitespace_error_style,
-            &config,
-        );
-        ansi_test_utils::assert_line_does_not_have_style(
-            &output,
-            9,
-            "  ",
-            whitespace_error_style,
-            &config,
+            "
+(red)    (yellow)return(red) value(normal)
+(red)    total = (ul normal)count(normal)
+(green)    (yellow)result(green) value(normal)
+(green)    total = (ul normal)amount(normal)",
+        );
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_KEYWORD_REPLACED_BY_IDENTIFIER)
+            .expect_contains("\n(red)    (ul normal)return(red) value(normal)\n");
+    }
+
+    #[test]
+    fn test_emphasis_algorithm_lcs() {
+        // The default algorithm emphasizes whole changed tokens; the LCS emphasizes only the
+        // characters inserted within them.
+        DeltaTest::with_args(&with_minus_plus_test_styles(&["--emphasis-algorithm=lcs"]))
+            .explain_ansi()
+            .with_input(DIFF_WITH_INTERLEAVED_EDITS)
+            .expect_after_skip(
+                8,
+                "
+(red)let total = price * qty;(normal)
+(green)let (ul normal)sub(green)total = price * q(ul normal)uan(green)t(ul normal)it(green)y;(normal)",
+            );
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_INTERLEAVED_EDITS)
+            .expect_after_skip(
+                8,
+                "
+(red)let (ul normal)total(red) = price * (ul normal)qty(red);(normal)
+(green)let (ul normal)subtotal(green) = price * (ul normal)quantity(green);(normal)",
+            );
+    }
+
+    #[test]
+    fn test_formatting_emph_style() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--formatting-emph-style=dim",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_FORMATTING_AND_MEANINGFUL_CHANGES)
+        .expect_contains(
+            "\n(red)let (dim normal)Total(red) = compute(a, (dim normal) (red)b);(normal)\n",
+        )
+        .expect_contains("\n(green)let (dim normal)total(green) = compute(a, b);(normal)\n")
+        .expect_contains("\n(red)let (dim normal)Count(red) = (ul normal)1(red);(normal)\n")
+        .expect_contains("\n(green)let (dim normal)count(green) = (ul normal)2(green);(normal)\n");
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_FORMATTING_AND_MEANINGFUL_CHANGES)
+            .expect_contains("\n(red)let (ul normal)Count(red) = (ul normal)1(red);(normal)\n");
+    }
+
+    #[test]
+    fn test_emphasize_additions_only() {
+        let args = with_minus_plus_test_styles(&["--minus-non-emph-style=red bold"]);
+        DeltaTest::with_args(&[&args[..], &["--emphasize-additions-only"]].concat())
+            .explain_ansi()
+            .with_input(DIFF_WITH_OPERATOR_AND_IDENTIFIER_CHANGES)
+            .expect_contains("\n(red)let count = 1;(normal)\n")
+            .expect_contains("\n(green)let (ul normal)total(green) = 1;(normal)\n");
+        DeltaTest::with_args(&args)
+            .explain_ansi()
+            .with_input(DIFF_WITH_OPERATOR_AND_IDENTIFIER_CHANGES)
+            .expect_contains("\n(bold red)let (ul red)count(bold red) = 1;(normal)\n");
+    }
+
+    #[test]
+    fn test_tab_symbol() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--tabs=4",
+            "--tab-symbol=→",
+            "--inline-hint-style=blue",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_TABS)
+        .expect_after_skip(
+            8,
+            r#"
+(blue)→(red)   foo((ul normal)a(red));(blue)→(red)// x(normal)
+(blue)→(green)   foo((ul normal)bc(green));(blue)→(ul green)   (green)// x(normal)
+(dim blue)→(dim normal)   x(blue)→(dim normal)  y(normal)"#,
+        );
+    }
+
+    #[test]
+    fn test_tab_symbol_in_code_is_not_styled() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--tabs=4",
+            "--tab-symbol=→",
+            "--inline-hint-style=blue",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_TABS_AND_ARROWS)
+        .expect_after_skip(
+            8,
+            r#"
+(blue)→(red)   a → (ul normal)b(normal)
+(blue)→(green)   a → (ul normal)c(normal)
+(dim normal)x(blue)→(dim normal)  // →(normal)"#,
+        );
+    }
+
+    #[test]
+    fn test_show_whitespace() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--tabs=4",
+            "--show-whitespace",
+            "--inline-hint-style=blue",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_LEADING_TABS_AND_SPACES)
+        .expect_after_skip(
+            8,
+            r#"
+(dim blue)→(red)   foo(a);(normal)
+(dim blue)→(green)   foo(a);(dim ul normal)·(ul green)//(dim normal)·(ul green)x(normal)
+(dim normal)··(blue)→(dim normal) x·(blue)→(dim normal) y(normal)"#,
+        );
+    }
+
+    #[test]
+    fn test_show_whitespace_does_not_mark_tab_symbols_in_code() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--tabs=4",
+            "--show-whitespace",
+            "--inline-hint-style=blue",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_TABS_AND_ARROWS)
+        .expect_after_skip(
+            8,
+            r#"
+(dim blue)→(red)   a(dim normal)·(red)→(dim normal)·(ul red)b(normal)
+(dim blue)→(green)   a(dim normal)·(green)→(dim normal)·(ul green)c(normal)
+(dim normal)x(blue)→(dim normal)  //·→(normal)"#,
+        );
+    }
+
+    #[test]
+    fn test_color_moved() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--color-moved",
+            "--moved-style=blue",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_MOVED_BLOCK)
+        .expect_after_skip(
+            8,
+            "
+(blue)fn a() {(normal)
+(blue)    1(normal)
+(blue)}(normal)
+(dim normal)fn b() {(normal)
+(red)    2(normal)
+(green)    3(normal)
+(dim normal)}(normal)
+(blue)fn a() {(normal)
+(blue)    1(normal)
+(blue)}(normal)
+
+(green)fn c() {}(normal)",
+        );
+        // Without --color-moved, the block is painted as removed and added lines.
+        DeltaTest::with_args(&[
+            "--syntax-theme=none",
+            "--minus-style=red",
+            "--plus-style=green",
+        ])
+        .explain_ansi()
+        .with_input(DIFF_WITH_MOVED_BLOCK)
+        .expect_contains("\n(red)fn a() {(normal)\n");
+    }
+
+    #[test]
+    fn test_wrap_lines() {
+        DeltaTest::with_args(&[
+            "--wrap-lines",
+            "--keep-plus-minus-markers",
+            "--syntax-theme=none",
+            "--inline-hint-style=blue",
+            "--minus-style=red",
+            "--plus-style=green",
+        ])
+        .set_config(|c| c.available_terminal_width = 20)
+        .explain_ansi()
+        .with_input(DIFF_WITH_LONG_LINES)
+        .expect_after_skip(
+            8,
+            "
+(red)-exactly twenty cols(normal)
+(green)+this line is much t(normal)
+(blue)…(green)oo long for a narro(normal)
+(blue)…(green)w terminal(normal)",
+        );
+    }
+
+    #[test]
+    fn test_wrap_lines_with_line_numbers() {
+        DeltaTest::with_args(&["--wrap-lines", "--line-numbers"])
+            .set_config(|c| c.available_terminal_width = 29)
+            .with_input(DIFF_WITH_LONG_LINES)
+            .expect_after_skip(
+                8,
+                "
+#indent_mark
+  1 ⋮    │exactly twenty cols
+    ⋮  1 │this line is much t
+         …oo long for a narro
+         …w terminal",
+            );
+    }
+
+    #[test]
+    fn test_truncate_lines() {
+        DeltaTest::with_args(&[
+            "--truncate-lines",
+            "--keep-plus-minus-markers",
+            "--syntax-theme=none",
+            "--inline-hint-style=blue",
+            "--minus-style=red",
+            "--plus-style=white green",
+        ])
+        .set_config(|c| c.available_terminal_width = 20)
+        .explain_ansi()
+        .with_input(DIFF_WITH_LONG_LINES)
+        .expect_after_skip(
+            8,
+            "
+(red)-exactly twenty cols(normal)
+(white green)+this line is much (blue green)…(normal)",
+        );
+    }
+
+    #[test]
+    fn test_truncate_lines_at_double_width_character() {
+        // The cut would split 'ｊ', which is replaced by a space.
+        DeltaTest::with_args(&["--truncate-lines"])
+            .set_config(|c| c.available_terminal_width = 20)
+            .with_input(DIFF_WITH_LONG_LINE_OF_DOUBLE_WIDTH_CHARACTERS)
+            .expect_after_skip(
+                8,
+                "
+ａｂｃｄｅｆｇ
+ａｂｃｄｅｆｇｈｉ …",
+            );
+    }
+
+    #[test]
+    fn test_collapse_leading_context() {
+        DeltaTest::with_args(&["--collapse-leading-context=5"])
+            .with_input(DIFF_WITH_LONG_LEADING_CONTEXT)
+            .expect_after_skip(
+                8,
+                "
+(… 6 lines …)
+let x = 1;
+let x = 2;
+seven",
+            );
+        DeltaTest::with_args(&["--collapse-leading-context=5", "--line-numbers"])
+            .with_input(DIFF_WITH_LONG_LEADING_CONTEXT)
+            .expect_contains(
+                "(… 6 lines …)\n  7 ⋮    │let x = 1;\n    ⋮  7 │let x = 2;\n  8 ⋮  8 │seven\n",
+            );
+        DeltaTest::with_args(&["--collapse-leading-context=6"])
+            .with_input(DIFF_WITH_LONG_LEADING_CONTEXT)
+            .expect_contains("\none\ntwo\nthree\nfour\nfive\nsix\nlet x = 1;\n");
+    }
+
+    #[test]
+    fn test_emph_merge_gap() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&["--emph-merge-gap=2"]))
+            .explain_ansi()
+            .with_input(DIFF_WITH_SCATTERED_EDITS)
+            .expect_contains("\n(red)call((ul normal)a, b(red), c);(normal)\n")
+            .expect_contains("\n(green)call((ul normal)x, y(green), c);(normal)\n");
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_SCATTERED_EDITS)
+            .expect_contains("\n(red)call((ul normal)a(red), (ul normal)b(red), c);(normal)\n");
+    }
+
+    #[test]
+    fn test_reordered_lines_style() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--reordered-lines-style=yellow",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_REORDERED_LINES)
+        .expect_after_skip(
+            8,
+            r#"
+                (yellow)use b;(normal)
+                (yellow)use a;(normal)
+                (yellow)use a;(normal)
+                (yellow)use b;(normal)"#,
+        );
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--reordered-lines-style=yellow",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_SCATTERED_EDITS)
+        .expect_contains("\n(red)call((ul normal)a(red), (ul normal)b(red), c);(normal)\n");
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_REORDERED_LINES)
+            .expect_contains("\n(red)use (ul normal)b(red);(normal)\n");
+    }
+
+    #[test]
+    fn test_matching_bracket_style() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&["--matching-bracket-style=bold"]))
+            .explain_ansi()
+            .with_input(DIFF_WITH_SCATTERED_EDITS)
+            .expect_contains(
+                "\n(red)call(bold normal)((ul red)a(red), (ul normal)b(red), c(bold normal))(red);(normal)\n",
+            )
+            .expect_contains(
+                "\n(green)call(bold normal)((ul green)x(green), (ul normal)y(green), c(bold normal))(green);(normal)\n",
+            );
+    }
+
+    #[test]
+    fn test_completion_notification() {
+        let output = DeltaTest::with_args(&["--completion-notification=bell"])
+            .with_input(DIFF_WITH_SCATTERED_EDITS)
+            .raw_output;
+        let (output, notification) = output.split_at(output.len() - 1);
+        assert!(strip_ansi_codes(output).ends_with("call(x, y, c);\n"));
+        assert_eq!(notification, "\x07");
+
+        let output = DeltaTest::with_args(&["--completion-notification=title:diff done"])
+            .with_input(DIFF_WITH_SCATTERED_EDITS)
+            .raw_output;
+        let (output, notification) = output.split_at(output.len() - "\x1b]0;diff done\x07".len());
+        assert!(strip_ansi_codes(output).ends_with("call(x, y, c);\n"));
+        assert_eq!(notification, "\x1b]0;diff done\x07");
+
+        let output = DeltaTest::with_args(&[])
+            .with_input(DIFF_WITH_SCATTERED_EDITS)
+            .raw_output;
+        assert!(!output.contains('\x07'));
+    }
+
+    #[test]
+    fn test_density_column() {
+        let output = DeltaTest::with_args(&[
+            "--density-column",
+            "--density-column-window=4",
+            "--width=12",
+        ])
+        .with_input(DIFF_WITH_CLUSTERED_CHANGES)
+        .output;
+        let hunk_lines = output.lines().skip(8).collect::<Vec<_>>();
+        assert!(hunk_lines.iter().all(|line| line.chars().count() == 12));
+        let shades = hunk_lines
+            .iter()
+            .map(|line| line.chars().last().unwrap())
+            .collect::<String>();
+        assert_eq!(shades, "  ░▒▓█▓▒░ ");
+    }
+
+    #[test]
+    fn test_shell_integration_marks() {
+        let output = DeltaTest::with_args(&["--shell-integration-marks"])
+            .with_input(DIFF_WITH_SCATTERED_EDITS)
+            .raw_output;
+        let output = output
+            .strip_prefix(OSC_133_COMMAND_OUTPUT_START)
+            .unwrap()
+            .strip_suffix(OSC_133_COMMAND_FINISHED)
+            .unwrap();
+        assert!(strip_ansi_codes(output).ends_with("call(x, y, c);\n"));
+        assert!(!output.contains("\x1b]133;"));
+
+        let output = DeltaTest::with_args(&[])
+            .with_input(DIFF_WITH_SCATTERED_EDITS)
+            .raw_output;
+        assert!(!output.contains("\x1b]133;"));
+    }
+
+    #[test]
+    fn test_preserve_missing_final_newline() {
+        let run_delta = |args: &[&str], input: &str| {
+            let config = integration_test_utils::make_config_from_args(args);
+            let mut writer = Vec::new();
+            delta_from_reader(input.as_bytes(), &mut writer, &config).unwrap();
+            strip_ansi_codes(&String::from_utf8(writer).unwrap())
+        };
+        let input = DIFF_WITH_SCATTERED_EDITS.strip_suffix('\n').unwrap();
+        let args = ["--preserve-missing-final-newline"];
+        assert!(run_delta(&args, input).ends_with("call(x, y, c);"));
+        assert!(run_delta(&args, DIFF_WITH_SCATTERED_EDITS).ends_with("call(x, y, c);\n"));
+        assert!(run_delta(&[], input).ends_with("call(x, y, c);\n"));
+        // In particular, --color-only output is byte-faithful to the input without colors.
+        let args = ["--preserve-missing-final-newline", "--color-only"];
+        assert_eq!(run_delta(&args, input), input);
+    }
+
+    #[test]
+    fn test_hunks_are_written_before_reading_further_input() {
+        use std::cell::RefCell;
+        use std::io::{self, BufRead, Read, Write};
+        use std::rc::Rc;
+
+        struct SharedWriter(Rc<RefCell<Vec<u8>>>);
+        impl Write for SharedWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.borrow_mut().write(buf)
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        // Serves one line at a time, recording the output written before serving each line.
+        struct LineByLineReader<'a> {
+            lines: std::str::SplitInclusive<'a, char>,
+            line: &'a [u8],
+            output: Rc<RefCell<Vec<u8>>>,
+            output_before_line: Vec<(&'a str, String)>,
+        }
+        impl Read for LineByLineReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let n = self.fill_buf()?.read(buf)?;
+                self.consume(n);
+                Ok(n)
+            }
+        }
+        impl BufRead for LineByLineReader<'_> {
+            fn fill_buf(&mut self) -> io::Result<&[u8]> {
+                if self.line.is_empty() {
+                    if let Some(line) = self.lines.next() {
+                        let output = String::from_utf8_lossy(&self.output.borrow()).into_owned();
+                        self.output_before_line
+                            .push((line, strip_ansi_codes(&output)));
+                        self.line = line.as_bytes();
+                    }
+                }
+                Ok(self.line)
+            }
+            fn consume(&mut self, n: usize) {
+                self.line = &self.line[n..];
+            }
+        }
+
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let output = Rc::new(RefCell::new(Vec::new()));
+        let mut reader = LineByLineReader {
+            lines: DIFF_WITH_TWO_HUNKS.split_inclusive('\n'),
+            line: &[],
+            output: Rc::clone(&output),
+            output_before_line: Vec::new(),
+        };
+        delta_from_reader(&mut reader, &mut SharedWriter(Rc::clone(&output)), &config).unwrap();
+
+        let output_before_line = |line: &str| {
+            reader
+                .output_before_line
+                .iter()
+                .find(|(l, _)| l.starts_with(line))
+                .map(|(_, output)| output.clone())
+                .unwrap()
+        };
+        let output_before_second_hunk = output_before_line("@@ -10,2 +10,2 @@");
+        assert!(output_before_second_hunk.contains("\nfirst new\n"));
+        assert!(!output_before_second_hunk.contains("second"));
+        assert!(
+            strip_ansi_codes(&String::from_utf8(output.take()).unwrap()).contains("\nsecond new\n")
+        );
+    }
+
+    #[test]
+    fn test_no_newline_at_end_of_file_marker() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--no-newline-marker-style=blue",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_NO_NEWLINE_AT_END_OF_FILE_MARKERS)
+        .expect_contains(
+            "
+(red)let a = (ul normal)1(red);(normal)
+(blue)\\ No newline at end of file(normal)
+(green)let a = (ul normal)2(green);(normal)
+",
+        )
+        .expect_contains(
+            "
+(red)let b = (ul normal)1(red);(normal)
+(blue)\\ No newline at end of file(normal)
+(green)let b = (ul normal)2(green);(normal)
+(blue)\\ No newline at end of file(normal)
+",
+        );
+
+        // A marker follows the lines of its side, whichever side is painted first.
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--no-newline-marker-style=blue",
+            "--plus-lines-first",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_NO_NEWLINE_AT_END_OF_FILE_MARKERS)
+        .expect_contains(
+            "
+(green)let a = (ul normal)2(green);(normal)
+(red)let a = (ul normal)1(red);(normal)
+(blue)\\ No newline at end of file(normal)
+",
+        );
+
+        // With --color-only, the order of the input lines is preserved.
+        let output = DeltaTest::with_args(&["--color-only"])
+            .with_input(DIFF_WITH_NO_NEWLINE_AT_END_OF_FILE_MARKERS)
+            .output;
+        assert_eq!(
+            output.trim(),
+            DIFF_WITH_NO_NEWLINE_AT_END_OF_FILE_MARKERS.trim()
+        );
+    }
+
+    #[test]
+    fn test_file_order_size() {
+        let output = DeltaTest::with_args(&["--file-order=size"])
+            .with_input(DIFF_WITH_FILES_OF_INCREASING_CHANGE_SIZE)
+            .output;
+        let position = |s| output.find(s).unwrap();
+        assert!(position("large.rs") < position("medium.rs"));
+        assert!(position("medium.rs") < position("small.rs"));
+        // The hunks follow their files.
+        assert!(position("large.rs") < position("fn large"));
+        assert!(position("fn large") < position("medium.rs"));
+        assert!(position("small.rs") < position("fn small"));
+
+        let output = DeltaTest::with_args(&[])
+            .with_input(DIFF_WITH_FILES_OF_INCREASING_CHANGE_SIZE)
+            .output;
+        assert!(output.find("small.rs").unwrap() < output.find("large.rs").unwrap());
+    }
+
+    #[test]
+    fn test_line_kind_labels() {
+        DeltaTest::with_args(&["--line-kind-labels"])
+            .with_input(DIFF_WITH_LINES_OF_EACH_KIND)
+            .expect_after_skip(
+                8,
+                r#"
+                [context] fn f() {
+                [changed]     let x = 1;
+                [removed]     gone();
+                [changed]     let x = 2;
+                [context] }
+                [added] added();"#,
+            );
+        DeltaTest::with_args(&[
+            "--line-kind-labels",
+            "--context-line-label=",
+            "--added-line-label=+",
+        ])
+        .with_input(DIFF_WITH_LINES_OF_EACH_KIND)
+        .expect_after_skip(
+            8,
+            r#"
+            fn f() {
+            [changed]     let x = 1;
+            [removed]     gone();
+            [changed]     let x = 2;
+            }
+            + added();"#,
+        );
+    }
+
+    #[test]
+    fn test_emphasis_of_multibyte_characters() {
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_MULTIBYTE_CHARACTER_CHANGES)
+            .expect_contains("\n(red)let s = \"(ul normal)café(red)\";(normal)\n")
+            .expect_contains("\n(red)// (ul normal)🎉(red) done(normal)\n")
+            .expect_contains("\n(green)let s = \"(ul normal)cafè(green)\";(normal)\n")
+            .expect_contains("\n(green)// (ul normal)🎊(green) done(normal)\n");
+        for extra_args in [&["--emphasis-algorithm=lcs"], &["--word-diff-regex=."]] {
+            DeltaTest::with_args(&with_minus_plus_test_styles(extra_args))
+                .explain_ansi()
+                .with_input(DIFF_WITH_MULTIBYTE_CHARACTER_CHANGES)
+                .expect_contains("\n(red)let s = \"caf(ul normal)é(red)\";(normal)\n")
+                .expect_contains("\n(red)// (ul normal)🎉(red) done(normal)\n")
+                .expect_contains("\n(green)let s = \"caf(ul normal)è(green)\";(normal)\n")
+                .expect_contains("\n(green)// (ul normal)🎊(green) done(normal)\n");
+        }
+    }
+
+    #[test]
+    fn test_emphasis_of_changed_words() {
+        // Only the changed words are emphasized, not the span between the common prefix and
+        // suffix of the lines.
+        for args in [&[][..], &["--emphasis-algorithm=lcs"]] {
+            DeltaTest::with_args(&[&MINUS_PLUS_TEST_STYLE_ARGS, args].concat())
+                .explain_ansi()
+                .with_input(DIFF_WITH_CHANGED_WORDS)
+                .expect_contains("\n(red)let (ul normal)x(red) = (ul normal)1(red);(normal)\n")
+                .expect_contains(
+                    "\n(green)let (ul normal)y(green) = (ul normal)2(green);(normal)\n",
+                );
+        }
+    }
+
+    #[test]
+    fn test_grid_width() {
+        for args in [
+            &["--grid-width=40"][..],
+            &["--grid-width=40", "--side-by-side", "--line-numbers"],
+            &["--grid-width=40", "--color-only"],
+        ] {
+            let output = DeltaTest::with_args(args)
+                .with_input(GIT_DIFF_SINGLE_HUNK)
+                .raw_output;
+            assert!(output.ends_with('\n'));
+            for line in output.lines() {
+                assert_eq!(
+                    ansi::measure_text_width(line),
+                    40,
+                    "{:?} is not 40 columns wide with {:?}",
+                    line,
+                    args
+                );
+                assert!(!line.contains('\t'));
+                if line.contains('\x1b') {
+                    assert!(line.trim_end().ends_with(ansi::ANSI_SGR_RESET));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_file_style() {
+        DeltaTest::with_args(&["--empty-file-style=yellow"])
+            .explain_ansi()
+            .with_input(DIFF_WITH_ADDED_AND_REMOVED_EMPTY_FILES)
+            .expect_contains("added: empty(normal)\n(blue)───")
+            .expect_contains("───(normal)\n(yellow)new empty file(normal)\n")
+            .expect_contains("───(normal)\n(yellow)deleted empty file(normal)\n");
+        let output = DeltaTest::with_args(&["--empty-file-style=omit"])
+            .with_input(DIFF_WITH_ADDED_AND_REMOVED_EMPTY_FILES)
+            .output;
+        assert!(!output.contains("empty file"));
+    }
+
+    #[test]
+    fn test_max_common_prefix_width() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--max-common-prefix-width=12",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_LONG_COMMON_PREFIX)
+        .expect_contains("\n(red)     …ction((ul normal)alpha(red));(normal)\n")
+        .expect_contains("\n(green)     …ction((ul normal)beta(green));(normal)\n");
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_LONG_COMMON_PREFIX)
+            .expect_contains(
+                "\n(red)        crate::some::very::long::namespace::function((ul normal)alpha(red));(normal)\n",
+            );
+    }
+
+    #[test]
+    fn test_whitespace_only_changes() {
+        DeltaTest::with_args(&[])
+            .with_input(DIFF_WITH_WHITESPACE_ONLY_CHANGE)
+            .expect_after_skip(
+                8,
+                r#"
+                #indent_mark
+                if (x) { y(); }
+                if (x) {
+                    y();
+                }
+                z();"#,
+            );
+        DeltaTest::with_args(&["--whitespace-only-changes=compact"])
+            .with_input(DIFF_WITH_WHITESPACE_ONLY_CHANGE)
+            .expect_after_skip(
+                8,
+                r#"
+                #indent_mark
+                whitespace-only change (-1/+3)
+                if (x) {
+                    y();
+                }
+                z();"#,
+            );
+        DeltaTest::with_args(&["--whitespace-only-changes=hide"])
+            .with_input(DIFF_WITH_WHITESPACE_ONLY_CHANGE)
+            .expect_after_skip(
+                8,
+                r#"
+                #indent_mark
+                whitespace-only change (-1/+3)
+                z();"#,
+            );
+    }
+
+    #[test]
+    fn test_line_ending_changes() {
+        DeltaTest::with_args(&["--line-ending-changes=compact"])
+            .with_input(DIFF_WITH_CRLF_TO_LF_CHANGE)
+            .expect_after_skip(
+                8,
+                r#"
+                line-ending change (-2/+2)
+                int x;
+                int y;
+                z();"#,
+            );
+        DeltaTest::with_args(&["--line-ending-changes=hide"])
+            .with_input(DIFF_WITH_CRLF_TO_LF_CHANGE)
+            .expect_after_skip(
+                8,
+                r#"
+                line-ending change (-2/+2)
+                z();"#,
+            );
+        // Changes that also alter the content of lines are displayed as usual.
+        DeltaTest::with_args(&["--line-ending-changes=hide"])
+            .with_input(DIFF_WITH_WHITESPACE_ONLY_CHANGE)
+            .expect_after_skip(
+                8,
+                r#"
+                #indent_mark
+                if (x) { y(); }
+                if (x) {
+                    y();
+                }
+                z();"#,
+            );
+        // Lines whose indentation changes from tabs to spaces are not identical, even though they
+        // are once tabs are expanded.
+        DeltaTest::with_args(&["--line-ending-changes=hide", "--tabs=4"])
+            .with_input(DIFF_WITH_TAB_INDENTATION_CHANGE)
+            .expect_after_skip(
+                8,
+                r#"
+                #indent_mark
+                    x;
+                    x;
+                z();"#,
+            );
+    }
+
+    #[test]
+    fn test_indent_normalization_style() {
+        let args = with_minus_plus_test_styles(&["--tabs=4"]);
+        DeltaTest::with_args(&[&args[..], &["--indent-normalization-style=dim"]].concat())
+            .explain_ansi()
+            .with_input(DIFF_WITH_TABS_TO_SPACES_CHANGE)
+            .expect_after_skip(
+                8,
+                "
+(dim normal)    x;(normal)
+(red)  (ul normal)  (red)y;(normal)
+(dim normal)    x;(normal)
+(green)  y;(normal)
+(dim normal)z();(normal)",
+            );
+        DeltaTest::with_args(&args)
+            .explain_ansi()
+            .with_input(DIFF_WITH_TABS_TO_SPACES_CHANGE)
+            .expect_contains("\n(red)    x;(normal)\n")
+            .expect_contains("\n(green)    x;(normal)\n");
+        // The lines differ in their indentation, not just their line endings.
+        DeltaTest::with_args(&[&args[..], &["--line-ending-changes=hide"]].concat())
+            .explain_ansi()
+            .with_input(DIFF_WITH_TABS_TO_SPACES_CHANGE)
+            .expect_contains("\n(red)    x;(normal)\n");
+    }
+
+    #[test]
+    fn test_insertion_point_style() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--insertion-point-style=blue",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_MID_LINE_INSERTION)
+        .expect_after_skip(
+            8,
+            "
+(red)call(a, (blue)‸(red)c);(normal)
+(green)call(a, (ul normal)b, (green)c);(normal)",
+        );
+        DeltaTest::with_args(&MINUS_PLUS_TEST_STYLE_ARGS)
+            .explain_ansi()
+            .with_input(DIFF_WITH_MID_LINE_INSERTION)
+            .expect_contains("\n(red)call(a, c);(normal)\n");
+    }
+
+    #[test]
+    fn test_indent_guide_style() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--indent-guide-style=blue",
+            "--tabs=4",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_DEEPLY_INDENTED_CHANGE)
+        .expect_after_skip(
+            8,
+            "
+(dim normal)if x:(normal)
+(blue)│(red)   (blue)│(red)   (blue)│(red) call((ul normal)a(red))(normal)
+(blue)│(green)   (blue)│(green)   (blue)│(green) call((ul normal)b(green))(normal)
+(dim blue)│(dim normal)   y = 1(normal)",
+        );
+    }
+
+    #[test]
+    fn test_whitespace_only_changes_ignores_other_changes() {
+        DeltaTest::with_args(&["--whitespace-only-changes=hide"])
+            .with_input(DIFF_WITH_TWO_REMOVED_LINES_AND_ONE_ADDED_LINE)
+            .expect_after_skip(
+                8,
+                r#"
+                let x = 1;
+                let z = 1;
+                let y = 1;"#,
+            );
+    }
+
+    #[test]
+    fn test_color() {
+        let output_with_color = |color: &str, stdout_is_term: bool| {
+            let mut opt =
+                integration_test_utils::make_options_from_args(&["--color", color, "--hyperlinks"]);
+            opt.computed.stdout_is_term = stdout_is_term;
+            let config = config::Config::from(opt);
+            integration_test_utils::run_delta(GIT_DIFF_SINGLE_HUNK, &config)
+        };
+        for stdout_is_term in [true, false] {
+            let output = output_with_color("always", stdout_is_term);
+            assert!(output.contains('\x1b'));
+            let output = output_with_color("never", stdout_is_term);
+            assert!(!output.contains('\x1b'));
+            assert_eq!(
+                output,
+                strip_ansi_codes(&output_with_color("always", stdout_is_term))
+            );
+        }
+        assert!(output_with_color("auto", true).contains('\x1b'));
+        assert!(!output_with_color("auto", false).contains('\x1b'));
+    }
+
+    #[test]
+    fn test_color_never_removes_escape_sequences_from_input() {
+        let output = integration_test_utils::run_delta(
+            GIT_DIFF_SINGLE_HUNK_WITH_ANSI_ESCAPE_SEQUENCES,
+            &integration_test_utils::make_config_from_args(&["--color=never"]),
+        );
+        assert!(!output.contains('\x1b'));
+        assert!(output.starts_with("commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e\n"));
+    }
+
+    #[test]
+    fn test_color_never_keeps_decorations() {
+        let output = DeltaTest::with_args(&[
+            "--color=never",
+            "--line-numbers",
+            "--keep-plus-minus-markers",
+            "--file-decoration-style=box",
+        ])
+        .with_input(DIFF_WITH_TWO_HUNKS)
+        .raw_output;
+        assert!(!output.contains('\x1b'));
+        for line in [
+            "\n  1 ⋮    │-first old\n",
+            "\n    ⋮  1 │+first new\n",
+            "\n  2 ⋮  2 │ unchanged\n",
+            "\na.rs │\n─────┘\n",
+        ] {
+            assert!(
+                output.contains(line),
+                "{:?} not found in:\n{}",
+                line,
+                output
+            );
+        }
+    }
+
+    #[test]
+    fn test_trailing_whitespace_error() {
+        DeltaTest::with_args(&with_minus_plus_test_styles(&[
+            "--whitespace-error-style=red reverse",
+        ]))
+        .explain_ansi()
+        .with_input(DIFF_WITH_TRAILING_WHITESPACE_ERRORS)
+        .expect_after_skip(
+            8,
+            "
+(dim normal)x(normal)
+(red)let a = (ul normal)1(red);(normal)
+(green)let a = (ul normal)2(green);(reverse red)  (normal)
+(green)foo(reverse red)  (normal)
+(reverse red)   (normal)",
+        );
+    }
+
+    #[test]
+    fn test_whitespace_error() {
+        let whitespace_error_style = "bold yellow red ul";
+        let config = integration_test_utils::make_config_from_args(&[
+            "--whitespace-error-style",
+            whitespace_error_style,
+        ]);
+        let output = integration_test_utils::run_delta(DIFF_WITH_WHITESPACE_ERROR, &config);
+        ansi_test_utils::assert_line_has_style(&output, 8, " ", whitespace_error_style, &config);
+        let output = integration_test_utils::run_delta(DIFF_WITH_REMOVED_WHITESPACE_ERROR, &config);
+        ansi_test_utils::assert_line_does_not_have_style(
+            &output,
+            8,
+            " ",
+            whitespace_error_style,
+            &config,
+        );
+    }
+
+    #[test]
+    fn test_whitespace_unrelated_edit_text_error() {
+        let whitespace_error_style = "bold yellow red ul";
+        let config = integration_test_utils::make_config_from_args(&[
+            "--whitespace-error-style",
+            whitespace_error_style,
+        ]);
+        let output =
+            integration_test_utils::run_delta(DIFF_WITH_WHITESPACE_UNRELATED_EDIT_ERROR, &config);
+        ansi_test_utils::assert_line_contain_substring_style(
+            &output,
+            9,
+            "some new",
+            "    ",
+            whitespace_error_style,
+            &config,
+        );
+    }
+
+    #[test]
+    fn test_whitespace_edit_text_error() {
+        let whitespace_error_style = "bold yellow red ul";
+        let config = integration_test_utils::make_config_from_args(&[
+            "--whitespace-error-style",
+            whitespace_error_style,
+        ]);
+        let output = integration_test_utils::run_delta(DIFF_WITH_WHITESPACE_EDIT_ERROR, &config);
+        ansi_test_utils::assert_line_contain_substring_style(
+            &output,
+            9,
+            "same ",
+            "    ",
+            whitespace_error_style,
+            &config,
+        );
+    }
+
+    #[test]
+    fn test_whitespace_added_empty_line_error() {
+        let whitespace_error_style = "bold yellow red ul";
+        let config = integration_test_utils::make_config_from_args(&[
+            "--whitespace-error-style",
+            whitespace_error_style,
+        ]);
+        let output =
+            integration_test_utils::run_delta(DIFF_WITH_ADDED_WHITESPACE_EMPTY_LINE_ERROR, &config);
+        // TODO is this the first style ?
+        ansi_test_utils::assert_line_has_style(&output, 9, " ", whitespace_error_style, &config);
+        ansi_test_utils::assert_line_contain_substring_style(
+            &output,
+            9,
+            "",
+            "   ",
+            whitespace_error_style,
+            &config,
+        );
+    }
+
+    #[test]
+    fn test_whitespace_after_text_error() {
+        let whitespace_error_style = "bold yellow red ul";
+        let config = integration_test_utils::make_config_from_args(&[
+            "--whitespace-error-style",
+            whitespace_error_style,
+        ]);
+        let output =
+            integration_test_utils::run_delta(DIFF_WITH_WHITESPACE_AFTER_TEXT_ERROR, &config);
+        ansi_test_utils::assert_line_contain_substring_style(
+            &output,
+            8,
+            "foo bar",
+            "  ",
+            whitespace_error_style,
+            &config,
+        );
+        let output = integration_test_utils::run_delta(
+            DIFF_WITH_REMOVED_WHITESPACE_AFTER_TEXT_ERROR,
+            &config,
+        );
+        ansi_test_utils::assert_line_does_not_contain_substring_style(
+            &output,
+            8,
+            "foo bar",
+            whitespace_error_style,
+            &config,
+        );
+    }
+
+    #[test]
+    fn test_whitespace_complex_error() {
+        let whitespace_error_style = "bold yellow red ul";
+        let config = integration_test_utils::make_config_from_args(&[
+            "--whitespace-error-style",
+            whitespace_error_style,
+        ]);
+        let output = integration_test_utils::run_delta(DIFF_WITH_WHITESPACE_COMPLEX_ERROR, &config);
+        // `minus` line should not display whitespace error
+        ansi_test_utils::assert_line_does_not_have_style(
+            &output,
+            8,
+            "  ",
+            whitespace_error_style,
+            &config,
+        );
+        ansi_test_utils::assert_line_does_not_have_style(
+            &output,
+            9,
+            "  ",
+            whitespace_error_style,
+            &config,
         );
         ansi_test_utils::assert_line_does_not_contain_substring_style(
             &output,
@@ -1900,6 +3266,47 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
         ));
     }
 
+    #[test]
+    fn test_lines_without_markers_are_source_lines() {
+        let output = DeltaTest::with_args(&[])
+            .with_input(DIFF_WITH_MOVED_BLOCK)
+            .output;
+        let source_lines: Vec<&str> = DIFF_WITH_MOVED_BLOCK
+            .lines()
+            .skip(6)
+            .map(|line| &line[1..])
+            .collect();
+        let painted_lines: Vec<&str> = output.lines().skip(8).collect();
+        assert_eq!(painted_lines, source_lines);
+    }
+
+    #[test]
+    fn test_custom_plus_minus_markers() {
+        DeltaTest::with_args(&[
+            "--keep-plus-minus-markers",
+            "--minus-marker=<",
+            "--plus-marker=>",
+            "--zero-marker=|",
+        ])
+        .with_input(DIFF_WITH_MOVED_BLOCK)
+        .expect_after_skip(
+            8,
+            "
+<fn a() {
+<    1
+<}
+|fn b() {
+<    2
+>    3
+|}
+>fn a() {
+>    1
+>}
+|
+>fn c() {}",
+        );
+    }
+
     #[test]
     fn test_file_mode_change_with_rename() {
         let config = integration_test_utils::make_config_from_args(&["--right-arrow=->"]);
@@ -2017,8 +3424,8 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
         (blue)71(normal):(231) (81)impl(231)<(203)'a(231)> (149)Alignmen(normal)t<'a> { (blue)│(normal)
         (blue)─────────────────────────────(blue)┘(normal)
 
-        (231)        (203)for(231) (i, x_(normal)i) in self.x.iter().en→
-        (231)            (203)for(231) (j(normal), y_j) in self.y.iter(→
+        (dim 231)        (203)for(231) (i, x_(dim normal)i) in self.x.iter().en→(normal)
+        (dim 231)            (203)for(231) (j(dim normal), y_j) in self.y.iter(→(normal)
         (normal 52)                let (left, diag, up) =(normal 124) ((normal)
         (normal 52)                    self.index(i, j + 1(normal 124)),(normal)
         (normal 52)                    self.index(i, j),(normal)
@@ -2026,9 +3433,9 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
         (normal 52)                );(normal)
         (231 22)                le(normal 22)t (left, diag, up) =(normal)
         (231 22)                  (normal 22)  (normal 28)((normal 22)self.index(i, j + 1(normal 28)→(normal)
-        (231)                le(normal)t candidates = [
-        (231)                  (normal)  Cell {
-        (231)                  (normal)      parent: left,
+        (dim 231)                le(dim normal)t candidates = [(normal)
+        (dim 231)                  (dim normal)  Cell {(normal)
+        (dim 231)                  (dim normal)      parent: left,(normal)
         "###);
     }
 
@@ -2060,10 +3467,10 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
         (blue)71(normal):(231) (81)impl(231)<(203)'a(231)> (149)Alignment(231)<(203)'a(normal)> { (blue)│(normal)
         (blue)─────────────────────────────(blue)┘(normal)
 
-        (blue)│(238) 71 (blue)│(231)        (203)for(231) (i, x_i)(blue)↵(blue) │(238) 71 (blue)│(231)        (203)for(231) (i, x_i)(blue)↵(normal)
-        (blue)│(238)    (blue)│(231) i(normal)n self.x.iter().en(reverse normal)→(blue) │(238)    (blue)│(231) i(normal)n self.x.iter().en(reverse normal)→(normal)
-        (blue)│(238) 72 (blue)│(231)            (203)for(231) (j, (blue)↵(blue) │(238) 72 (blue)│(231)            (203)for(231) (j, (blue)↵(normal)
-        (blue)│(238)    (blue)│(231)y_(normal)j) in self.y.iter((reverse normal)→(blue) │(238)    (blue)│(231)y_(normal)j) in self.y.iter((reverse normal)→(normal)
+        (blue)│(238) 71 (blue)│(dim 231)        (203)for(231) (i, x_i)(blue)↵(blue) │(238) 71 (blue)│(dim 231)        (203)for(231) (i, x_i)(blue)↵(normal)
+        (blue)│(238)    (blue)│(dim 231) i(dim normal)n self.x.iter().en(reverse normal)→(blue) │(238)    (blue)│(dim 231) i(dim normal)n self.x.iter().en(reverse normal)→(normal)
+        (blue)│(238) 72 (blue)│(dim 231)            (203)for(231) (j, (blue)↵(blue) │(238) 72 (blue)│(dim 231)            (203)for(231) (j, (blue)↵(normal)
+        (blue)│(238)    (blue)│(dim 231)y_(dim normal)j) in self.y.iter((reverse normal)→(blue) │(238)    (blue)│(dim 231)y_(dim normal)j) in self.y.iter((reverse normal)→(normal)
         (blue)│(88) 73 (blue)│(231 52)                (81)let(231) (blue)↵(blue) │(28) 73 (blue)│(231 22)                (81)let(231) (blue)↵(normal)
         (blue)│(88)    (blue)│(231 52)(l(normal 52)eft, diag, up) =(normal 124) ((normal 52) (blue) │(28)    (blue)│(231 22)(l(normal 22)eft, diag, up) =(normal)
         (blue)│(88) 74 (blue)│(231 52)                    (blue)↵(blue) │(28) 74 (blue)│(231 22)                    (blue)↵(normal)
@@ -2073,12 +3480,12 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
         (blue)│(88) 76 (blue)│(231 52)                    (blue)↵(blue) │(28)    (blue)│(normal)
         (blue)│(88)    (blue)│(231 52)se(normal 52)lf.index(i + 1, j),(blue) │(28)    (blue)│(normal)
         (blue)│(88) 77 (blue)│(231 52)                );(normal 52)   (blue) │(28)    (blue)│(normal)
-        (blue)│(238) 78 (blue)│(231)                (81)let(231) (blue)↵(blue) │(238) 75 (blue)│(231)                (81)let(231) (blue)↵(normal)
-        (blue)│(238)    (blue)│(231)ca(normal)ndidates = [       (blue) │(238)    (blue)│(231)ca(normal)ndidates = [
-        (blue)│(238) 79 (blue)│(231)                    (blue)↵(blue) │(238) 76 (blue)│(231)                    (blue)↵(normal)
-        (blue)│(238)    (blue)│(231)Ce(normal)ll {               (blue) │(238)    (blue)│(231)Ce(normal)ll {
-        (blue)│(238) 80 (blue)│(231)                    (blue)↵(blue) │(238) 77 (blue)│(231)                    (blue)↵(normal)
-        (blue)│(238)    (blue)│(231)  (normal)  parent: left,    (blue) │(238)    (blue)│(231)  (normal)  parent: left,
+        (blue)│(238) 78 (blue)│(dim 231)                (81)let(231) (blue)↵(blue) │(238) 75 (blue)│(dim 231)                (81)let(231) (blue)↵(normal)
+        (blue)│(238)    (blue)│(dim 231)ca(dim normal)ndidates = [(normal)       (blue) │(238)    (blue)│(dim 231)ca(dim normal)ndidates = [(normal)
+        (blue)│(238) 79 (blue)│(dim 231)                    (blue)↵(blue) │(238) 76 (blue)│(dim 231)                    (blue)↵(normal)
+        (blue)│(238)    (blue)│(dim 231)Ce(dim normal)ll {(normal)               (blue) │(238)    (blue)│(dim 231)Ce(dim normal)ll {(normal)
+        (blue)│(238) 80 (blue)│(dim 231)                    (blue)↵(blue) │(238) 77 (blue)│(dim 231)                    (blue)↵(normal)
+        (blue)│(238)    (blue)│(dim 231)  (dim normal)  parent: left,(normal)    (blue) │(238)    (blue)│(dim 231)  (dim normal)  parent: left,(normal)
         "###);
     }
 
@@ -2097,15 +3504,15 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
         (blue)───(blue)┐(normal)
         (blue)1(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (231)一æäöø€ÆÄÖ(normal)〇Øß一
-        (231)一æäöø€ÆÄÖ(normal)〇Øß一
+        (dim 231)一æäöø€ÆÄÖ(dim normal)〇Øß一(normal)
+        (dim 231)一æäöø€ÆÄÖ(dim normal)〇Øß一(normal)
         (normal 52)二æäöø(normal 124)¢(normal 52)ÆÄÖ〇Øß二(normal)
         (normal 52)二æäöø(normal 124)¢(normal 52)ÆÄÖ〇Øß二(normal)
         (231 22)二æäöø(normal 28)€(normal 22)ÆÄÖ(normal 22)〇Øß二(normal)
         (231 22)二æäöø(normal 28)€(normal 22)ÆÄÖ(normal 22)〇Øß二(normal)
-        (231)三æäöø€ÆÄÖ(normal)〇Øß三
-        (231)三æäöø€ÆÄÖ(normal)〇Øß三
-        (231)¶(normal)
+        (dim 231)三æäöø€ÆÄÖ(dim normal)〇Øß三(normal)
+        (dim 231)三æäöø€ÆÄÖ(dim normal)〇Øß三(normal)
+        (dim 231)¶(normal)
         "###);
 
         let result = DeltaTest::with_args(&[
@@ -2125,15 +3532,15 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
         (blue)───(blue)┐(normal)
         (blue)1(normal): (blue)│(normal)
         (blue)───(blue)┘(normal)
-        (231)一æäöø€ÆÄÖ(normal)〇Øß→
-        (231)一æäöø€ÆÄÖ(normal)〇Øß→
+        (dim 231)一æäöø€ÆÄÖ(dim normal)〇Øß→(normal)
+        (dim 231)一æäöø€ÆÄÖ(dim normal)〇Øß→(normal)
         (normal 52)二æäöø(normal 124)¢(normal 52)ÆÄÖ〇Øß→(normal)
         (normal 52)二æäöø(normal 124)¢(normal 52)ÆÄÖ〇Øß→(normal)
         (231 22)二æäöø(normal 28)€(normal 22)ÆÄÖ(normal 22)〇Øß→(normal)
         (231 22)二æäöø(normal 28)€(normal 22)ÆÄÖ(normal 22)〇Øß→(normal)
-        (231)三æäöø€ÆÄÖ(normal)〇Øß→
-        (231)三æäöø€ÆÄÖ(normal)〇Øß→
-        (231)¶(normal)
+        (dim 231)三æäöø€ÆÄÖ(dim normal)〇Øß→(normal)
+        (dim 231)三æäöø€ÆÄÖ(dim normal)〇Øß→(normal)
+        (dim 231)¶(normal)
         "###);
     }
 
@@ -2477,6 +3884,57 @@ Only in b/: just_b
  test.
 ";
 
+    const DIFF_WITH_TWO_HUNKS_WITH_CONTEXT: &str = "\
+diff --git a/a.txt b/a.txt
+index 1..2 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,5 +1,5 @@
+ a
+ b
+ c
+-x
++y
+ d
+@@ -10,3 +10,3 @@
+ e
+-x
++y
+ f
+ g
+";
+
+    const DIFF_WITH_CONTEXT_LINE_OF_RUST: &str = "\
+diff --git a/a.rs b/a.rs
+index 1..2 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+ fn f() {}
+-let x = 1;
++let x = 2;
+";
+
+    const DIFF_UNIFIED_OF_BACKUP_FILE: &str = "\
+--- lib.rs.orig	2024-01-02 10:00:00.000000000 +0100
++++ lib.rs	2024-01-02 10:05:00.000000000 +0100
+@@ -1,2 +1,2 @@
+ fn f() {}
+-let x = 1;
++let x = 2;
+";
+
+    const QUILT_PATCH: &str = "\
+Index: foo/src/lib.rs
+===================================================================
+--- foo.orig/src/lib.rs
++++ foo/src/lib.rs
+@@ -1,2 +1,2 @@
+ fn f() {}
+-let x = 1;
++let x = 2;
+";
+
     const NOT_A_DIFF_OUTPUT: &str = "\
 Hello world
 This is a regular file that contains:
@@ -2647,6 +4105,26 @@ rename to bar
 diff --git a/qux b/qux
 index 00de669..d47cd84 100644
 Binary files a/qux and b/qux differ
+";
+
+    const TWO_FILES_THE_FIRST_WITH_TWO_HUNKS: &str = "\
+diff --git a/foo b/foo
+index 7b57bd29ea8a..4d3b8c11a4a2 100644
+--- a/foo
++++ b/foo
+@@ -1 +1 @@
+-abc
++def
+@@ -10 +10 @@
+-ghi
++jkl
+diff --git a/bar b/bar
+index 7b57bd29ea8a..4d3b8c11a4a2 100644
+--- a/bar
++++ b/bar
+@@ -1 +1 @@
+-123
++456
 ";
 
     const BINARY_FILES_DIFFER_BETWEEN_OTHER: &str = "\
@@ -2872,6 +4350,479 @@ index 0000000..c6c830c
 +}
 ";
 
+    const DIFF_WITH_CODE_AND_COMMENT_CHANGE: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1 +1 @@
+-let x = 1; // old comment
++let y = 1; // new comment
+";
+
+    const DIFF_WITH_KEYWORD_REPLACED_BY_IDENTIFIER: &str = r"
+diff --git a/a.py b/a.py
+index 1111111..2222222 100644
+--- a/a.py
++++ b/a.py
+@@ -1,2 +1,2 @@
+-    return value
+-    total = count
++    result value
++    total = amount
+";
+
+    const DIFF_WITH_INTERLEAVED_EDITS: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1 +1 @@
+-let total = price * qty;
++let subtotal = price * quantity;
+";
+
+    const DIFF_WITH_TWO_REMOVED_LINES_AND_ONE_ADDED_LINE: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1 @@
+-let x = 1;
+-let z = 1;
++let y = 1;
+";
+
+    const DIFF_WITH_REMOVED_ADDED_AND_CHANGED_LINES: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,6 +1,6 @@
+ fn main() {
+-    removed();
+     context();
+-    let x = 1;
++    let y = 1;
+     context();
++    added();
+ }
+";
+
+    const DIFF_WITH_ADDED_TRAILING_COMMA: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,3 +1,4 @@
+ f(
+-    a
++    a,
++    b
+ );
+";
+
+    const DIFF_WITH_CLUSTERED_CHANGES: &str = r"
+diff --git a/a b/a
+index 1111111..2222222 100644
+--- a/a
++++ b/a
+@@ -1,8 +1,8 @@
+ 1
+ 2
+-3
+-4
++3x
++4x
+ 5
+ 6
+ 7
+ 8
+";
+
+    const DIFF_WITH_CHANGE_ACROSS_TOKEN_BOUNDARY: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+-say(hello world);
++say(helxy zzrld);
+ zero
+";
+
+    const DIFF_WITH_RENAME_AND_OTHER_CHANGE: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,3 +1,3 @@
+ fn f() {
+-let x = old_name(a);
++let x = new_name(b);
+ }
+";
+
+    const DIFF_WITH_FILES_OF_INCREASING_CHANGE_SIZE: &str = r"
+diff --git a/small.rs b/small.rs
+index 1111111..2222222 100644
+--- a/small.rs
++++ b/small.rs
+@@ -1,2 +1,2 @@ fn small() {
+ fn small() {
+-    a();
++    b();
+diff --git a/medium.rs b/medium.rs
+index 3333333..4444444 100644
+--- a/medium.rs
++++ b/medium.rs
+@@ -1,3 +1,3 @@ fn medium() {
+ fn medium() {
+-    a();
+-    b();
++    c();
++    d();
+diff --git a/large.rs b/large.rs
+index 5555555..6666666 100644
+--- a/large.rs
++++ b/large.rs
+@@ -1,4 +1,4 @@ fn large() {
+ fn large() {
+-    a();
+-    b();
+-    c();
++    d();
++    e();
++    f();
+";
+
+    const DIFF_WITH_MULTIBYTE_CHARACTER_CHANGES: &str = r#"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,3 +1,3 @@
+-let s = "café";
+-// 🎉 done
++let s = "cafè";
++// 🎊 done
+ });
+"#;
+
+    const DIFF_WITH_CHANGED_WORDS: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1 +1 @@
+-let x = 1;
++let y = 2;
+";
+
+    const DIFF_WITH_TWO_HUNKS: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+-first old
++first new
+ unchanged
+@@ -10,2 +10,2 @@
+-second old
++second new
+ unchanged
+";
+
+    const DIFF_WITH_NO_NEWLINE_AT_END_OF_FILE_MARKERS: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+ fn f() {}
+-let a = 1;
+\ No newline at end of file
++let a = 2;
+diff --git a/b.rs b/b.rs
+index 1111111..2222222 100644
+--- a/b.rs
++++ b/b.rs
+@@ -1 +1 @@
+-let b = 1;
+\ No newline at end of file
++let b = 2;
+\ No newline at end of file
+";
+
+    const DIFF_WITH_LINES_OF_EACH_KIND: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,4 +1,4 @@
+ fn f() {
+-    let x = 1;
+-    gone();
++    let x = 2;
+ }
++added();
+";
+
+    const DIFF_WITH_PAIR_SWAP_AND_OTHER_CHANGE: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,3 +1,3 @@
+ fn f() {
+-let x = f(true, a);
++let x = f(false, b);
+ }
+";
+
+    const DIFF_WITH_TRAILING_COMMENT_CHANGES: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,3 +1,3 @@
+-let x = 1;
++let x = 1;  // fixed
+-let y = 2; // old
++let y = 2; // new
+-let z = 3; // z
++let z = 4; // z
+";
+
+    const DIFF_WITH_FORMATTING_AND_MEANINGFUL_CHANGES: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+-let Total = compute(a,  b);
++let total = compute(a, b);
+-let Count = 1;
++let count = 2;
+";
+
+    const DIFF_WITH_OPERATOR_AND_IDENTIFIER_CHANGES: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,3 +1,3 @@
+-if a == b {
++if a != b {
+     x();
+-let count = 1;
++let total = 1;
+";
+
+    const DIFF_WITH_TABS: &str = "
+diff --git a/a.c b/a.c
+index 1111111..2222222 100644
+--- a/a.c
++++ b/a.c
+@@ -1,2 +1,2 @@
+-\tfoo(a);\t// x
++\tfoo(bc);\t// x
+ \tx\ty
+";
+
+    const DIFF_WITH_TABS_AND_ARROWS: &str = "
+diff --git a/a.c b/a.c
+index 1111111..2222222 100644
+--- a/a.c
++++ b/a.c
+@@ -1,2 +1,2 @@
+-\ta → b
++\ta → c
+ x\t// →
+";
+
+    const DIFF_WITH_LEADING_TABS_AND_SPACES: &str = "
+diff --git a/a.c b/a.c
+index 1111111..2222222 100644
+--- a/a.c
++++ b/a.c
+@@ -1,2 +1,2 @@
+-\tfoo(a);
++\tfoo(a); // x
+   \tx \ty
+";
+
+    const DIFF_WITH_MOVED_BLOCK: &str = "
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,7 +1,8 @@
+-fn a() {
+-    1
+-}
+ fn b() {
+-    2
++    3
+ }
++fn a() {
++    1
++}
+ 
++fn c() {}
+";
+
+    const DIFF_WITH_LONG_LINES: &str = "
+diff --git a/a.txt b/a.txt
+index 1111111..2222222 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-exactly twenty cols
++this line is much too long for a narrow terminal
+";
+
+    const DIFF_WITH_LONG_LINE_OF_DOUBLE_WIDTH_CHARACTERS: &str = "
+diff --git a/a.txt b/a.txt
+index 1111111..2222222 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-ａｂｃｄｅｆｇ
++ａｂｃｄｅｆｇｈｉｊｋｌ
+";
+
+    const DIFF_WITH_LONG_LEADING_CONTEXT: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,8 +1,8 @@
+ one
+ two
+ three
+ four
+ five
+ six
+-let x = 1;
++let x = 2;
+ seven
+";
+
+    const DIFF_WITH_SCATTERED_EDITS: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1 +1 @@
+-call(a, b, c);
++call(x, y, c);
+";
+
+    const DIFF_WITH_REORDERED_LINES: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+-use b;
+-use a;
++use a;
++use b;
+";
+
+    const DIFF_WITH_ADDED_AND_REMOVED_EMPTY_FILES: &str = r"
+diff --git a/empty b/empty
+new file mode 100644
+index 0000000..e69de29
+diff --git a/gone b/gone
+deleted file mode 100644
+index e69de29..0000000
+";
+
+    const DIFF_WITH_LONG_COMMON_PREFIX: &str = r"
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1 +1 @@
+-        crate::some::very::long::namespace::function(alpha);
++        crate::some::very::long::namespace::function(beta);
+";
+
+    const DIFF_WITH_WHITESPACE_ONLY_CHANGE: &str = r"
+diff --git a/a.c b/a.c
+index 1111111..2222222 100644
+--- a/a.c
++++ b/a.c
+@@ -1,2 +1,4 @@
+-if (x) { y(); }
++if (x) {
++    y();
++}
+ z();
+";
+
+    const DIFF_WITH_TAB_INDENTATION_CHANGE: &str = "
+diff --git a/a.c b/a.c
+index 1111111..2222222 100644
+--- a/a.c
++++ b/a.c
+@@ -1,2 +1,2 @@
+-\tx;
++    x;
+ z();
+";
+
+    const DIFF_WITH_CRLF_TO_LF_CHANGE: &str = "
+diff --git a/a.c b/a.c
+index 1111111..2222222 100644
+--- a/a.c
++++ b/a.c
+@@ -1,3 +1,3 @@
+-int x;\r
+-int y;\r
++int x;
++int y;
+ z();
+";
+
+    const DIFF_WITH_MID_LINE_INSERTION: &str = "
+diff --git a/a.py b/a.py
+index 1111111..2222222 100644
+--- a/a.py
++++ b/a.py
+@@ -1 +1 @@
+-call(a, c);
++call(a, b, c);
+";
+
+    const DIFF_WITH_DEEPLY_INDENTED_CHANGE: &str = "
+diff --git a/a.py b/a.py
+index 1111111..2222222 100644
+--- a/a.py
++++ b/a.py
+@@ -1,3 +1,3 @@
+ if x:
+-          call(a)
++\t\t  call(b)
+     y = 1
+";
+
+    const DIFF_WITH_TABS_TO_SPACES_CHANGE: &str = "
+diff --git a/a.c b/a.c
+index 1111111..2222222 100644
+--- a/a.c
++++ b/a.c
+@@ -1,3 +1,3 @@
+-\tx;
+-\ty;
++    x;
++  y;
+ z();
+";
+
     const DIFF_WITH_WHITESPACE_ERROR: &str = r"
 diff --git c/a i/a
 new file mode 100644
@@ -2880,6 +4831,19 @@ index 0000000..8d1c8b6
 +++ i/a
 @@ -0,0 +1 @@
 + 
+";
+
+    const DIFF_WITH_TRAILING_WHITESPACE_ERRORS: &str = "
+diff --git a/a b/a
+index 1111111..2222222 100644
+--- a/a
++++ b/a
+@@ -1,2 +1,4 @@
+ x
+-let a = 1;
++let a = 2;  
++foo  
++   
 ";
 
     const DIFF_WITH_REMOVED_WHITESPACE_ERROR: &str = r"
@@ -81,3 +81,53 @@ pub fn as_terminal_escaped(
     style.background = background_color.and_then(|c| to_ansi_color(c, true_color));
     style.paint(text).to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_ansi_color_256_keeps_grays_gray() {
+        // Near-gray colors, as found in themes, must map to palette colors that are gray too
+        // (those of the grayscale ramp 232-255, or the grays of the color cube), not to the
+        // nearest saturated entries of the color cube.
+        for (r, g, b) in [
+            (0x08, 0x08, 0x08),
+            (0x1c, 0x1d, 0x1f),
+            (0x2b, 0x30, 0x3b),
+            (0x5c, 0x63, 0x70),
+            (0x7f, 0x80, 0x80),
+            (0xa0, 0xa1, 0xa7),
+            (0xd0, 0xd0, 0xcc),
+            (0xf5, 0xf5, 0xf5),
+        ] {
+            let color = highlighting::Color { r, g, b, a: 0xff };
+            let Some(Fixed(n)) = to_ansi_color(color, false) else {
+                panic!("Expected a 256-color palette color for {:?}", color);
+            };
+            let (pr, pg, pb) = ansi_colours::rgb_from_ansi256(n);
+            let spread = pr.max(pg).max(pb) - pr.min(pg).min(pb);
+            assert!(
+                spread <= 0x10,
+                "{:?} mapped to non-gray palette color {}: {:?}",
+                color,
+                n,
+                (pr, pg, pb)
+            );
+            let brightness = |x: u8, y: u8, z: u8| (x as i32 + y as i32 + z as i32) / 3;
+            assert!((brightness(r, g, b) - brightness(pr, pg, pb)).abs() <= 0x10);
+        }
+        assert_eq!(
+            to_ansi_color(
+                highlighting::Color {
+                    r: 0x80,
+                    g: 0x80,
+                    b: 0x80,
+                    a: 0xff
+                },
+                false
+            ),
+            Some(Fixed(244))
+        );
+    }
+}
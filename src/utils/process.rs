@@ -517,7 +517,7 @@ where
             // The full scan is expensive on Linux and rarely successful, so disable it by default.
             #[cfg(target_os = "linux")]
             let full_scan = std::env::var("DELTA_CALLING_PROCESS_QUERY_ALL")
-                .map_or(false, |v| !["0", "false", "no"].iter().any(|&n| n == v));
+                .is_ok_and(|v| !["0", "false", "no"].iter().any(|&n| n == v));
 
             if full_scan {
                 info.refresh_processes();
@@ -820,15 +820,15 @@ pub mod tests {
     #[test]
     #[should_panic(expected = "test logic error (in once): wrong FakeParentArgs scope?")]
     fn test_process_testing_assert_for_scope_never_used() {
-        let _args = FakeParentArgs::for_scope(&"never used");
-        let _args = FakeParentArgs::once(&"never used");
+        let _args = FakeParentArgs::for_scope("never used");
+        let _args = FakeParentArgs::once("never used");
     }
 
     #[test]
     #[should_panic(expected = "test logic error (in for_scope): wrong FakeParentArgs scope?")]
     fn test_process_testing_assert_once_never_used2() {
-        let _args = FakeParentArgs::once(&"never used");
-        let _args = FakeParentArgs::for_scope(&"never used");
+        let _args = FakeParentArgs::once("never used");
+        let _args = FakeParentArgs::for_scope("never used");
     }
 
     #[test]
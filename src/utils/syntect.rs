@@ -1,6 +1,9 @@
 use std::str::FromStr;
 
-use syntect::highlighting::{Color, FontStyle, Style};
+use syntect::highlighting::{
+    Color, FontStyle, HighlightIterator, HighlightState, Highlighter, Style, Theme,
+};
+use syntect::parsing::{ParseState, ParsingError, ScopeStack, SyntaxReference, SyntaxSet};
 
 use crate::color;
 use crate::style as delta_style;
@@ -93,3 +96,47 @@ impl FromDeltaStyle for Style {
         Self::from_ansi_term_style(delta_style.ansi_term_style)
     }
 }
+
+/// Highlighter of consecutive lines of a file, like syntect's `HighlightLines`, which can also leave
+/// unhighlighted the lines on which the scope stack grows too deep.
+pub struct HighlightLines<'a> {
+    highlighter: Highlighter<'a>,
+    parse_state: ParseState,
+    highlight_state: HighlightState,
+}
+
+impl<'a> HighlightLines<'a> {
+    pub fn new(syntax: &SyntaxReference, theme: &'a Theme) -> Self {
+        let highlighter = Highlighter::new(theme);
+        let highlight_state = HighlightState::new(&highlighter, ScopeStack::new());
+        Self {
+            highlighter,
+            parse_state: ParseState::new(syntax),
+            highlight_state,
+        }
+    }
+
+    /// Highlight `line`, unless the scope stack grows deeper than `max_depth` on it, in which case
+    /// None is returned. Either way, the line is parsed once, and the state of the highlighter
+    /// advances past it.
+    pub fn highlight_line<'b>(
+        &mut self,
+        line: &'b str,
+        syntax_set: &SyntaxSet,
+        max_depth: usize,
+    ) -> Result<Option<Vec<(Style, &'b str)>>, ParsingError> {
+        let ops = self.parse_state.parse_line(line, syntax_set)?;
+        let mut scope_stack = self.highlight_state.path.clone();
+        let within_depth = ops
+            .iter()
+            .all(|(_, op)| scope_stack.apply(op).is_ok() && scope_stack.len() <= max_depth);
+        let highlighted =
+            HighlightIterator::new(&mut self.highlight_state, &ops[..], line, &self.highlighter);
+        if within_depth {
+            Ok(Some(highlighted.collect()))
+        } else {
+            highlighted.for_each(drop);
+            Ok(None)
+        }
+    }
+}
@@ -1,47 +1,112 @@
+use std::ops::Range;
+
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Clone)]
 pub struct TabCfg {
-    replacement: String,
+    width: usize,
+    symbol: Option<String>,
 }
 
 impl TabCfg {
     pub fn new(width: usize) -> Self {
         TabCfg {
-            replacement: " ".repeat(width),
+            width,
+            symbol: None,
+        }
+    }
+    /// Display each tab as `symbol` followed by spaces up to the next tab stop.
+    pub fn with_symbol(mut self, symbol: &str) -> Self {
+        if !symbol.is_empty() {
+            self.symbol = Some(symbol.to_string());
         }
+        self
+    }
+    pub fn symbol(&self) -> Option<&str> {
+        self.symbol.as_deref()
     }
     pub fn width(&self) -> usize {
-        self.replacement.len()
+        self.width
     }
     pub fn replace(&self) -> bool {
-        !self.replacement.is_empty()
+        self.width > 0
     }
 }
 
-/// Expand tabs as spaces.
+/// Expand tabs as spaces up to the next tab stop, preceded by the tab symbol if there is one.
 pub fn expand(line: &str, tab_cfg: &TabCfg) -> String {
-    if tab_cfg.replace() && line.as_bytes().iter().any(|c| *c == b'\t') {
-        itertools::join(line.split('\t'), &tab_cfg.replacement)
+    expand_marking_symbols(line, tab_cfg).0
+}
+
+/// Expand tabs as `expand()` does, also returning the byte ranges of the tab symbols in the
+/// expanded line, so that they can be told apart from the same text occurring in the line itself.
+pub fn expand_marking_symbols(line: &str, tab_cfg: &TabCfg) -> (String, Vec<Range<usize>>) {
+    let mut symbol_ranges = Vec::new();
+    if !tab_cfg.replace() || !line.as_bytes().contains(&b'\t') {
+        (line.to_string(), symbol_ranges)
     } else {
-        line.to_string()
+        let expanded = expand_to_tab_stops(
+            line,
+            tab_cfg.symbol().unwrap_or(""),
+            tab_cfg.width(),
+            &mut symbol_ranges,
+        );
+        (expanded, symbol_ranges)
     }
 }
 
-/// Remove `prefix` chars from `line`, then call `tabs::expand()`.
-pub fn remove_prefix_and_expand(prefix: usize, line: &str, tab_cfg: &TabCfg) -> String {
+fn expand_to_tab_stops(
+    line: &str,
+    symbol: &str,
+    width: usize,
+    symbol_ranges: &mut Vec<Range<usize>>,
+) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for (i, text) in line.split('\t').enumerate() {
+        if i > 0 {
+            let padding = width - column % width;
+            if !symbol.is_empty() {
+                symbol_ranges.push(expanded.len()..expanded.len() + symbol.len());
+            }
+            expanded.push_str(symbol);
+            expanded.push_str(&" ".repeat(padding.saturating_sub(symbol.width())));
+            column += padding;
+        }
+        expanded.push_str(text);
+        column += text.width();
+    }
+    expanded
+}
+
+/// Remove `prefix` chars from `line`, then call `tabs::expand_marking_symbols()`.
+pub fn remove_prefix_and_expand(
+    prefix: usize,
+    line: &str,
+    tab_cfg: &TabCfg,
+) -> (String, Vec<Range<usize>>) {
     let line_bytes = line.as_bytes();
     // The to-be-removed prefixes are almost always ascii +/- (or ++/ +/.. for merges) for
     // which grapheme clusters are not required.
     if line_bytes.len() >= prefix && line_bytes[..prefix].is_ascii() {
         // Safety: slicing into the utf-8 line-str is ok, upto `prefix` only ascii was present.
-        expand(&line[prefix..], tab_cfg)
+        expand_marking_symbols(&line[prefix..], tab_cfg)
     } else {
         let cut_line = line.graphemes(true).skip(prefix).collect::<String>();
-        expand(&cut_line, tab_cfg)
+        expand_marking_symbols(&cut_line, tab_cfg)
     }
 }
 
+/// Return the width in columns of the `indentation` (leading whitespace) of a line, tabs advancing
+/// to the next multiple of `tab_width`.
+pub fn indentation_width(indentation: &str, tab_width: usize) -> usize {
+    indentation.chars().fold(0, |column, c| match c {
+        '\t' => (column / tab_width + 1) * tab_width,
+        _ => column + 1,
+    })
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -49,16 +114,67 @@ pub mod tests {
     #[test]
     fn test_remove_prefix_and_expand() {
         let line = "+-foo\tbar";
-        let result = remove_prefix_and_expand(2, line, &TabCfg::new(3));
+        let (result, _) = remove_prefix_and_expand(2, line, &TabCfg::new(3));
         assert_eq!(result, "foo   bar");
-        let result = remove_prefix_and_expand(2, line, &TabCfg::new(0));
+        let (result, _) = remove_prefix_and_expand(2, line, &TabCfg::new(0));
         assert_eq!(result, "foo\tbar");
 
         let utf8_prefix = "-│-foo\tbar";
         let n = 3;
-        let result = remove_prefix_and_expand(n, utf8_prefix, &TabCfg::new(1));
+        let (result, _) = remove_prefix_and_expand(n, utf8_prefix, &TabCfg::new(1));
         assert_eq!(result, "foo bar");
         // ensure non-ascii chars were removed:
         assert!(utf8_prefix.len() - result.len() > n);
     }
+
+    #[test]
+    fn test_expand_to_tab_stops() {
+        let tab_cfg = TabCfg::new(4);
+        assert_eq!(expand("\tfoo", &tab_cfg), "    foo");
+        assert_eq!(expand("a\tb", &tab_cfg), "a   b");
+        assert_eq!(expand("abc\tb", &tab_cfg), "abc b");
+        assert_eq!(expand("abcd\tb", &tab_cfg), "abcd    b");
+        assert_eq!(expand("a\t\tb", &tab_cfg), "a       b");
+        assert_eq!(expand("日本\tb", &tab_cfg), "日本    b");
+        assert_eq!(expand("a\tb", &TabCfg::new(0)), "a\tb");
+    }
+
+    #[test]
+    fn test_expand_with_symbol() {
+        let tab_cfg = TabCfg::new(4).with_symbol("→");
+        assert_eq!(expand("\tfoo", &tab_cfg), "→   foo");
+        assert_eq!(expand("\t\tfoo", &tab_cfg), "→   →   foo");
+        assert_eq!(expand("a\tb", &tab_cfg), "a→  b");
+        assert_eq!(expand("abc\tb", &tab_cfg), "abc→b");
+        assert_eq!(expand("abcd\tb", &tab_cfg), "abcd→   b");
+        assert_eq!(expand("é\tb", &tab_cfg), "é→  b");
+        assert_eq!(expand("\tfoo", &TabCfg::new(0).with_symbol("→")), "\tfoo");
+        assert_eq!(expand("a\tb", &TabCfg::new(4).with_symbol("")), "a   b");
+    }
+
+    #[test]
+    fn test_expand_marking_symbols() {
+        let tab_cfg = TabCfg::new(4).with_symbol("→");
+        assert_eq!(
+            expand_marking_symbols("a\t→\tb", &tab_cfg),
+            ("a→  →→  b".to_string(), vec![1..4, 9..12])
+        );
+        assert_eq!(
+            expand_marking_symbols("a\tb", &TabCfg::new(4)),
+            ("a   b".to_string(), vec![])
+        );
+        assert_eq!(
+            remove_prefix_and_expand(1, "+\t\tb", &tab_cfg),
+            ("→   →   b".to_string(), vec![0..3, 6..9])
+        );
+    }
+
+    #[test]
+    fn test_indentation_width() {
+        assert_eq!(indentation_width("", 4), 0);
+        assert_eq!(indentation_width("\t", 4), 4);
+        assert_eq!(indentation_width("  \t", 4), 4);
+        assert_eq!(indentation_width("\t  \t ", 4), 9);
+        assert_eq!(indentation_width("    ", 8), 4);
+    }
 }